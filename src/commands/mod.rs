@@ -5,9 +5,24 @@ pub mod workspace;
 pub mod project;
 pub mod models;
 pub mod utils;
-mod utils_branch;
-mod utils_commit;
+pub mod utils_branch;
+pub mod utils_commit;
+pub mod utils_diff;
+pub mod utils_graph;
 mod utils_clean_workspace;
-mod utils_file;
+pub mod utils_conflict;
+pub mod utils_file;
 mod utils_ignore;
-pub mod utils_windows;
+pub mod utils_log;
+pub mod utils_pick;
+pub mod utils_rename;
+mod utils_status;
+pub mod utils_stash;
+pub mod utils_tag;
+pub mod utils_revert;
+pub mod utils_revision;
+pub mod utils_platform;
+#[cfg(windows)]
+mod utils_windows;
+#[cfg(unix)]
+mod utils_unix;
@@ -2,6 +2,12 @@
 //! 
 //! 
 
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::models::StatusEntry;
+
 /// SVN 日志类型
 pub enum SVNLogType {
     /// 默认日志
@@ -15,14 +21,71 @@ pub enum SVNLogType {
     /// - 没有 path 字段
     /// - xml 格式
     /// - --stop-on-copy
-    WsLog,
+    /// - 见 LogQuery
+    WsLog(LogQuery),
 
     /// ws log 完整日志
     /// - 有 message 字段
     /// - 有 path 字段
     /// - xml 格式
     /// - 没有 --stop-on-copy
-    WsLogFull,
+    /// - 见 LogQuery
+    WsLogFull(LogQuery),
+
+    /// 单个版本的日志，用于 ws show
+    /// - 有 message 字段
+    /// - 有 path 字段
+    /// - 包括合并的版本（-g）
+    /// - xml 格式
+    Revision { revision: u64 },
+
+    /// 分支历史，用于解析版本表达式
+    /// - 只有 revision 字段
+    /// - xml 格式
+    /// - 从 start 向前，最多 limit 条
+    History { start: String, limit: u64 },
+
+    /// 一段版本的日志，用于 ws revert --commit
+    /// - 有 message 字段
+    /// - 没有 path 字段
+    /// - xml 格式
+    /// - 从 start 到 end，包括两端
+    Range { start: u64, end: u64 },
+
+    /// 复制之后的历史，用于读取标签的来源
+    /// - 没有 message 字段
+    /// - 有 path 字段
+    /// - xml 格式
+    /// - --stop-on-copy
+    SinceCopy,
+}
+
+/// 交给 svn log 的过滤条件，其余条件在读取日志后检查
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// 最多 limit 条
+    pub limit: Option<u32>,
+    /// 从该版本向前读取，默认从 HEAD 开始；读取改名前的分支时必须指定
+    pub start: Option<u64>,
+    /// 只读取该时间（svn 日期格式）之后的日志
+    pub since: Option<String>,
+    /// 只包括这些路径（相对于项目根目录）下的更改
+    pub paths: Vec<String>,
+}
+
+/// ws log 的过滤条件，原样保存命令行参数
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// 作者，不区分大小写的子串匹配
+    pub author: Option<String>,
+    /// 起止时间，见 parse_date_arg
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// 匹配提交信息的正则表达式
+    pub grep: Option<String>,
+    /// 最多显示的条数，不指定时使用配置的 log_limit（--all 时不限制）
+    pub limit: Option<u32>,
+    pub paths: Vec<String>,
 }
 
 /// 项目状态
@@ -45,6 +108,26 @@ pub struct ConflictItem {
     pub kind: ConflictKind,
 }
 
+/// 内容冲突的三个版本，svn 保存在冲突文件旁边
+/// - update 时为 .rOLD、.mine、.rNEW，merge 时为 .merge-left.rN、.working、.merge-right.rN
+/// - 二进制文件没有单独的 .mine，本地版本就是工作文件
+pub struct ConflictFiles {
+    pub base: PathBuf,
+    pub mine: PathBuf,
+    pub theirs: PathBuf,
+}
+
+/// 冲突规则给出的解决方式，见 utils_conflict::CONFLICT_RULES_FILE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// 保留本地版本
+    Mine,
+    /// 使用仓库版本
+    Theirs,
+    /// 先使用仓库版本，再在项目根目录运行命令重新生成文件
+    Command(String),
+}
+
 /// 冲突类型
 pub enum ConflictKind {
     Standard,
@@ -54,8 +137,68 @@ pub enum ConflictKind {
 }
 
 /// 分支信息
+#[derive(Serialize)]
 pub struct BranchInfo {
+    #[serde(rename = "name")]
     pub branch_name: String,
+    #[serde(rename = "is_current")]
     pub is_current_branch: bool, // 是否是当前工作区正在用的分支
+    /// 可以合并到 trunk 的提交数，ws list --compare 时才读取
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ahead: Option<usize>,
+    /// trunk 中可以合并过来的提交数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behind: Option<usize>,
+}
+
+/// 标签信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    /// 复制来源的分支，来源不是 trunk 或分支时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_revision: Option<u64>,
+    /// 创建标签的版本和 svn 记录的 ISO 8601 时间
+    pub revision: u64,
+    pub date: String,
+    /// ws revert 创建的 rollback-* 锚点
+    pub is_rollback: bool,
+    /// 是否是当前工作区正在查看的标签
+    pub is_current: bool,
 }
 
+/// ws stash 保存的一组更改，保存在 .ws_store/{repo_name}/.stash/{project_name}/{id}/stash.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashInfo {
+    /// 在列表中的位置，0 为最新，读取列表时填写
+    #[serde(skip_deserializing)]
+    pub index: usize,
+    pub message: String,
+    /// 保存时所在的分支和版本
+    pub branch: String,
+    pub revision: u64,
+    /// ISO 8601 时间
+    pub date: String,
+    /// 由 ensure_clean_workspace 保存，ws switch 回到同一个分支时自动恢复
+    pub auto_pop: bool,
+    /// 保存的更改，路径使用 / 分隔
+    pub entries: Vec<StatusEntry>,
+    /// 属性有修改的路径
+    #[serde(default)]
+    pub properties: Vec<StashedProperties>,
+    /// 新增（svn add）的目录，恢复时重新添加
+    #[serde(default)]
+    pub directories: Vec<String>,
+}
+
+/// stash 中一个路径的属性，svn:ignore、svn:externals、svn:mergeinfo 等
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StashedProperties {
+    pub path: String,
+    /// 保存时 BASE 中的属性，新增的路径为空
+    pub base: BTreeMap<String, String>,
+    /// 保存时工作副本中的属性
+    pub working: BTreeMap<String, String>,
+}
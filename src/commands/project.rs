@@ -5,9 +5,13 @@
 //! 包括指令：
 //! 
 //! - log: 查看项目日志
+//! - status: 查看提交时会处理的更改
+//! - diff: 查看更改的内容
+//! - show: 查看一个版本的详细信息
 //! - review: 查看一个版本
 //! - revert: 将项目还原到某个版本
 //! - commit: 提交当前项目的更改
+//! - tag: 创建、查看和删除标签
 //! 
 
 
@@ -17,125 +21,206 @@ use chrono::Local;
 // use colored::Colorize;
 use crossterm::style::Stylize;
 
-use crate::{commands::{models::{CommitResult, SVNLogType}, utils::{callback_for_log_xml, check_url_exists, format_relative_time, get_copy_source_rev, validate_folder_name}, utils_branch::{create_and_commit_to_branch, create_and_switch_to_branch, extract_branch_name_from_path, get_branch_source}, utils_clean_workspace::ensure_clean_workspace, utils_commit::{commit_with_conflict_resolution, resolve_conflicts}, utils_windows::refresh_explorer_view, workspace::handle_switch}, core::{app::App, error::{AppError, AppResult}, svn::{svn_copy, svn_delete, svn_merge, svn_revert, svn_switch, svn_update}, utils::parse_revision_arg}, ui::models::LogEntry};
+use crate::{commands::{models::{CommitResult, LogFilter, LogQuery, SVNLogType}, utils::{callback_for_log_xml, check_url_exists, validate_folder_name}, utils_branch::{compare_branches, create_and_commit_to_branch, create_and_switch_to_branch, get_branch_renames, get_project_branch_url}, utils_clean_workspace::ensure_clean_workspace, utils_commit::{commit_selected_with_conflict_resolution, commit_with_conflict_resolution, resolve_conflicts, select_changes, update_and_resolve_conflicts}, utils_diff::{DiffTarget, build_diff, build_revision_diff}, utils_graph::build_log_graph, utils_log::{LogMatcher, classify_log_entry, parse_log_entry}, utils_pick::{pick_message, resolve_picked_revisions}, utils_platform::refresh_explorer_view, utils_revert::{normalize_revert_paths, resolve_undo_revisions, restore_paths, rollback_paths_message, undo_message, undo_revisions}, utils_revision::resolve_revision, utils_stash::{drop_stash, get_stash, list_stashes, pop_stash, push_stash}, utils_status::{collect_status_entries, current_ignore_matcher}, utils_tag::{add_tag_sources, create_tag, ensure_not_on_tag, get_project_tags, tag_log_entries}, workspace::handle_switch}, core::{app::App, error::{AppError, AppResult}, svn::{svn_copy, svn_delete, svn_info, svn_merge, svn_move, svn_revert, svn_switch, svn_update}, utils::{Revision, parse_revision_expr, parse_revision_range}}, ui::{models::{ChangedPath, LogEntry, LogEntryKind, RevisionDetail, WorkspaceStatus}, policy::{PromptChoice, PromptKind}}};
 
 /// 查看项目的提交历史
-pub fn handle_log(app: &App, all: bool) -> AppResult<()> {
+pub fn handle_log(app: &App, all: bool, filter: &LogFilter) -> AppResult<()> {
+    let log_vec = build_log_entries(app, all, filter)?;
+    app.ui.show_log(log_vec);
+    Ok(())
+}
+
+/// 以分支图的形式查看项目所有分支的提交历史
+pub fn handle_log_graph(app: &App, limit: Option<u32>) -> AppResult<()> {
+    let limit = limit.or(Some(app.svn_ctx.get_config().log_limit));
+    let commits = build_log_graph(app, limit)?;
+    app.ui.show_log_graph(commits);
+    Ok(())
+}
+
+/// 读取项目的提交历史，并按 [WS-*] 约定识别每条日志的种类
+/// - 识别种类之后再按 filter 过滤
+pub fn build_log_entries(app: &App, all: bool, filter: &LogFilter) -> AppResult<Vec<LogEntry>> {
+    let matcher = LogMatcher::new(filter, Local::now())?;
+    let limit = filter.limit.or((!all).then(|| app.svn_ctx.get_config().log_limit));
+
     let mut log_vec = Vec::new();
     let current_rev = app.svn_ctx.get_current_revision(); 
     let current_branch = app.svn_ctx.get_current_branch_name()?;
-    let callback = |xml_doc: &roxmltree::Document| -> AppResult<()> {
+    let mut callback = |xml_doc: &roxmltree::Document| -> AppResult<()> {
         for entry in xml_doc.descendants().filter(|n| n.has_tag_name("logentry")) {
             if entry.parent().map(|p| p.has_tag_name("logentry")).unwrap_or(false) {
                 continue;
             }
 
-            let revision = entry.attribute("revision").unwrap_or("0");
-            
-            let date_str = entry.descendants().find(|n| n.has_tag_name("date"))
-                .map(|n| n.text().unwrap_or("")).unwrap_or("");
-            
-            let msg = entry.descendants().find(|n| n.has_tag_name("msg"))
-                .map(|n| n.text().unwrap_or("")).unwrap_or("");
-
-            let message;
-            let is_rollback;
-
-            let mut merge_source = None;
-            for child in  entry.children().filter(|n| n.has_tag_name("logentry")) {
-                if let Some(paths_node) = child.children().find(|n| n.has_tag_name("paths")) {
-                    for path_node in paths_node.children().filter(|n| n.has_tag_name("path")) {
-                        let path_text = path_node.text().unwrap_or("");
-                        if let Ok(branch) = extract_branch_name_from_path(app, path_text) {
-                            // 只要找到一个特征，就认定为来源（排除自己）
-                            if branch != current_branch {
-                                merge_source = Some(branch);
-                                break;
-                            }
-                        }
-                    }
-                }
-                if merge_source.is_some() { break; }
-            }
+            let Some(log_entry) = parse_log_entry(app, entry, &current_branch, Some(current_rev))? else { continue };
 
-            if msg.starts_with("[WS-ROLLBACK]") {
-                let tag_path = msg.trim_start_matches("[WS-ROLLBACK] ").trim();
-                // Assuming get_copy_source_rev is refactored
-                let real_rev = get_copy_source_rev(app, tag_path)?;
-                message = format!("{} {}", "↩ Reverted from".dark_yellow(), real_rev.yellow().bold());
-                is_rollback = true;
-            } 
-            else if msg.starts_with("[WS-BRANCH]") {
-                // [WS-BRANCH] Create {}
-                let branch_name = msg.trim_start_matches("[WS-BRANCH] Create ");
-                let branch_info = get_branch_source(app, branch_name)?;
-                message = format!("{} {}", "⎇ Branch created from".dark_green(), branch_info.green().bold());
-                is_rollback = false;
-            }
-            else if msg.starts_with("[WS-INIT]") {
-                // 直接忽略初始化的提交
-                continue;
-            }
-            else if msg.starts_with("[WS-INIT-GITIGNORE]") {
-                // 初始化 .gitignore 的提交，作为仓库最初的提交，显示为 Init
-                message = format!("Init {}", app.svn_ctx.get_current_project_name().yellow().bold());
-                is_rollback = false;
-            }
-            else if msg.starts_with("[WS-RESOLV-GITIGNORE]") {
-                // 处理 .gitignore externals 的提交，直接忽略
-                continue;
+            if matcher.matches(&log_entry) {
+                log_vec.push(log_entry);
             }
-            else
-            
-            if let Some(source_branch) = &merge_source {
-                message = format!("{} {}", "⇄ Merged from".dark_cyan(), source_branch.clone().cyan().bold());
-                is_rollback = false;
-            }
-            else {
-                message = msg.to_string().yellow().to_string();
-                is_rollback = false;
-            }
-
-            let parsed_rev = parse_revision_arg(revision)?;
-            let (revision_str, is_current) =
-                if parsed_rev == *current_rev {
-                    (format!("> r{}", revision), true)
-                } else {
-                    (format!("  r{}", revision), false)
-                };
-            
-            let log_entry = LogEntry {
-                revision: revision_str,
-                date: format_relative_time(date_str),
-                message: message,
-                is_rollback: is_rollback,
-                is_current: is_current,
-            };
-
-            log_vec.push(log_entry);
         }
         Ok(())
     };
 
+    let query = matcher.query(filter, limit);
+    let work_copy_root = app.svn_ctx.get_current_work_copy_root()?;
     if all {
-        callback_for_log_xml(&app.svn_ctx.get_current_work_copy_root()?, SVNLogType::WsLogFull, callback)?;
+        callback_for_log_xml(app.svn(), &work_copy_root, SVNLogType::WsLogFull(query), callback)?;
     }
     else {
-        callback_for_log_xml(&app.svn_ctx.get_current_work_copy_root()?, SVNLogType::WsLog, callback)?;
+        callback_for_log_xml(app.svn(), &work_copy_root, SVNLogType::WsLog(query.clone()), &mut callback)?;
+        // --stop-on-copy 停在分支改名的移动上，接着读取改名前的分支
+        let renames = if work_copy_root == app.svn_ctx.get_branch_url(&current_branch) { get_branch_renames(app, &current_branch)? } else { Vec::new() };
+        // 改名早于 --since 时旧名称上没有要显示的日志，而且 last_rev:{since} 会越过 peg 版本
+        let since_rev = match &query.since {
+            Some(since) if !renames.is_empty() => Some(get_date_revision(app, since)?),
+            _ => None,
+        };
+        for (old_name, last_rev) in renames.into_iter().take_while(|(_, last_rev)| since_rev.is_none_or(|since_rev| *last_rev >= since_rev)) {
+            let old_url = format!("{}@{}", app.svn_ctx.get_branch_url(&old_name), last_rev);
+            callback_for_log_xml(app.svn(), &old_url, SVNLogType::WsLog(LogQuery { start: Some(last_rev), ..query.clone() }), &mut callback)?;
+        }
     }
-    app.ui.show_log(log_vec);
+
+    if let Some(limit) = limit {
+        log_vec.truncate(limit as usize);
+    }
+
+    // 标签显示在它指向的提交上，不包括 rollback-* 锚点
+    tag_log_entries(app, &mut log_vec, &current_branch)?;
+    Ok(log_vec)
+}
+
+/// 某个时间点仓库的版本，即该时间之前最近的版本
+fn get_date_revision(app: &App, date: &str) -> AppResult<u64> {
+    let revision = svn_info(app.svn(), &["--show-item", "revision", "-r", &format!("{{{}}}", date), app.svn_ctx.get_repo_root_url()])?;
+    revision.parse().map_err(|_| AppError::RevisionParse(format!("Unexpected revision {} for {}", revision, date)))
+}
+
+/// 查看一个版本的详细信息
+pub fn handle_show(app: &App, revision_str: &str) -> AppResult<()> {
+    let detail = build_revision_detail(app, revision_str)?;
+    app.ui.show_revision(&detail);
     Ok(())
 }
 
+/// 读取一个版本的作者、时间、提交信息、更改的路径和 diff
+/// - 与 ws log 一样按 [WS-*] 约定识别种类
+pub fn build_revision_detail(app: &App, revision_str: &str) -> AppResult<RevisionDetail> {
+    app.ui.update_step("Parsing target revision");
+    let target = resolve_revision(app, &parse_revision_expr(revision_str)?)?;
+    let is_current = target.is_current_branch && Revision::Number(target.revision) == *app.svn_ctx.get_current_revision();
+
+    app.ui.update_step(&format!("Reading revision r{}", target.revision));
+    let callback = |xml_doc: &roxmltree::Document| -> AppResult<Option<RevisionDetail>> {
+        let Some(entry) = xml_doc.root_element().children().find(|n| n.has_tag_name("logentry")) else { return Ok(None) };
+        let text_of = |tag: &str| entry.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text()).unwrap_or("").to_string();
+
+        let paths = entry.children()
+            .filter(|n| n.has_tag_name("paths"))
+            .flat_map(|n| n.children().filter(|p| p.has_tag_name("path")))
+            .map(|p| ChangedPath {
+                action: p.attribute("action").unwrap_or("M").to_string(),
+                path: p.text().unwrap_or("").to_string(),
+                copy_from: p.attribute("copyfrom-path").map(str::to_string),
+                copy_from_revision: p.attribute("copyfrom-rev").and_then(|r| r.parse().ok()),
+            })
+            .collect();
+
+        // 合并提交中嵌套的 logentry 是被合并的版本
+        let merged_revisions = entry.children()
+            .filter(|n| n.has_tag_name("logentry"))
+            .filter_map(|n| n.attribute("revision")?.parse().ok())
+            .collect();
+
+        // [WS-INIT] 等内部提交在 ws log 中不显示，这里作为普通提交显示
+        let kind = classify_log_entry(app, entry, &target.branch)?.unwrap_or(LogEntryKind::Commit);
+
+        Ok(Some(RevisionDetail {
+            entry: LogEntry {
+                revision: target.revision,
+                date: text_of("date"),
+                author: text_of("author"),
+                message: text_of("msg"),
+                kind,
+                is_current,
+                tags: Vec::new(),
+            },
+            merged_revisions,
+            paths,
+            diff: Vec::new(),
+        }))
+    };
+
+    let mut detail = callback_for_log_xml(app.svn(), &target.url_with_rev(), SVNLogType::Revision { revision: target.revision }, callback)?
+        .ok_or_else(|| AppError::Validation(format!("Revision {} did not change branch {}", format!("r{}", target.revision).yellow().bold(), target.branch.as_str().yellow().bold())))?;
+
+    app.ui.update_step("Computing differences");
+    detail.diff = build_revision_diff(app, &target)?;
+    Ok(detail)
+}
+
+/// 查看工作区状态，与提交时的处理方式一致
+/// - 只读取状态，不会同步 .gitignore 或修改工作副本
+pub fn handle_status(app: &App) -> AppResult<()> {
+    let status = build_workspace_status(app)?;
+    app.ui.show_status(&status);
+    Ok(())
+}
+
+/// 查看更改
+/// - 不指定 revision 和 branch 时比较工作副本与 BASE
+/// - revision 为 "A" 时比较版本 A 与工作副本，为 "A:B" 时比较两个版本，A、B 为版本表达式
+/// - branch 为同一项目的另一个分支，比较当前分支与该分支
+pub fn handle_diff(app: &App, revision: Option<&str>, branch: Option<&str>, stat: bool, paths: &[String]) -> AppResult<()> {
+    let target = match (revision, branch) {
+        (_, Some(branch_name)) => DiffTarget::Branch(branch_name.to_string()),
+        (Some(range), None) => {
+            app.ui.update_step("Parsing revisions");
+            let (from, to) = parse_revision_range(range)?;
+            DiffTarget::Revisions { from, to }
+        }
+        (None, None) => DiffTarget::WorkingCopy,
+    };
+
+    app.ui.update_step("Computing differences");
+    let diffs = build_diff(app, &target, paths)?;
+    app.ui.show_diff(&diffs, stat);
+    Ok(())
+}
+
+/// 读取当前项目、分支、版本和工作区中的更改
+pub fn build_workspace_status(app: &App) -> AppResult<WorkspaceStatus> {
+    app.ui.update_step("Reading workspace status");
+    let entries = collect_status_entries(app.svn(), &current_ignore_matcher()?)?;
+
+    let revision_number = |rev: &Revision| match rev {
+        Revision::Number(n) => *n,
+        Revision::Head => 0,
+    };
+
+    Ok(WorkspaceStatus {
+        project: app.svn_ctx.get_current_project_name().to_string(),
+        branch: app.svn_ctx.get_current_branch_name()?,
+        revision: revision_number(app.svn_ctx.get_current_revision()),
+        latest_revision: revision_number(app.svn_ctx.get_latest_revision()),
+        is_review: app.svn_ctx.check_review_state(),
+        entries,
+    })
+}
+
 /// 查看项目的某个版本
+/// - revision_str 为版本表达式，见 RevisionExpr
+/// - 指定其他分支时切换到该分支的这个版本
 pub fn handle_review(app: &App, revision_str: &str) -> AppResult<()> {
     app.ui.update_step("Parsing target revision");
-    let target_rev = parse_revision_arg(&revision_str)?;
+    let target = resolve_revision(app, &parse_revision_expr(revision_str)?)?;
+    let target_rev = Revision::Number(target.revision);
 
     app.ui.update_step("Fetching latest revision");
     let latest_rev = app.svn_ctx.get_latest_revision();
 
-    if target_rev > *latest_rev {
+    if target.is_current_branch && target_rev > *latest_rev {
         // This is a validation error, we can create a specific error for it if we want.
         let msg = format!("Target revision {} is not latest revision {}, cannot review", target_rev.to_string().yellow().bold(), latest_rev.to_string().yellow().bold());
         return Err(AppError::RevisionParse(msg)); 
@@ -145,7 +230,12 @@ pub fn handle_review(app: &App, revision_str: &str) -> AppResult<()> {
     ensure_clean_workspace(app)?;
     
     app.ui.update_step(&format!("Updating to revision {}", target_rev));
-    match svn_update(&["-r", &target_rev.to_string()]) {
+    let result = if target.is_current_branch {
+        svn_update(app.svn(), &["-r", &target_rev.to_string()])
+    } else {
+        svn_switch(app.svn(), &target.url_with_rev())
+    };
+    match result {
         Ok(_) => {}
         Err(e) => {
             match e {
@@ -166,8 +256,18 @@ pub fn handle_review(app: &App, revision_str: &str) -> AppResult<()> {
 }
 
 /// 将当前项目还原到某个历史版本
-pub fn handle_revert(app: &App, revision_str: &str) -> AppResult<()> {
-    let target_rev = parse_revision_arg(&revision_str)?;
+/// - revision_str 为版本表达式，只能是当前分支的版本
+/// - paths 不为空时只还原这些路径，见 revert_paths
+pub fn handle_revert(app: &App, revision_str: &str, paths: &[String]) -> AppResult<()> {
+    ensure_not_on_tag(app)?;
+    app.ui.update_step("Parsing target revision");
+    let target = resolve_revision(app, &parse_revision_expr(revision_str)?)?;
+    if !target.is_current_branch {
+        return Err(AppError::Validation(format!("Cannot revert to branch {}, switch to it or use ws pull instead", target.branch.yellow().bold())));
+    }
+    let target_rev = Revision::Number(target.revision);
+    // 路径不合法时直接报错，不能先处理工作区中的更改
+    let paths = normalize_revert_paths(paths)?;
 
     // 1. Check target revision
     app.ui.update_step("Checking latest revision");
@@ -183,6 +283,10 @@ pub fn handle_revert(app: &App, revision_str: &str) -> AppResult<()> {
     app.ui.update_step("Auto save before revert");
     ensure_clean_workspace(app)?;
 
+    if !paths.is_empty() {
+        return revert_paths(app, &paths, target.revision);
+    }
+
     // 3. Copy to tags to set a snapshot
     app.ui.update_step("Creating snapshot tag before revert");
     let timestamp = Local::now().format("%Y%m%d-%H%M%S");
@@ -190,15 +294,15 @@ pub fn handle_revert(app: &App, revision_str: &str) -> AppResult<()> {
     let tag_url = format!("{}/tags/{}", app.svn_ctx.get_current_project_repo_root_url(), tag_name);
     let current_url_with_rev = format!("{}@{}", app.svn_ctx.get_current_work_copy_root()?, target_rev);
 
-    svn_copy(&[&current_url_with_rev, &tag_url, "-m", &format!("[WS-REVERT] Anchor for revert: {}", tag_name), "--parents"])?;
+    svn_copy(app.svn(), &[&current_url_with_rev, &tag_url, "-m", &format!("[WS-REVERT] Anchor for revert: {}", tag_name), "--parents"])?;
 
     // 4. Revert to target revision
     app.ui.update_step(&format!("Reverting to revision {}", target_rev));
     let merge_range = format!("HEAD:{}", target_rev);
 
-    if let Err(e) = svn_merge(&["-r", &merge_range, "."]) {
+    if let Err(e) = svn_merge(app.svn(), &["-r", &merge_range, "."]) {
         app.ui.warn("Status is not successful during SVN merge, trying to recover changes...");
-        svn_revert(&["-R", "."])?;
+        svn_revert(app.svn(), &["-R", "."])?;
         app.ui.warn("Revert failed and local changes have been recovered.");
         return Err(e); // Propagate the original merge error
     }
@@ -211,11 +315,65 @@ pub fn handle_revert(app: &App, revision_str: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// 撤销当前分支上的一些提交，保留之后的提交
+/// - revisions 为逗号分隔的版本表达式或 A:B 范围，见 resolve_undo_revisions
+pub fn handle_revert_commits(app: &App, revisions: &str) -> AppResult<()> {
+    ensure_not_on_tag(app)?;
+    app.ui.update_step("Reading commits to undo");
+    let undone = resolve_undo_revisions(app, revisions)?;
+    let revision_list = undone.iter().map(|(revision, _)| format!("r{}", revision)).collect::<Vec<_>>().join(", ");
+
+    // clean workspace
+    app.ui.update_step("Ensuring clean workspace");
+    ensure_clean_workspace(app)?;
+    update_and_resolve_conflicts(app)?;
+
+    // reverse merge
+    app.ui.update_step(&format!("Undoing {}", revision_list));
+    let branch_url = app.svn_ctx.get_current_work_copy_root()?;
+    let numbers: Vec<u64> = undone.iter().map(|(revision, _)| *revision).collect();
+    undo_revisions(app, &branch_url, &numbers)?;
+
+    // resolve conflicts
+    app.ui.update_step("Resolving conflicts");
+    resolve_conflicts(app)?;
+
+    app.ui.update_step("Committing the undo");
+    match commit_with_conflict_resolution(app, &undo_message(&undone))? {
+        CommitResult::NoChanges => app.ui.success(&format!("{} already undone", revision_list)),
+        CommitResult::Success => app.ui.success(&format!("Undid {}", revision_list.yellow().bold())),
+    };
+    Ok(())
+}
+
+/// 只把 paths 还原到 revision 时的内容，包括之后删除的路径，不创建锚点标签
+fn revert_paths(app: &App, paths: &[String], revision: u64) -> AppResult<()> {
+    app.ui.update_step("Updating to latest revision");
+    update_and_resolve_conflicts(app)?;
+
+    app.ui.update_step(&format!("Restoring {} path(s) from r{}", paths.len(), revision));
+    let branch_url = app.svn_ctx.get_current_work_copy_root()?;
+    if let Err(e) = restore_paths(app, &branch_url, paths, revision) {
+        svn_revert(app.svn(), &["-R", "."])?;
+        app.ui.warn("Revert failed and local changes have been recovered.");
+        return Err(e);
+    }
+    resolve_conflicts(app)?;
+
+    app.ui.update_step("Committing the revert changes");
+    match commit_with_conflict_resolution(app, &rollback_paths_message(paths, revision))? {
+        CommitResult::NoChanges => app.ui.success(&format!("{} already the same as r{}", paths.join(", "), revision)),
+        CommitResult::Success => app.ui.success(&format!("Reverted {} from r{}", paths.join(", ").yellow().bold(), revision)),
+    };
+    Ok(())
+}
+
 /// 提交当前项目的更改
 pub fn handle_commit(app: &App, commit_message: &Option<String>) -> AppResult<()> {
     app.ui.update_step("Committing changes to SVN");
+    ensure_not_on_tag(app)?;
 
-    if app.svn_ctx.is_dirty()? == false {
+    if !app.svn_ctx.is_dirty()? {
         app.ui.success("No changes need to commit");
         return Ok(());
     }
@@ -225,10 +383,10 @@ pub fn handle_commit(app: &App, commit_message: &Option<String>) -> AppResult<()
     let final_commit_message;
     if is_review {
         app.ui.warn("Current project is not at the latest revision.");
-        let selection = app.ui.selector("Continue to commit?", vec![
-            "Continue to commit (may get conflicts!)",
-            "Create a new branch and commit there (no conflicts)",
-            "No, cancel operation",
+        let selection = app.ui.selector(PromptKind::OutdatedCommit, "Continue to commit?", vec![
+            (PromptChoice::Commit, "Continue to commit (may get conflicts!)"),
+            (PromptChoice::Branch, "Create a new branch and commit there (no conflicts)"),
+            (PromptChoice::Other, "No, cancel operation"),
         ])?;
 
         match selection {
@@ -274,15 +432,52 @@ pub fn handle_commit(app: &App, commit_message: &Option<String>) -> AppResult<()
     Ok(())
 }
 
+/// 只提交指定路径下的更改，或交互地选择文件和修改块
+/// - 未选中的更改保留在工作区
+pub fn handle_commit_selected(app: &App, commit_message: &Option<String>, paths: &[String], interactive: bool) -> AppResult<()> {
+    app.ui.update_step("Committing selected changes to SVN");
+    ensure_not_on_tag(app)?;
+
+    if app.svn_ctx.check_review_state() {
+        return Err(AppError::Validation("Cannot commit selected changes while reviewing an older revision. Run commit without paths to choose how to continue".to_string()));
+    }
+
+    // 先 update，选择的修改块以最新的版本为基础
+    app.ui.update_step("Updating working copy");
+    update_and_resolve_conflicts(app)?;
+
+    let changes = select_changes(app, paths, interactive)?;
+    if changes.is_empty() {
+        app.ui.success("No changes need to commit");
+        return Ok(());
+    }
+
+    let final_commit_message = if let Some(msg) = commit_message && !msg.trim().is_empty() {
+        msg.clone()
+    } else {
+        app.ui.input_commit_message()?
+    };
+
+    app.ui.update_step("Committing changes");
+    let result = commit_selected_with_conflict_resolution(app, &final_commit_message, &changes)?;
+
+    match result {
+        CommitResult::NoChanges => app.ui.success("No changes to commit"),
+        CommitResult::Success => app.ui.success(&format!("{} change(s) committed successfully. Commit message: {}", changes.len(), final_commit_message.yellow().bold())),
+    };
+
+    Ok(())
+}
+
 /// 创建新分支并切换过去，或者删除分支
-pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, is_delete: bool, is_restore: bool) -> AppResult<()> {
+pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, is_delete: bool, is_restore: bool, revision: Option<&str>) -> AppResult<()> {
     if let Some(branch_name) = new_branch_name {
         validate_folder_name(&branch_name, false)?;
         if is_restore {
             // check branch name
             app.ui.update_step("checking branch");
             let branch_url = app.svn_ctx.get_branch_url(&branch_name);
-            match check_url_exists(&branch_url) {
+            match check_url_exists(app.svn(), &branch_url) {
                 Ok(exists) => {
                     if exists {
                         app.ui.warn(&format!("Branch {} already exists", branch_name.yellow().bold()));
@@ -303,11 +498,10 @@ pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, i
                         for path_node in paths.children().filter(|n| n.has_tag_name("path")) {
                             if path_node.attribute("action") == Some("D") {
                                 let path_txt = path_node.text().unwrap_or("");
-                                if path_txt.ends_with(&target_suffix) {
-                                    if let Some(rev_str) = entry.attribute("revision") {
+                                if path_txt.ends_with(&target_suffix)
+                                    && let Some(rev_str) = entry.attribute("revision") {
                                         return Ok(rev_str.parse::<u64>().unwrap_or(0));
                                     }
-                                }
                             }
                         }
                     }
@@ -316,7 +510,7 @@ pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, i
                 Ok(0)
             };
 
-            let deleted_rev = callback_for_log_xml(&app.svn_ctx.get_current_branches_url(), SVNLogType::Default, callback)?;
+            let deleted_rev = callback_for_log_xml(app.svn(), &app.svn_ctx.get_current_branches_url(), SVNLogType::Default, callback)?;
             if deleted_rev == 0 {
                 app.ui.warn(&format!("No deleted branch named {} found in history", branch_name.yellow().bold()));
                 return Ok(());
@@ -326,10 +520,10 @@ pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, i
             app.ui.update_step("Restoring Branch");
             let restore_rev = deleted_rev - 1;
             let source_url = format!("{}@{}", branch_url, restore_rev);
-            svn_copy(&[&source_url, &branch_url, "-m", &format!("Restore branch {}", branch_name)])?;
+            svn_copy(app.svn(), &[&source_url, &branch_url, "-m", &app.svn_ctx.get_config().messages.restore_branch_message(&branch_name)])?;
             
             app.ui.success(&format!("Branch {} restored successfully", branch_name.clone().yellow().bold()));
-            let switch = app.ui.selector_yes_or_no("Switch to the restored branch?")?;
+            let switch = app.ui.selector_yes_or_no(PromptKind::Confirm, "Switch to the restored branch?")?;
             if switch {
                 app.ui.update_step(&format!("Switching to branch {}", branch_name.clone().yellow().bold()));
                 handle_switch(app, None, Some(branch_name))?;
@@ -349,7 +543,7 @@ pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, i
             }
 
             let branch_url = app.svn_ctx.get_branch_url(&branch_name);
-            match check_url_exists(&branch_url) {
+            match check_url_exists(app.svn(), &branch_url) {
                 Ok(exists) => {
                     if !exists {
                         app.ui.warn(&format!("Branch {} does not exist", branch_name.yellow().bold()));
@@ -365,18 +559,113 @@ pub fn handle_branch(app: &App, new_branch_name: Option<String>, is_new: bool, i
             // delete branch
             app.ui.update_step("Deleting Branch");
             let delete_message = format!("[WS-BRANCH-DELETE] Delete {}", branch_name);
-            svn_delete(&[&branch_url, "-m", &delete_message])?;
+            svn_delete(app.svn(), &[&branch_url, "-m", &delete_message])?;
             app.ui.success(&format!("Branch {} deleted successfully", branch_name.yellow().bold()));
             return Ok(());
         } 
         else if is_new {
+            let from = match revision {
+                Some(revision_str) => {
+                    app.ui.update_step("Parsing source revision");
+                    Some(resolve_revision(app, &parse_revision_expr(revision_str)?)?)
+                }
+                None => None,
+            };
             app.ui.update_step(&format!("Creating and switching to branch {}", branch_name.clone().yellow().bold()));
-            create_and_switch_to_branch(app, &branch_name)?;
+            create_and_switch_to_branch(app, &branch_name, from.as_ref())?;
             app.ui.success(&format!("Now on branch {}", branch_name.yellow().bold()));
         }
     } else {
-        handle_log(app, false)?;
+        handle_log(app, false, &LogFilter::default())?;
+    }
+    Ok(())
+}
+
+/// 重命名分支，服务器端移动 branches/ 下的目录，保留分支的历史
+/// - 工作副本在被改名的分支上时切换到新的 URL，本地的更改保持不变
+pub fn handle_branch_rename(app: &App, old_name: &str, new_name: &str) -> AppResult<()> {
+    app.ui.update_step("Checking branch");
+    validate_folder_name(old_name, false)?;
+    validate_folder_name(new_name, false)?;
+    if old_name == "trunk" || new_name == "trunk" {
+        return Err(AppError::Validation("Cannot rename trunk, or rename a branch to trunk".to_string()));
+    }
+
+    let old_url = app.svn_ctx.get_branch_url(old_name);
+    let new_url = app.svn_ctx.get_branch_url(new_name);
+    if !check_url_exists(app.svn(), &old_url)? {
+        return Err(AppError::Validation(format!("Branch {} does not exist", old_name.yellow().bold())));
+    }
+    if check_url_exists(app.svn(), &new_url)? {
+        return Err(AppError::Validation(format!("Branch {} already exists", new_name.yellow().bold())));
+    }
+
+    app.ui.update_step("Renaming branch");
+    let is_current = app.svn_ctx.get_current_branch_name()? == old_name;
+    svn_move(app.svn(), &[&old_url, &new_url, "-m", &format!("[WS-BRANCH-RENAME] Rename {} to {}", old_name, new_name)])?;
+
+    if is_current {
+        app.ui.update_step(&format!("Switching to branch {}", new_name.yellow().bold()));
+        svn_switch(app.svn(), &new_url)?;
+    }
+    app.ui.success(&format!("Branch {} renamed to {}", old_name.yellow().bold(), new_name.yellow().bold()));
+    Ok(())
+}
+
+/// 比较两个分支，branch 为 None 时与当前分支比较
+pub fn handle_branch_compare(app: &App, base: &str, branch: Option<&str>) -> AppResult<()> {
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => match app.svn_ctx.get_current_tag_name()? {
+            Some(tag_name) => return Err(AppError::Validation(format!("The workspace is on tag {}, specify both branches to compare", tag_name.yellow().bold()))),
+            None => app.svn_ctx.get_current_branch_name()?,
+        },
+    };
+
+    app.ui.update_step(&format!("Comparing {} with {}", branch.clone().yellow().bold(), base.yellow().bold()));
+    let comparison = compare_branches(app, base, &branch)?;
+    app.ui.show_branch_comparison(&comparison);
+    Ok(())
+}
+
+/// 创建标签，或者删除标签
+/// - 没有指定标签名时列出标签，include_rollback 时包括 ws revert 创建的锚点
+pub fn handle_tag(app: &App, tag_name: Option<String>, revision: Option<&str>, is_delete: bool, include_rollback: bool) -> AppResult<()> {
+    let Some(tag_name) = tag_name else {
+        app.ui.update_step("Reading tags");
+        let mut tags = get_project_tags(app, app.svn_ctx.get_current_project_name(), include_rollback)?;
+        add_tag_sources(app, app.svn_ctx.get_current_project_name(), &mut tags)?;
+        app.ui.show_tags(tags);
+        return Ok(());
+    };
+    validate_folder_name(&tag_name, false)?;
+
+    if is_delete {
+        app.ui.update_step("Checking tag");
+        if app.svn_ctx.get_current_tag_name()?.as_deref() == Some(tag_name.as_str()) {
+            return Err(AppError::Validation(format!("Cannot delete tag {}, the workspace is on it", tag_name.yellow().bold())));
+        }
+        let tag_url = app.svn_ctx.get_tag_url(&tag_name);
+        if !check_url_exists(app.svn(), &tag_url)? {
+            return Err(AppError::Validation(format!("Tag {} does not exist", tag_name.yellow().bold())));
+        }
+
+        app.ui.update_step("Deleting tag");
+        svn_delete(app.svn(), &[&tag_url, "-m", &format!("[WS-TAG-DELETE] Delete {}", tag_name)])?;
+        app.ui.success(&format!("Tag {} deleted successfully", tag_name.yellow().bold()));
+        return Ok(());
     }
+
+    let from = match revision {
+        Some(revision_str) => {
+            app.ui.update_step("Parsing source revision");
+            Some(resolve_revision(app, &parse_revision_expr(revision_str)?)?)
+        }
+        None => None,
+    };
+    app.ui.update_step(&format!("Creating tag {}", tag_name.clone().yellow().bold()));
+    let tagged_rev = create_tag(app, &tag_name, from.as_ref())?;
+    app.ui.success(&format!("Tag {} created at revision {}", tag_name.yellow().bold(), format!("r{}", tagged_rev).yellow().bold()));
     Ok(())
 }
 
@@ -391,10 +680,10 @@ pub fn handle_pull(app: &App, source_arg: Option<&str>) -> AppResult<()> {
         let source_url = if source_name == "trunk" {
             app.svn_ctx.get_current_trunk_url()
         } else {
-            app.svn_ctx.get_branch_url(&source_name)
+            app.svn_ctx.get_branch_url(source_name)
         };
 
-        match check_url_exists(&source_url) {
+        match check_url_exists(app.svn(), &source_url) {
             Ok(exists) => {
                 if !exists {
                     app.ui.warn(&format!("Source branch {} does not exist", source_name.yellow().bold()));
@@ -412,8 +701,8 @@ pub fn handle_pull(app: &App, source_arg: Option<&str>) -> AppResult<()> {
         ensure_clean_workspace(app)?;
 
         // perform merge
-        app.ui.update_step(&format!("Merging changes"));
-        svn_merge(&["--accept", "postpone", &source_url, "."])?;
+        app.ui.update_step("Merging changes");
+        svn_merge(app.svn(), &["--accept", "postpone", &source_url, "."])?;
 
         // resolve conflicts
         app.ui.update_step("Resolving conflicts");
@@ -424,7 +713,7 @@ pub fn handle_pull(app: &App, source_arg: Option<&str>) -> AppResult<()> {
     else { // 没有指定分支，或者指定的分支是当前分支，直接更新到最新版本
         // update to latest
         app.ui.update_step("Updating to latest revision");
-        svn_update(&["--accept", "postpone"])?;
+        svn_update(app.svn(), &["--accept", "postpone"])?;
 
         // resolve conflicts
         app.ui.update_step("Resolving conflicts");
@@ -447,10 +736,10 @@ pub fn handle_push(app: &App, target_arg: Option<&str>) -> AppResult<()> {
         let target_url = if target_name == "trunk" {
             app.svn_ctx.get_current_trunk_url()
         } else {
-            app.svn_ctx.get_branch_url(&target_name)
+            app.svn_ctx.get_branch_url(target_name)
         };
 
-        match check_url_exists(&target_url) {
+        match check_url_exists(app.svn(), &target_url) {
             Ok(exists) => {
                 if !exists {
                     app.ui.warn(&format!("Target branch {} does not exist", target_name.yellow().bold()));
@@ -470,11 +759,11 @@ pub fn handle_push(app: &App, target_arg: Option<&str>) -> AppResult<()> {
         // switch to target branch
         let source_url = app.svn_ctx.get_current_work_copy_root()?;
         app.ui.update_step("Switching to target branch");
-        svn_switch(&target_url)?;
+        svn_switch(app.svn(), &target_url)?;
 
         // perform merge
         app.ui.update_step("Merging changes from source branch");
-        svn_merge(&["--accept", "postpone", &source_url, "."])?;
+        svn_merge(app.svn(), &["--accept", "postpone", &source_url, "."])?;
 
         // resolve conflicts
         app.ui.update_step("Resolving conflicts");
@@ -491,3 +780,85 @@ pub fn handle_push(app: &App, target_arg: Option<&str>) -> AppResult<()> {
     Ok(())
 }
 
+/// 保存工作区的更改并还原工作区，message 为空时使用 WIP on {branch}@r{revision}
+pub fn handle_stash_push(app: &App, message: Option<&str>) -> AppResult<()> {
+    app.ui.update_step("Stashing local changes");
+    match push_stash(app, message.filter(|m| !m.trim().is_empty()), false)? {
+        Some(stash) => app.ui.success(&format!("Stashed {} change(s): {}", stash.entries.len(), stash.message.yellow().bold())),
+        None => app.ui.success("No local changes to stash"),
+    };
+    Ok(())
+}
+
+/// 列出当前项目的 stash
+pub fn handle_stash_list(app: &App) -> AppResult<()> {
+    app.ui.update_step("Reading stashes");
+    let stashes = list_stashes(app, app.svn_ctx.get_current_project_name())?.into_iter().map(|(_, info)| info).collect();
+    app.ui.show_stashes(stashes);
+    Ok(())
+}
+
+/// 显示一个 stash 保存的文件
+pub fn handle_stash_show(app: &App, index: usize) -> AppResult<()> {
+    app.ui.update_step("Reading stash");
+    let (_, stash) = get_stash(app, app.svn_ctx.get_current_project_name(), index)?;
+    app.ui.show_stash(&stash);
+    Ok(())
+}
+
+/// 恢复一个 stash，全部恢复后删除它
+pub fn handle_stash_pop(app: &App, index: usize) -> AppResult<()> {
+    ensure_not_on_tag(app)?;
+    app.ui.update_step("Restoring stash");
+    let project_name = app.svn_ctx.get_current_project_name();
+    let (dir, stash) = get_stash(app, project_name, index)?;
+    if pop_stash(app, project_name, &dir, &stash)?.is_empty() {
+        app.ui.success(&format!("Restored {} change(s): {}", stash.entries.len(), stash.message.yellow().bold()));
+    }
+    Ok(())
+}
+
+/// 删除一个 stash，不恢复其中的更改
+pub fn handle_stash_drop(app: &App, index: usize) -> AppResult<()> {
+    let (dir, stash) = get_stash(app, app.svn_ctx.get_current_project_name(), index)?;
+    drop_stash(&dir)?;
+    app.ui.success(&format!("Dropped stash {}: {}", index, stash.message.yellow().bold()));
+    Ok(())
+}
+
+/// 类似 git cherry-pick 的行为，只把来源分支上选中的版本合并到当前分支并提交
+/// - 提交信息记录来源分支和版本，见 pick_message
+pub fn handle_pick(app: &App, revisions: &str, from: Option<&str>) -> AppResult<()> {
+    ensure_not_on_tag(app)?;
+
+    app.ui.update_step("Reading revisions to pick");
+    let (source, picked) = resolve_picked_revisions(app, revisions, from)?;
+    let revision_list: Vec<String> = picked.iter().map(|p| p.revision.to_string()).collect();
+    // 来源分支之后可能被删除或改名，固定在最后一个版本上
+    let last_revision = picked.last().map(|p| p.revision).unwrap_or(0);
+    let source_url = format!("{}@{}", get_project_branch_url(app, app.svn_ctx.get_current_project_name(), &source), last_revision);
+
+    // clean workspace
+    app.ui.update_step("Ensuring clean workspace");
+    ensure_clean_workspace(app)?;
+    update_and_resolve_conflicts(app)?;
+
+    // perform merge
+    app.ui.update_step("Merging picked revisions");
+    svn_merge(app.svn(), &["--accept", "postpone", "-c", &revision_list.join(","), &source_url, "."])?;
+
+    // resolve conflicts
+    app.ui.update_step("Resolving conflicts");
+    resolve_conflicts(app)?;
+
+    app.ui.update_step("Committing picked revisions");
+    let commit_message = pick_message(&source, &picked);
+    let revisions = picked.iter().map(|p| format!("r{}", p.revision)).collect::<Vec<_>>().join(", ");
+    match commit_with_conflict_resolution(app, &commit_message)? {
+        CommitResult::NoChanges => app.ui.success(&format!("{} from {} already on the current branch", revisions, source.yellow().bold())),
+        CommitResult::Success => app.ui.success(&format!("Picked {} from {}", revisions.yellow().bold(), source.yellow().bold())),
+    };
+
+    Ok(())
+}
+
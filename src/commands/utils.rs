@@ -8,7 +8,7 @@ use chrono::{DateTime, Local};
 use crossterm::style::Stylize;
 use regex::Regex;
 
-use crate::{commands::{models::{ProjectStatus, SVNLogType}, utils_ignore::{auto_sync_ignore_rules, build_folder_walker, build_ignore_matcher}}, core::{app::App, context::SvnContext, error::{AppError, AppResult}, svn::{StatusType, svn_info, svn_log, svn_status}}};
+use crate::{commands::{models::{ProjectStatus, SVNLogType}, utils_ignore::{auto_sync_ignore_rules, build_folder_walker, build_ignore_matcher}}, core::{app::App, error::{AppError, AppResult, SvnErrorKind}, svn::{StatusType, svn_info, svn_log, svn_status}, svn_executor::SvnExecutor}};
 
 /// 格式化相对时间显示
 pub fn format_relative_time(iso_time: &str) -> String {
@@ -36,64 +36,103 @@ pub fn format_relative_time(iso_time: &str) -> String {
     }
 }
 
-/// 获取标签或分支的复制来源版本号
-pub fn get_copy_source_rev(app: &App, tag_rel_path: &str) -> AppResult<String> {
+/// 格式化完整的本地时间，带时区
+pub fn format_full_time(iso_time: &str) -> String {
+    match DateTime::parse_from_rfc3339(iso_time) {
+        Ok(dt) => dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %z").to_string(),
+        Err(_) => iso_time.to_string(),
+    }
+}
+
+/// 获取标签或分支的复制来源版本号，找不到时返回 None
+pub fn get_copy_source_rev(app: &App, tag_rel_path: &str) -> AppResult<Option<u64>> {
     let project_root = app.svn_ctx.get_current_project_repo_root_url();
     let full_tag_url = format!("{}/{}", project_root, tag_rel_path.trim_start_matches('/'));
 
-    if let Ok(xml_str) = svn_log(&["-v", "--xml", "--stop-on-copy", "--limit", "1", &full_tag_url]) {
+    if let Ok(xml_str) = svn_log(app.svn(), &["-v", "--xml", "--stop-on-copy", "--limit", "1", &full_tag_url]) {
         let doc = roxmltree::Document::parse(&xml_str)?;
         for logentry in doc.descendants().filter(|n| n.has_tag_name("logentry")) {
             if let Some(paths) = logentry.children().find(|n| n.has_tag_name("paths")) {
                 for path in paths.children().filter(|n| n.has_tag_name("path")) {
                     if let Some(rev_str) = path.attribute("copyfrom-rev") {
-                        return Ok(format!("{}{}", "r", rev_str));
+                        return Ok(rev_str.parse().ok());
                     }
                 }
             }
         }
     }
 
-    Ok("Unknown Rev".to_string())
+    Ok(None)
 }
 
 /// 回调处理日志 XML 数据
-pub fn callback_for_log_xml<F, T>(url: &str, log_type: SVNLogType, callback: F) -> AppResult<T>
+pub fn callback_for_log_xml<F, T>(svn: &dyn SvnExecutor, url: &str, log_type: SVNLogType, callback: F) -> AppResult<T>
 where F: FnOnce(&roxmltree::Document) -> AppResult<T>
 {
+    let limit_arg;
+    let range_arg;
     let args = match log_type {
         SVNLogType::Default => vec!["-v", "-q", "--xml", url],
-        SVNLogType::WsLog => vec!["-v", "-g", "--xml", "--stop-on-copy", "--limit", "100", url],
-        SVNLogType::WsLogFull => vec!["-v", "-g", "--xml", url],
+        SVNLogType::WsLog(ref query) | SVNLogType::WsLogFull(ref query) => {
+            let mut args = vec!["-v", "-g", "--xml"];
+            if matches!(log_type, SVNLogType::WsLog(_)) {
+                args.push("--stop-on-copy");
+            }
+            if let Some(limit) = query.limit {
+                limit_arg = limit.to_string();
+                args.extend(["--limit", limit_arg.as_str()]);
+            }
+            let start = query.start.map_or("HEAD".to_string(), |start| start.to_string());
+            if let Some(since) = &query.since {
+                range_arg = format!("{}:{{{}}}", start, since);
+                args.extend(["-r", range_arg.as_str()]);
+            } else if query.start.is_some() {
+                range_arg = format!("{}:0", start);
+                args.extend(["-r", range_arg.as_str()]);
+            }
+            args.push(url);
+            args.extend(query.paths.iter().map(String::as_str));
+            args
+        },
+        SVNLogType::Revision { revision } => {
+            range_arg = revision.to_string();
+            vec!["-v", "-g", "--xml", "-r", &range_arg, url]
+        },
+        SVNLogType::History { ref start, limit } => {
+            range_arg = format!("{}:0", start);
+            limit_arg = limit.to_string();
+            vec!["-q", "--xml", "-r", &range_arg, "--limit", &limit_arg, url]
+        },
+        SVNLogType::Range { start, end } => {
+            range_arg = format!("{}:{}", start, end);
+            vec!["--xml", "-r", &range_arg, url]
+        },
+        SVNLogType::SinceCopy => vec!["-v", "-q", "--xml", "--stop-on-copy", url],
     };
 
-    let log_string = svn_log(&args)?;
+    let log_string = svn_log(svn, &args)?;
     // if log_string.is_empty() {
     //     return Ok(());
     // }
 
-    let doc = roxmltree::Document::parse(&log_string.trim())?;
+    let doc = roxmltree::Document::parse(log_string.trim())?;
 
     callback(&doc)
 }
 
 /// 检查指定 URL 是否存在
-pub fn check_url_exists(url: &str) -> AppResult<bool> {
-    match svn_info(&[url]) {
+/// - 只有 svn 明确报告路径不存在时才返回 false，其他失败（认证、网络等）直接返回错误
+pub fn check_url_exists(svn: &dyn SvnExecutor, url: &str) -> AppResult<bool> {
+    match svn_info(svn, &[url]) {
         Ok(info) => Ok(!info.is_empty()),
-        Err(e) => {
-            if let AppError::SvnCommandFailed { .. } = e {
-                Ok(false)
-            } else {
-                Err(e)
-            }
-        }
+        Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => Ok(false),
+        Err(e) => Err(e),
     }
 }
 
 /// 检查指定项目在仓库中的状态
-pub fn check_project_exists(ctx: &SvnContext, target_url: &str, project_name: &str, only_active_check: bool) -> AppResult<ProjectStatus> {
-    if check_url_exists(target_url)? {
+pub fn check_project_exists(app: &App, target_url: &str, project_name: &str, only_active_check: bool) -> AppResult<ProjectStatus> {
+    if check_url_exists(app.svn(), target_url)? {
         return Ok(ProjectStatus::Active);
     }
 
@@ -117,7 +156,7 @@ pub fn check_project_exists(ctx: &SvnContext, target_url: &str, project_name: &s
         Ok(ProjectStatus::NonExistent)
     };
 
-    callback_for_log_xml(ctx.get_repo_root_url(), SVNLogType::Default, callback)
+    callback_for_log_xml(app.svn(), app.svn_ctx.get_repo_root_url(), SVNLogType::Default, callback)
 }
 
 /// 验证文件夹名称是否合法
@@ -155,10 +194,10 @@ pub fn validate_folder_name(name: &str, only_path_check: bool) -> AppResult<()>
 }
 
 /// 使用忽略规则检查工作区是否脏
-pub fn is_workspace_dirty(project_name: &str) -> AppResult<bool> {
+pub fn is_workspace_dirty(svn: &dyn SvnExecutor, project_name: &str) -> AppResult<bool> {
     // 先同步忽略规则
-    auto_sync_ignore_rules(project_name)?;
-    let xml_str = svn_status(StatusType::Commit)?;
+    auto_sync_ignore_rules(svn, project_name)?;
+    let xml_str = svn_status(svn, StatusType::Commit)?;
     let gitignore = build_ignore_matcher(&PathBuf::from("."), &PathBuf::from("."))?;
     let doc = roxmltree::Document::parse(&xml_str)?;
 
@@ -176,12 +215,10 @@ pub fn is_workspace_dirty(project_name: &str) -> AppResult<bool> {
                         if is_dir {
                             // 如果是目录，检查目录下的文件是否有未忽略的
                             let walker = build_folder_walker(path)?;
-                            for result in walker {
-                                if let Ok(entry) = result {
-                                    let sub_path = entry.path();
-                                    if !gitignore.matched(sub_path, sub_path.is_dir()).is_ignore() {
-                                        return Ok(true);
-                                    }
+                            for entry in walker.flatten() {
+                                let sub_path = entry.path();
+                                if !gitignore.matched(sub_path, sub_path.is_dir()).is_ignore() {
+                                    return Ok(true);
                                 }
                             }
                         }
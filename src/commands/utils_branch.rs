@@ -2,12 +2,15 @@
 //! 
 //! 
 
+use std::cmp::Reverse;
+
 use crossterm::style::Stylize;
 
-use crate::{commands::{models::BranchInfo, utils::{check_url_exists, validate_folder_name}, utils_commit::commit_with_conflict_resolution}, core::{app::App, error::{AppError, AppResult}, svn::{svn_copy, svn_list, svn_log, svn_switch}}};
+use crate::{commands::{models::BranchInfo, utils::{check_url_exists, validate_folder_name}, utils_commit::commit_with_conflict_resolution, utils_log::{is_internal_commit, parse_log_entry}, utils_revision::ResolvedRevision}, core::{app::App, error::{AppError, AppResult}, svn::{svn_copy, svn_list, svn_log, svn_mergeinfo, svn_switch}}, ui::{models::{BranchComparison, LogEntry}, policy::PromptKind}};
 
 /// 基于当前版本创建并切换到新分支，不会有版本冲突
-pub fn create_and_switch_to_branch(app: &App, branch_name: &str) -> AppResult<()> {
+/// - from 不为 None 时基于指定的版本创建
+pub fn create_and_switch_to_branch(app: &App, branch_name: &str, from: Option<&ResolvedRevision>) -> AppResult<()> {
     // Validate branch name
     validate_folder_name(branch_name, false)?;
 
@@ -16,20 +19,25 @@ pub fn create_and_switch_to_branch(app: &App, branch_name: &str) -> AppResult<()
     let new_branch_url = format!("{}/branches/{}", project_root_url, branch_name);
 
     // Check whether branch already exists
-    if check_url_exists(&new_branch_url)? {
+    if check_url_exists(app.svn(), &new_branch_url)? {
         return Err(AppError::Validation(format!("Branch {} already exists", branch_name.yellow().bold())));
     }
 
     // Get current revision to branch from
-    let current_rev = app.svn_ctx.get_current_revision();
-    let project_work_copy_url = app.svn_ctx.get_current_work_copy_root()?;
-    let source_url = format!("{}@{}", project_work_copy_url, current_rev);
+    let source_url = match from {
+        Some(from) => from.url_with_rev(),
+        None => {
+            let current_rev = app.svn_ctx.get_current_revision();
+            let project_work_copy_url = app.svn_ctx.get_current_work_copy_root()?;
+            format!("{}@{}", project_work_copy_url, current_rev)
+        }
+    };
 
     // Create branch
-    svn_copy(&[&source_url, &new_branch_url, "-m", &format!("[WS-BRANCH] Create {}", branch_name), "--parents"])?;
+    svn_copy(app.svn(), &[&source_url, &new_branch_url, "-m", &format!("[WS-BRANCH] Create {}", branch_name), "--parents"])?;
 
     // Switch to the new branch
-    svn_switch(&new_branch_url)?;
+    svn_switch(app.svn(), &new_branch_url)?;
     Ok(())
 }
 
@@ -38,14 +46,14 @@ pub fn create_and_commit_to_branch(app: &App, commit_msg: Option<&str>) -> AppRe
     let mut branch_name;
 
     loop {
-        branch_name = app.ui.input("Input New Branch Name:")?;
-        match create_and_switch_to_branch(app, &branch_name) {
+        branch_name = app.ui.input(PromptKind::BranchName, "Input New Branch Name:")?;
+        match create_and_switch_to_branch(app, &branch_name, None) {
             Ok(_) => {
                 break;
             }
             Err(e) => {
                 app.ui.warn(&format!("Failed to create branch: {}", e));
-                if !app.ui.selector_yes_or_no("Try a different branch name?")? {
+                if !app.ui.selector_yes_or_no(PromptKind::Retry, "Try a different branch name?")? {
                     return Err(AppError::OperationCancelled);
                 }
             }
@@ -76,10 +84,12 @@ pub fn get_project_branches(app: &App, project_name: &str) -> AppResult<Vec<Bran
     branches.push(BranchInfo {
         branch_name: "trunk".to_string(),
         is_current_branch: if is_current_project { current_branch_name == "trunk" } else { false },
+        ahead: None,
+        behind: None,
     });
 
     // List branches
-    let output = svn_list(&[&branches_url])?;
+    let output = svn_list(app.svn(), &[&branches_url])?;
 
     for line in output.lines() {
         let line = line.trim();
@@ -88,7 +98,9 @@ pub fn get_project_branches(app: &App, project_name: &str) -> AppResult<Vec<Bran
             let is_current_branch = if is_current_project { branch_name == current_branch_name } else { false };
             branches.push(BranchInfo {
                 branch_name: branch_name.to_string(),
-                is_current_branch: is_current_branch,
+                is_current_branch,
+                ahead: None,
+                behind: None,
             });
         }
     }
@@ -96,30 +108,181 @@ pub fn get_project_branches(app: &App, project_name: &str) -> AppResult<Vec<Bran
     Ok(branches)
 }
 
-/// 获取指定分支的源版本号
-pub fn get_branch_source(app: &App, branch_name: &str) -> AppResult<String> {
-    let branch_url = app.svn_ctx.get_branch_url(branch_name);
-    let list_output = svn_log(&[
+/// 获取项目某个分支的 URL
+/// - {repo_url}/{project_name}/trunk
+/// - {repo_url}/{project_name}/branches/{branch_name}
+pub fn get_project_branch_url(app: &App, project_name: &str, branch_name: &str) -> String {
+    if branch_name == "trunk" {
+        app.svn_ctx.get_project_trunk_url(project_name)
+    } else {
+        format!("{}/{}", app.svn_ctx.get_project_branches_url(project_name), branch_name)
+    }
+}
+
+/// 填写每个分支领先和落后于 trunk 的提交数，用于 ws list --compare
+pub fn add_ahead_behind(app: &App, project_name: &str, branches: &mut [BranchInfo]) -> AppResult<()> {
+    let trunk_url = app.svn_ctx.get_project_trunk_url(project_name);
+    for branch in branches.iter_mut().filter(|b| b.branch_name != "trunk") {
+        let branch_url = get_project_branch_url(app, project_name, &branch.branch_name);
+        branch.ahead = Some(count_eligible_commits(app, &branch_url, &trunk_url)?);
+        branch.behind = Some(count_eligible_commits(app, &trunk_url, &branch_url)?);
+    }
+    Ok(())
+}
+
+/// 比较当前项目的两个分支，列出双方可以合并到对方的提交
+pub fn compare_branches(app: &App, base: &str, branch: &str) -> AppResult<BranchComparison> {
+    if base == branch {
+        return Err(AppError::Validation(format!("Cannot compare branch {} with itself", base.yellow().bold())));
+    }
+    let project_name = app.svn_ctx.get_current_project_name();
+    let base_url = get_project_branch_url(app, project_name, base);
+    let branch_url = get_project_branch_url(app, project_name, branch);
+    for (name, url) in [(base, &base_url), (branch, &branch_url)] {
+        validate_folder_name(name, true)?;
+        if !check_url_exists(app.svn(), url)? {
+            return Err(AppError::Validation(format!("Branch {} does not exist", name.yellow().bold())));
+        }
+    }
+
+    Ok(BranchComparison {
+        base: base.to_string(),
+        branch: branch.to_string(),
+        ahead: get_eligible_entries(app, &branch_url, &base_url, branch)?,
+        behind: get_eligible_entries(app, &base_url, &branch_url, base)?,
+    })
+}
+
+/// 读取 source 中还没有合并到 target 的版本，来自 svn mergeinfo
+fn get_eligible_revisions(app: &App, source_url: &str, target_url: &str) -> AppResult<Vec<u64>> {
+    let output = svn_mergeinfo(app.svn(), &["--show-revs", "eligible", source_url, target_url])?;
+    // 只合并了部分子目录的版本带有 * 后缀
+    Ok(output.lines().filter_map(|line| line.trim().trim_start_matches('r').trim_end_matches('*').parse().ok()).collect())
+}
+
+/// 读取 source 中可以合并到 target 的提交，从新到旧，不包括 ws 内部的提交
+fn get_eligible_entries(app: &App, source_url: &str, target_url: &str, source_branch: &str) -> AppResult<Vec<LogEntry>> {
+    let revisions = get_eligible_revisions(app, source_url, target_url)?;
+    let mut entries = Vec::new();
+    for_each_revision_log(app, source_url, &revisions, &["-v", "-g"], |doc| {
+        for entry in doc.root_element().children().filter(|n| n.has_tag_name("logentry")) {
+            let message = entry.children().find(|n| n.has_tag_name("msg")).and_then(|n| n.text()).unwrap_or("");
+            if is_internal_commit(message) {
+                continue;
+            }
+            if let Some(log_entry) = parse_log_entry(app, entry, source_branch, None)? {
+                entries.push(log_entry);
+            }
+        }
+        Ok(())
+    })?;
+    entries.sort_by_key(|e| Reverse(e.revision));
+    Ok(entries)
+}
+
+/// 统计 source 中可以合并到 target 的提交数，与 get_eligible_entries 的结果一致
+fn count_eligible_commits(app: &App, source_url: &str, target_url: &str) -> AppResult<usize> {
+    let revisions = get_eligible_revisions(app, source_url, target_url)?;
+    let mut count = 0;
+    for_each_revision_log(app, source_url, &revisions, &[], |doc| {
+        count += doc.root_element().children()
+            .filter(|n| n.has_tag_name("logentry"))
+            .filter(|entry| !is_internal_commit(entry.children().find(|n| n.has_tag_name("msg")).and_then(|n| n.text()).unwrap_or("")))
+            .count();
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+/// 分批读取 url 在这些版本的日志，每批最多 500 个版本，避免命令行过长
+fn for_each_revision_log(app: &App, url: &str, revisions: &[u64], log_args: &[&str], mut callback: impl FnMut(&roxmltree::Document) -> AppResult<()>) -> AppResult<()> {
+    for chunk in revisions.chunks(500) {
+        let changes = chunk.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        let xml_str = svn_log(app.svn(), &[log_args, &["--xml", "-c", &changes, url]].concat())?;
+        callback(&roxmltree::Document::parse(&xml_str)?)?;
+    }
+    Ok(())
+}
+
+/// 获取指定分支的创建来源：(来源分支名, 来源版本号)
+/// - 分支改过名时，沿着改名找到最初创建它的复制
+pub fn get_branch_source(app: &App, branch_name: &str) -> AppResult<(String, u64)> {
+    let mut name = branch_name.to_string();
+    let mut peg = None;
+    while let Some(copy) = find_branch_copy(app, &name, peg)? {
+        if !copy.is_move {
+            let clean_path = copy.from_path.split('/').next_back().unwrap_or(&copy.from_path);
+            return Ok((clean_path.to_string(), copy.from_rev));
+        }
+        name = copy.from_name;
+        peg = Some(copy.from_rev);
+    }
+    Err(AppError::Validation(format!("Failed to get source revision for branch {}", branch_name.yellow().bold())))
+}
+
+/// 获取分支改名前的名称，从新到旧：(旧名称, 旧名称的最后一个版本)
+pub fn get_branch_renames(app: &App, branch_name: &str) -> AppResult<Vec<(String, u64)>> {
+    let mut renames = Vec::new();
+    if branch_name == "trunk" {
+        return Ok(renames);
+    }
+
+    let mut name = branch_name.to_string();
+    let mut peg = None;
+    while let Some(copy) = find_branch_copy(app, &name, peg)? {
+        if !copy.is_move || renames.iter().any(|(n, _)| *n == copy.from_name) {
+            break;
+        }
+        renames.push((copy.from_name.clone(), copy.from_rev));
+        name = copy.from_name;
+        peg = Some(copy.from_rev);
+    }
+    Ok(renames)
+}
+
+/// 分支根目录最早的一次复制
+struct BranchCopy {
+    from_path: String,
+    from_rev: u64,
+    /// from_path 为 branches/ 下的分支时的分支名
+    from_name: String,
+    /// 同一个版本删除了 from_path，即 ws branch --rename 的移动
+    is_move: bool,
+}
+
+/// 读取分支（peg 为 None 时是 HEAD 中的分支）根目录最早的一次复制
+fn find_branch_copy(app: &App, branch_name: &str, peg: Option<u64>) -> AppResult<Option<BranchCopy>> {
+    let branch_url = match peg {
+        Some(peg) => format!("{}@{}", app.svn_ctx.get_branch_url(branch_name), peg),
+        None => app.svn_ctx.get_branch_url(branch_name),
+    };
+    let range = format!("1:{}", peg.map_or("HEAD".to_string(), |peg| peg.to_string()));
+    let list_output = svn_log(app.svn(), &[
         "-v", "--xml", "--stop-on-copy", 
         "--limit", "1", 
-        "-r", "1:HEAD", 
+        "-r", &range, 
         &branch_url]
     )?;
 
     let doc = roxmltree::Document::parse(&list_output)?;
-    
+    let root = format!("/branches/{}", branch_name);
+
     for logentry in doc.descendants().filter(|n| n.has_tag_name("logentry")) {
         if let Some(paths) = logentry.children().find(|n| n.has_tag_name("paths")) {
-            for path in paths.children().filter(|n| n.has_tag_name("path")) {
-                
+            let path_nodes: Vec<roxmltree::Node> = paths.children().filter(|n| n.has_tag_name("path")).collect();
+            for path in &path_nodes {
                 if let (Some(cp), Some(cr)) = (path.attribute("copyfrom-path"), path.attribute("copyfrom-rev")) {
-                    let clean_path = cp.split('/').last().unwrap_or(cp);
-                    return Ok(format!("{}@r{}", clean_path, cr));
+                    // 改名时的复制在分支根目录上，其他复制（如恢复分支）也按复制处理
+                    let is_root = path.text().is_some_and(|p| p.trim_end_matches('/').ends_with(&root));
+                    let from_name = cp.rsplit_once("/branches/").map(|(_, name)| name.to_string()).filter(|name| !name.contains('/')).unwrap_or_default();
+                    let is_move = is_root && !from_name.is_empty() && from_name != branch_name
+                        && path_nodes.iter().any(|n| n.attribute("action") == Some("D") && n.text() == Some(cp));
+                    return Ok(Some(BranchCopy { from_path: cp.to_string(), from_rev: cr.parse().unwrap_or(0), from_name, is_move }));
                 }
             }
         }
     }
-    Err(AppError::Validation(format!("Failed to get source revision for branch {}", branch_name.yellow().bold())))
+    Ok(None)
 }
 
 /// 从一个 path 中提取分支名称
@@ -1,86 +1,51 @@
 //! SVN 提交相关工具函数
 //!
 
-use std::path::PathBuf;
+use std::{fs, path::{Path, PathBuf}};
 
 use crossterm::style::Stylize;
 
-use crate::{commands::{models::{CommitResult, ConflictItem, ConflictKind}, utils_ignore::{auto_sync_ignore_rules, build_folder_walker, build_ignore_matcher, set_remaining_unversioned_as_ignored}}, core::{app::App, error::{AppError, AppResult}, svn::{StatusType, svn_add, svn_cleanup, svn_commit, svn_delete, svn_resolve, svn_revert, svn_status, svn_update}}};
+use crate::{commands::{models::{CommitResult, ConflictFiles, ConflictItem, ConflictKind}, utils_conflict::{ConflictRule, apply_conflict_resolution, editor_command, has_conflict_markers, is_binary_file, load_conflict_rules, match_conflict_rule, merge_tool_command, parse_conflict_regions, read_conflict_files, run_external_tool, side_by_side_path}, utils_diff::{apply_hunks, parse_unified_diff}, utils_platform::is_process_running, utils_rename::{detect_renames, record_renames}, utils_ignore::{auto_sync_ignore_rules, build_ignore_matcher, set_remaining_unversioned_as_ignored}, utils_status::{collect_status_entries, current_ignore_matcher, is_under_paths}, utils_tag::ensure_not_on_tag}, core::{app::App, error::{AppError, AppResult}, svn::{StatusType, svn_add, svn_cat, svn_cleanup, svn_commit, svn_commit_paths, svn_delete, svn_diff, svn_resolve, svn_revert, svn_status, svn_update}}, ui::{models::{DetectedRename, StatusEntry, StatusKind}, policy::{PromptChoice, PromptKind}}};
 
 /// ### svn add and delete
-/// 添加新文件和删除缺失文件
+/// 添加新文件和删除缺失文件，与 ws status 显示的一致
+/// - 内容相近的缺失文件和新文件记录为改名
 fn svn_add_and_delete(app: &App) -> AppResult<()> {
-    auto_sync_ignore_rules(app.svn_ctx.get_current_project_name())?;
+    auto_sync_ignore_rules(app.svn(), app.svn_ctx.get_current_project_name())?;
 
     let ignore_matcher = build_ignore_matcher(&PathBuf::from("."), &PathBuf::from("."))?;
-    let xml_str = svn_status(StatusType::CheckIgnore)?;
-    let doc = roxmltree::Document::parse(&xml_str)?;
-
-    let mut unversioned_items = Vec::new();
-    let mut adds = Vec::new();
-    let mut dels = Vec::new();
-
-    for entry in doc.descendants().filter(|n| n.has_tag_name("entry")) {
-        let path = entry.attribute("path").unwrap_or("");
-
-        if let Some(wc_status) = entry.children().find(|n| n.has_tag_name("wc-status")) {
-            let item = wc_status.attribute("item").unwrap_or("");
-
-            match item {
-                "unversioned" | "ignored" => { // 这里也处理 ignored 项目，确保忽略规则生效
-                    let path_buf = std::path::PathBuf::from(path);
-                    unversioned_items.push(path_buf);
-                },
-                "missing" => {
-                    dels.push(path);
-                },
-                _ => {},
-            };
-        }
+    let mut entries = collect_status_entries(app.svn(), &ignore_matcher)?;
+
+    // 先把改名记录为 svn move，剩下的才作为添加和删除
+    let renames = detect_renames(app, &entries, app.svn_ctx.get_config().rename_similarity)?;
+    if !renames.is_empty() {
+        app.ui.show_renames(&renames);
+        record_renames(app, &renames)?;
+        entries = collect_status_entries(app.svn(), &ignore_matcher)?;
     }
 
-    for item in &unversioned_items {
-        let is_dir = item.is_dir();
-        let matched = ignore_matcher.matched(&item, is_dir);
-        if matched.is_ignore() {
-            continue;
-        } else {
-            if is_dir {
-                // 如果是目录，直接看目录里面的内容是否需要添加
-                // 目录本身无需添加，只要里面的文件添加了，目录就会被 SVN 跟踪
-                let walker = build_folder_walker(&item)?;
-                for result in walker {
-                    if let Ok(entry) = result {
-                        let sub_path = entry.path();
-                        adds.push(sub_path.to_path_buf());
-                    }
-                }
-            }
-            else {
-                // 文件，直接处理
-                adds.push(item.to_path_buf());
-            }
-        }
-
-    }
+    let auto_paths = |kind: StatusKind| -> Vec<&str> {
+        entries.iter().filter(|e| e.auto && e.kind == kind).map(|e| e.path.as_str()).collect()
+    };
+    let adds = auto_paths(StatusKind::Added);
+    let dels = auto_paths(StatusKind::Deleted);
 
     if !adds.is_empty() {
-        let add_paths: Vec<&str> = adds.iter().map(|s| s.to_str().unwrap_or(".")).collect();
-        svn_add(&add_paths)?;
+        svn_add(app.svn(), &adds)?;
     }
 
     if !dels.is_empty() {
-        svn_delete(&dels)?;
+        svn_delete(app.svn(), &dels)?;
     }
 
-    set_remaining_unversioned_as_ignored(app.svn_ctx.get_current_project_name())?;
+    set_remaining_unversioned_as_ignored(app.svn(), app.svn_ctx.get_current_project_name())?;
 
     Ok(())
 }
 
 /// 获取所有冲突文件列表
-fn get_conflicted_files() -> AppResult<Vec<ConflictItem>> {
-    let xml_str = svn_status(StatusType::Commit)?;
+pub fn get_conflicted_files(app: &App) -> AppResult<Vec<ConflictItem>> {
+    let xml_str = svn_status(app.svn(), StatusType::Commit)?;
     let doc = roxmltree::Document::parse(&xml_str)?;
 
     let mut conflicts = Vec::new();
@@ -115,27 +80,137 @@ fn get_conflicted_files() -> AppResult<Vec<ConflictItem>> {
     Ok(conflicts)
 }
 
+/// 内容冲突的处理方式
+/// - 前两项对应 --conflicts=mine|theirs 的策略答案，见 choice
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ConflictAction {
+    KeepMine,
+    DiscardMine,
+    MineConflicts,
+    TheirsConflicts,
+    KeepBoth,
+    ShowDiff,
+    MergeTool,
+    Edit,
+}
+
+impl ConflictAction {
+    /// 交互策略选择选项时使用的含义
+    fn choice(self) -> PromptChoice {
+        match self {
+            ConflictAction::KeepMine => PromptChoice::KeepMine,
+            ConflictAction::DiscardMine => PromptChoice::TakeTheirs,
+            _ => PromptChoice::Other,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ConflictAction::KeepMine => "Keep My Version (Keep Local Changes)",
+            ConflictAction::DiscardMine => "Discard My Version (Delete Local Changes)",
+            ConflictAction::MineConflicts => "Merge, Keep Mine Where They Conflict (Keep non-conflicting changes from both)",
+            ConflictAction::TheirsConflicts => "Merge, Take Theirs Where They Conflict (Keep non-conflicting changes from both)",
+            ConflictAction::KeepBoth => "Keep Both Versions (Save theirs next to the file)",
+            ConflictAction::ShowDiff => "Show the Three-way Diff",
+            ConflictAction::MergeTool => "Open in Merge Tool",
+            ConflictAction::Edit => "Edit the File with Conflict Markers",
+        }
+    }
+}
+
+/// 交互式解决内容冲突，可以先查看三方差异，或交给合并工具、编辑器
+/// - 二进制文件不能按冲突块合并，改为可以同时保留两个版本
+fn resolve_content_conflict(app: &App, path: &str, files: &ConflictFiles) -> AppResult<()> {
+    let merge_tool = app.svn_ctx.get_config().merge_tool.clone();
+    let is_binary = is_binary_file(&files.mine) || is_binary_file(&files.theirs);
+    let mut actions = vec![ConflictAction::KeepMine, ConflictAction::DiscardMine];
+    if is_binary {
+        actions.push(ConflictAction::KeepBoth);
+    } else {
+        actions.extend([ConflictAction::MineConflicts, ConflictAction::TheirsConflicts, ConflictAction::ShowDiff]);
+    }
+    if merge_tool.is_some() {
+        actions.push(ConflictAction::MergeTool);
+    }
+    if !is_binary {
+        actions.push(ConflictAction::Edit);
+    }
+    let choices: Vec<(PromptChoice, &str)> = actions.iter().map(|a| (a.choice(), a.label())).collect();
+
+    loop {
+        let selection = app.ui.selector(PromptKind::Conflict, &format!("Conflict in file: {}", path.yellow().bold()), choices.clone())?;
+        let accept_arg = match actions[selection] {
+            ConflictAction::KeepMine => "mine-full",
+            ConflictAction::DiscardMine => "theirs-full",
+            ConflictAction::MineConflicts => "mine-conflict",
+            ConflictAction::TheirsConflicts => "theirs-conflict",
+            ConflictAction::KeepBoth => {
+                let copy = side_by_side_path(path, "theirs");
+                if Path::new(&copy).exists() {
+                    app.ui.warn(&format!("{} already exists, move it away first", copy));
+                    continue;
+                }
+                fs::copy(&files.theirs, &copy)?;
+                app.ui.info(&format!("Saved their version as {}", copy.green()));
+                "working"
+            }
+            ConflictAction::ShowDiff => {
+                let regions = parse_conflict_regions(&String::from_utf8_lossy(&fs::read(path)?));
+                if regions.is_empty() {
+                    app.ui.warn(&format!("No conflict markers left in {}", path));
+                } else {
+                    app.ui.show_conflict_regions(path, &regions);
+                }
+                continue;
+            }
+            ConflictAction::MergeTool | ConflictAction::Edit => {
+                let command = match (actions[selection], &merge_tool) {
+                    (ConflictAction::MergeTool, Some(template)) => merge_tool_command(template, files, path),
+                    _ => editor_command(path),
+                };
+                if !run_external_tool(app, &command, &[])? {
+                    app.ui.warn(&format!("{} exited with an error, the conflict is not resolved", command[0]));
+                    continue;
+                }
+                if has_conflict_markers(path)? {
+                    app.ui.warn(&format!("{} still contains conflict markers", path));
+                    continue;
+                }
+                if !app.ui.selector_yes_or_no(PromptKind::Confirm, &format!("Mark {} as resolved?", path.yellow().bold()))? {
+                    continue;
+                }
+                "working"
+            }
+        };
+        svn_resolve(app.svn(), &["--accept", accept_arg, path])?;
+        return Ok(());
+    }
+}
+
 /// 交互式解决单个冲突文件
 fn resolve_single_conflict(app: &App, item: &ConflictItem) -> AppResult<()> {
-    let choices = vec![
-        "Keep My Version (Keep Local Changes)",
-        "Discard My Version (Delete Local Changes)",
-    ];
+    if let ConflictKind::Standard = item.kind
+        && let Some(files) = read_conflict_files(app, &item.path)? {
+        return resolve_content_conflict(app, &item.path, &files);
+    }
+
+    let choices = [ConflictAction::KeepMine, ConflictAction::DiscardMine].map(|a| (a.choice(), a.label())).to_vec();
 
-    let selection = app.ui.selector(&format!("Conflict in file: {}", item.path.clone().yellow().bold()), choices)?;
+    let selection = app.ui.selector(PromptKind::Conflict, &format!("Conflict in file: {}", item.path.clone().yellow().bold()), choices)?;
 
     match item.kind {
+        // 属性冲突只能整体选择一边
         ConflictKind::Standard => {
             let accept_arg = if selection == 0 { "mine-full" } else { "theirs-full" };
-            svn_resolve(&["--accept", accept_arg, &item.path])?;
+            svn_resolve(app.svn(), &["--accept", accept_arg, &item.path])?;
         }
         ConflictKind::TreeConflict => {
-            svn_resolve(&["--accept", "working", &item.path])?;
+            svn_resolve(app.svn(), &["--accept", "working", &item.path])?;
             if selection == 1 { // Discard
-                svn_revert(&["-R", &item.path])?;
-                svn_update(&[&item.path])?;
+                svn_revert(app.svn(), &["-R", &item.path])?;
+                svn_update(app.svn(), &[&item.path])?;
             } else { // Keep
-                svn_add(&["--force", &item.path])?;
+                svn_add(app.svn(), &["--force", &item.path])?;
             }
         }
         ConflictKind::Obstructed => {
@@ -148,11 +223,11 @@ fn resolve_single_conflict(app: &App, item: &ConflictItem) -> AppResult<()> {
                         std::fs::remove_file(&item.path)?;
                     }
                 }
-                svn_revert(&[&item.path])?;
-                svn_update(&[&item.path])?;
+                svn_revert(app.svn(), &[&item.path])?;
+                svn_update(app.svn(), &[&item.path])?;
             } else { // Keep
-                svn_delete(&["--keep-local", "--force", &item.path])?;
-                svn_add(&["--force", &item.path])?;
+                svn_delete(app.svn(), &["--keep-local", "--force", &item.path])?;
+                svn_add(app.svn(), &["--force", &item.path])?;
             }
         }
         ConflictKind::Incomplete => {
@@ -163,23 +238,56 @@ fn resolve_single_conflict(app: &App, item: &ConflictItem) -> AppResult<()> {
 }
 
 /// 获取冲突文件列表，解决冲突
+/// - 先按项目的冲突规则自动解决内容冲突，剩下的再逐个询问
 pub fn resolve_conflicts(app: &App) -> AppResult<()> {
-    let conflicted_files = get_conflicted_files()?;
+    let conflicted_files = get_conflicted_files(app)?;
+    if conflicted_files.is_empty() {
+        return Ok(());
+    }
+
+    resolve_conflicts_with_rules(app, conflicted_files, &load_conflict_rules()?)
+}
 
-    if !conflicted_files.is_empty() {
-        app.ui.warn(&format!("Conflict detected in {} file(s). Need to resolve them", conflicted_files.len()));
-        for file in conflicted_files {
-            resolve_single_conflict(app, &file)?;
+/// 按给定的冲突规则解决冲突，见 resolve_conflicts
+/// - 规则解决失败的文件与其他剩下的冲突一样逐个询问，最后总是显示规则的处理结果
+pub fn resolve_conflicts_with_rules(app: &App, conflicted_files: Vec<ConflictItem>, rules: &[ConflictRule]) -> AppResult<()> {
+    let mut auto_resolved = Vec::new();
+    let mut failed = Vec::new();
+    let mut remaining = Vec::new();
+    for file in conflicted_files {
+        let rule = match file.kind {
+            ConflictKind::Standard => match_conflict_rule(rules, &file.path),
+            _ => None,
+        };
+        match rule {
+            Some(rule) => match apply_conflict_resolution(app, &file.path, &rule.resolution) {
+                Ok(()) => auto_resolved.push((file.path, rule)),
+                Err(e) => {
+                    app.ui.warn(&e.to_string());
+                    failed.push((file.path.clone(), rule));
+                    remaining.push(file);
+                }
+            },
+            None => remaining.push(file),
         }
     }
 
-    Ok(())
+    let mut result = Ok(());
+    if !remaining.is_empty() {
+        app.ui.warn(&format!("Conflict detected in {} file(s). Need to resolve them", remaining.len()));
+        result = remaining.iter().try_for_each(|file| resolve_single_conflict(app, file));
+    }
+
+    if !auto_resolved.is_empty() || !failed.is_empty() {
+        app.ui.show_auto_resolved(&auto_resolved, &failed);
+    }
+    result
 }
 
 /// 更新并解决冲突
-fn update_and_resolve_conflicts(app: &App) -> AppResult<()> {
+pub fn update_and_resolve_conflicts(app: &App) -> AppResult<()> {
     // 1. Update
-    svn_update(&["--accept", "postpone"])?;
+    svn_update(app.svn(), &["--accept", "postpone"])?;
 
     // 2. Conflict Resolution
     resolve_conflicts(app)?;
@@ -189,6 +297,8 @@ fn update_and_resolve_conflicts(app: &App) -> AppResult<()> {
 
 /// 提交更改，包含冲突解决流程
 pub fn commit_with_conflict_resolution(app: &App, commit_message: &str) -> AppResult<CommitResult> {
+    ensure_not_on_tag(app)?;
+
     // 1. Add and Delete
     svn_add_and_delete(app)?;
 
@@ -196,13 +306,13 @@ pub fn commit_with_conflict_resolution(app: &App, commit_message: &str) -> AppRe
     update_and_resolve_conflicts(app)?;
 
     // 4. Commit
-    let commit_output = svn_commit(commit_message)?;
+    let commit_output = svn_commit(app.svn(), commit_message)?;
 
     // 5. Update again to ensure up-to-date
     update_and_resolve_conflicts(app)?;
 
     // 5. Cleanup
-    svn_cleanup()?;
+    svn_cleanup(app.svn())?;
 
     if commit_output.trim().is_empty() {
         Ok(CommitResult::NoChanges)
@@ -210,3 +320,277 @@ pub fn commit_with_conflict_resolution(app: &App, commit_message: &str) -> AppRe
         Ok(CommitResult::Success)
     }
 }
+
+/// 选择性提交中的一个条目
+pub struct SelectedChange {
+    pub entry: StatusEntry,
+    /// 只提交部分修改块时，提交的文件内容
+    pub content: Option<Vec<u8>>,
+}
+
+/// 选出要提交的更改，与 ws status 显示的一致
+/// - paths 不为空时只选择这些路径下的更改
+/// - interactive 时逐个勾选文件，文本文件还可以勾选修改块
+pub fn select_changes(app: &App, paths: &[String], interactive: bool) -> AppResult<Vec<SelectedChange>> {
+    auto_sync_ignore_rules(app.svn(), app.svn_ctx.get_current_project_name())?;
+    let ignore_matcher = build_ignore_matcher(&PathBuf::from("."), &PathBuf::from("."))?;
+    let mut entries: Vec<StatusEntry> = collect_status_entries(app.svn(), &ignore_matcher)?.into_iter()
+        .filter(|e| matches!(e.kind, StatusKind::Added | StatusKind::Modified | StatusKind::Deleted))
+        .filter(|e| is_under_paths(&e.path, paths))
+        .collect();
+
+    if let Some(path) = paths.iter().find(|p| !entries.iter().any(|e| is_under_paths(&e.path, std::slice::from_ref(p)))) {
+        return Err(AppError::Validation(format!("No changes to commit under {}", path.clone().yellow().bold())));
+    }
+
+    if interactive && !entries.is_empty() {
+        let items: Vec<String> = entries.iter().map(|e| format!("{} ({})", e.path.replace('\\', "/"), format!("{:?}", e.kind).to_lowercase())).collect();
+        let selected = app.ui.multi_selector(PromptKind::Selection, "Select the files to commit", &items, &vec![true; items.len()])?;
+        entries = entries.into_iter().enumerate().filter(|(i, _)| selected.contains(i)).map(|(_, e)| e).collect();
+    }
+
+    let mut changes = Vec::new();
+    for entry in entries {
+        let content = if interactive && entry.kind == StatusKind::Modified && !entry.auto && Path::new(&entry.path).is_file() {
+            match select_hunks(app, &entry.path)? {
+                HunkSelection::All => None,
+                HunkSelection::None => continue,
+                HunkSelection::Partial(content) => Some(content),
+            }
+        } else {
+            None
+        };
+        changes.push(SelectedChange { entry, content });
+    }
+    Ok(changes)
+}
+
+enum HunkSelection {
+    All,
+    None,
+    /// 只应用选中的修改块之后的文件内容
+    Partial(Vec<u8>),
+}
+
+/// 勾选一个文件的修改块，只有一个修改块时直接提交整个文件
+fn select_hunks(app: &App, path: &str) -> AppResult<HunkSelection> {
+    let Some(diff) = parse_unified_diff(&svn_diff(app.svn(), &[path])?).into_iter().next() else { return Ok(HunkSelection::All) };
+    let hunks = diff.hunks();
+    if hunks.len() < 2 {
+        return Ok(HunkSelection::All);
+    }
+
+    app.ui.show_hunks(&diff.path, &hunks);
+    let items: Vec<String> = hunks.iter().enumerate().map(|(i, hunk)| format!("Hunk {}: {}", i + 1, hunk.lines[0])).collect();
+    let selected = app.ui.multi_selector(PromptKind::Selection, &format!("Select the hunks of {} to commit", diff.path), &items, &vec![true; items.len()])?;
+
+    if selected.is_empty() {
+        return Ok(HunkSelection::None);
+    }
+    if selected.len() == hunks.len() {
+        return Ok(HunkSelection::All);
+    }
+    let flags: Vec<bool> = (0..hunks.len()).map(|i| selected.contains(&i)).collect();
+    let base = svn_cat(app.svn(), &["-r", "BASE", path])?;
+    Ok(HunkSelection::Partial(apply_hunks(&base, &fs::read(path)?, &hunks, &flags)))
+}
+
+/// 只提交选中的更改，未选中的更改保留在工作区
+/// - 调用前应已 update 并解决冲突，见 update_and_resolve_conflicts
+pub fn commit_selected_with_conflict_resolution(app: &App, commit_message: &str, changes: &[SelectedChange]) -> AppResult<CommitResult> {
+    ensure_not_on_tag(app)?;
+
+    // 1. 选中改名的任一端时，记录为 svn move，两端一起提交
+    let renames = detect_selected_renames(app, changes)?;
+    if !renames.is_empty() {
+        app.ui.show_renames(&renames);
+        record_renames(app, &renames)?;
+    }
+    let renamed_paths: Vec<String> = renames.iter().flat_map(|r| [r.from.clone(), r.to.clone()]).collect();
+
+    // 2. Add and Delete
+    // 记录改名后重新读取状态，移动的目录中多出的文件仍要添加，去掉的文件仍要删除
+    let mut selected_paths: Vec<String> = changes.iter().map(|c| c.entry.path.clone()).chain(renamed_paths.iter().cloned()).collect();
+    let entries: Vec<StatusEntry> = if renames.is_empty() {
+        changes.iter().map(|c| c.entry.clone()).collect()
+    } else {
+        collect_status_entries(app.svn(), &current_ignore_matcher()?)?.into_iter()
+            .filter(|e| is_under_paths(&e.path, &selected_paths))
+            .collect()
+    };
+    let auto_paths = |kind: StatusKind| -> Vec<&str> {
+        entries.iter().filter(|e| e.auto && e.kind == kind).map(|e| e.path.as_str()).collect()
+    };
+    let adds = auto_paths(StatusKind::Added);
+    let dels = auto_paths(StatusKind::Deleted);
+    if !adds.is_empty() {
+        svn_add(app.svn(), &adds)?;
+    }
+    if !dels.is_empty() {
+        svn_delete(app.svn(), &dels)?;
+    }
+
+    // 3. Commit，部分提交的文件先写入选中的内容，原内容保存在 .ws_store 中，提交后恢复
+    let partial: Vec<(&str, &[u8])> = changes.iter()
+        .filter_map(|c| c.content.as_deref().map(|content| (c.entry.path.as_str(), content)))
+        .collect();
+    let backup_root = if partial.is_empty() { None } else { Some(partial_commit_root(app)?) };
+    if let Some(root) = &backup_root {
+        write_partial_files(root, &partial)?;
+    }
+    selected_paths.extend(adds.iter().chain(&dels).map(|p| p.to_string()));
+    let selected_refs: Vec<&str> = selected_paths.iter().map(String::as_str).collect();
+    let commit_result = commit_selected_paths(app, commit_message, &selected_refs);
+    if let Some(root) = &backup_root {
+        restore_partial_files(root, false)?;
+    }
+    let commit_output = commit_result?;
+
+    // 4. Update again to ensure up-to-date
+    update_and_resolve_conflicts(app)?;
+
+    // 5. Cleanup
+    svn_cleanup(app.svn())?;
+
+    if commit_output.trim().is_empty() {
+        Ok(CommitResult::NoChanges)
+    } else {
+        Ok(CommitResult::Success)
+    }
+}
+
+/// 非递归地提交选中的路径，返回 svn commit 的输出
+/// - 目录只提交自身的添加、删除和属性修改，其中未选中的更改不会被一起提交
+/// - svn add --parents 添加的上级目录也要一起提交
+pub fn commit_selected_paths(app: &App, commit_message: &str, paths: &[&str]) -> AppResult<String> {
+    let scheduled_dirs = get_scheduled_added_paths(app)?;
+    let mut targets: Vec<String> = Vec::new();
+    for path in paths {
+        let path = path.replace('\\', "/");
+        for dir in Path::new(&path).ancestors().skip(1).map(|p| p.to_string_lossy().to_string()) {
+            if scheduled_dirs.contains(&dir) {
+                targets.push(dir);
+            }
+        }
+        targets.push(path);
+    }
+    targets.sort();
+    targets.dedup();
+
+    let target_refs: Vec<&str> = targets.iter().map(String::as_str).collect();
+    svn_commit_paths(app.svn(), commit_message, &target_refs)
+}
+
+/// 识别选中的更改中的改名，改名的任一端被选中即可
+/// - 只选中新路径时，缺失的旧路径也一起提交，否则会记录为删除加添加
+fn detect_selected_renames(app: &App, changes: &[SelectedChange]) -> AppResult<Vec<DetectedRename>> {
+    let entries = collect_status_entries(app.svn(), &current_ignore_matcher()?)?;
+    let renames = detect_renames(app, &entries, app.svn_ctx.get_config().rename_similarity)?;
+    Ok(renames.into_iter()
+        .filter(|r| changes.iter().any(|c| is_under_paths(&c.entry.path, &[r.from.clone(), r.to.clone()])))
+        .collect())
+}
+
+/// 部分提交的备份中记录文件路径的清单，每行一个绝对路径
+const PARTIAL_MANIFEST: &str = "paths";
+
+/// 部分提交的备份中记录进行提交的 ws 进程 PID 的文件
+const PARTIAL_OWNER: &str = "owner";
+
+/// 部分提交期间保存工作文件原内容的目录：.ws_store/{repo_name}/.partial-commit/{project}
+fn partial_commit_root(app: &App) -> AppResult<PathBuf> {
+    Ok(app.svn_ctx.get_ws_store_root()?
+        .join(app.svn_ctx.get_repo_name()?)
+        .join(".partial-commit")
+        .join(app.svn_ctx.get_current_project_name()))
+}
+
+/// 把部分提交的文件写入选中的内容
+/// - 先记录当前进程，再把原内容和选中的内容保存到 root，最后写入清单，有清单时才需要恢复
+pub fn write_partial_files(root: &Path, files: &[(&str, &[u8])]) -> AppResult<()> {
+    if root.exists() {
+        fs::remove_dir_all(root)?;
+    }
+    fs::create_dir_all(root)?;
+    fs::write(root.join(PARTIAL_OWNER), std::process::id().to_string())?;
+    let mut manifest = String::new();
+    for (index, (path, selected)) in files.iter().enumerate() {
+        let path = std::path::absolute(path)?;
+        fs::write(root.join(format!("{}.orig", index)), fs::read(&path)?)?;
+        fs::write(root.join(format!("{}.selected", index)), selected)?;
+        manifest.push_str(&format!("{}\n", path.to_string_lossy()));
+    }
+    fs::write(root.join(PARTIAL_MANIFEST), manifest)?;
+
+    for (path, selected) in files {
+        fs::write(path, selected)?;
+    }
+    Ok(())
+}
+
+/// 把部分提交的文件恢复为原内容，并删除 root，返回另存的文件
+/// - keep_edits 时，内容已不是选中的内容的文件先把当前内容另存为 {name}.partial{ext}
+pub fn restore_partial_files(root: &Path, keep_edits: bool) -> AppResult<Vec<String>> {
+    let manifest = match fs::read_to_string(root.join(PARTIAL_MANIFEST)) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut kept = Vec::new();
+    for (index, path) in manifest.lines().enumerate() {
+        let original = fs::read(root.join(format!("{}.orig", index)))?;
+        if keep_edits
+            && let Ok(current) = fs::read(path)
+            && current != fs::read(root.join(format!("{}.selected", index)))? {
+            let aside = side_by_side_path(path, "partial");
+            fs::write(&aside, current)?;
+            kept.push(aside);
+        }
+        fs::write(path, original)?;
+    }
+    if root.exists() {
+        fs::remove_dir_all(root)?;
+    }
+    Ok(kept)
+}
+
+/// 部分提交是否正由另一个仍在运行的 ws 进程进行
+pub fn is_partial_commit_in_progress(root: &Path) -> bool {
+    fs::read_to_string(root.join(PARTIAL_OWNER)).ok()
+        .and_then(|owner| owner.trim().parse::<u32>().ok())
+        .is_some_and(|pid| pid != std::process::id() && is_process_running(pid))
+}
+
+/// 恢复被中断的部分提交，未选中的修改块回到工作文件中
+/// - 进行提交的 ws 进程仍在运行时不恢复，否则未选中的修改块会在提交过程中被写回
+pub fn recover_partial_commit(app: &App) -> AppResult<()> {
+    // 无法确定 .ws_store 时也不可能进行过部分提交
+    let Ok(root) = partial_commit_root(app) else { return Ok(()) };
+    if !root.exists() {
+        return Ok(());
+    }
+    if is_partial_commit_in_progress(&root) {
+        app.ui.warn("A partial commit is in progress in another ws process, unselected changes are restored when it finishes");
+        return Ok(());
+    }
+
+    let kept = restore_partial_files(&root, true)?;
+    app.ui.warn("Restored the files of an interrupted partial commit");
+    for path in kept {
+        app.ui.warn(&format!("A file was changed after the interrupted commit, its content is saved as {}", path.yellow().bold()));
+    }
+    Ok(())
+}
+
+/// 已经计划添加但还没有提交的路径，使用 / 分隔
+fn get_scheduled_added_paths(app: &App) -> AppResult<Vec<String>> {
+    let xml_str = svn_status(app.svn(), StatusType::Commit)?;
+    let doc = roxmltree::Document::parse(&xml_str)?;
+    Ok(doc.descendants()
+        .filter(|n| n.has_tag_name("entry"))
+        .filter(|n| n.children().any(|c| c.has_tag_name("wc-status") && c.attribute("item") == Some("added")))
+        .filter_map(|n| n.attribute("path"))
+        .map(|p| p.replace('\\', "/"))
+        .collect())
+}
@@ -0,0 +1,211 @@
+//! ### 解决内容冲突相关的工具函数
+//!
+//! - svn 把冲突的三个版本保存在冲突文件旁边，路径由 svn info --xml 给出
+//! - 文本冲突在工作文件中写入 <<<<<<< ||||||| ======= >>>>>>> 标记
+//! - 外部合并工具由配置中的 merge_tool 给出，编辑器使用 $VISUAL、$EDITOR
+//! - 项目根目录的 .ws-conflicts.toml 按路径给出自动解决的方式，先于交互提示应用
+//! - 规则中的命令通过环境变量 WS_CONFLICT_PATH 得到冲突文件，路径不经过 shell 解析
+
+use std::{fs, path::{Path, PathBuf}, process::Command};
+
+use crossterm::style::Stylize;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+
+use crate::{
+    commands::{models::{ConflictFiles, ConflictResolution}, utils_platform::{DEFAULT_EDITOR, SHELL_COMMAND, SHELL_PATH_REFERENCE}},
+    core::{app::App, error::{AppError, AppResult}, svn::{svn_info, svn_resolve}},
+    ui::models::ConflictRegion,
+};
+
+/// 冲突规则文件，放在项目根目录，随项目一起提交
+pub const CONFLICT_RULES_FILE: &str = ".ws-conflicts.toml";
+
+/// 一条冲突规则，pattern 的写法与 .gitignore 相同
+pub struct ConflictRule {
+    pub pattern: String,
+    pub resolution: ConflictResolution,
+    matcher: Gitignore,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<RuleEntry>,
+}
+
+/// resolve 和 command 只能给出一个
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleEntry {
+    pattern: String,
+    resolve: Option<String>,
+    command: Option<String>,
+}
+
+/// 读取项目根目录的冲突规则，没有规则文件时返回空列表
+pub fn load_conflict_rules() -> AppResult<Vec<ConflictRule>> {
+    match fs::read_to_string(CONFLICT_RULES_FILE) {
+        Ok(content) => parse_conflict_rules(&content, CONFLICT_RULES_FILE),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 解析冲突规则，source 用于错误信息
+pub fn parse_conflict_rules(content: &str, source: &str) -> AppResult<Vec<ConflictRule>> {
+    let file: RulesFile = toml::from_str(content)
+        .map_err(|e| AppError::Config(format!("{}: {}", source, e.message())))?;
+
+    file.rules.into_iter().map(|entry| {
+        let resolution = match (entry.resolve.as_deref(), entry.command) {
+            (Some("mine"), None) => ConflictResolution::Mine,
+            (Some("theirs"), None) => ConflictResolution::Theirs,
+            (None, Some(command)) if !command.trim().is_empty() => ConflictResolution::Command(command),
+            (Some(resolve), None) => return Err(AppError::Config(format!("{}: invalid resolve '{}' for '{}', expected mine or theirs", source, resolve, entry.pattern))),
+            _ => return Err(AppError::Config(format!("{}: rule '{}' needs either resolve or command", source, entry.pattern))),
+        };
+        let mut builder = GitignoreBuilder::new("");
+        builder.add_line(None, &entry.pattern)
+            .map_err(|e| AppError::Config(format!("{}: invalid pattern '{}': {}", source, entry.pattern, e)))?;
+        let matcher = builder.build()
+            .map_err(|e| AppError::Config(format!("{}: invalid pattern '{}': {}", source, entry.pattern, e)))?;
+        Ok(ConflictRule { pattern: entry.pattern, resolution, matcher })
+    }).collect()
+}
+
+/// 找出第一条匹配 path 的规则，path 相对于项目根目录
+pub fn match_conflict_rule<'a>(rules: &'a [ConflictRule], path: &str) -> Option<&'a ConflictRule> {
+    let path = path.replace('\\', "/");
+    rules.iter().find(|rule| rule.matcher.matched_path_or_any_parents(&path, false).is_ignore())
+}
+
+/// 冲突规则中的命令通过这个环境变量得到冲突文件的路径
+pub const CONFLICT_PATH_VAR: &str = "WS_CONFLICT_PATH";
+
+/// 按规则解决一个内容冲突
+/// - 命令在仓库版本上运行，成功后才标记为已解决；失败时工作文件和冲突保持不变
+pub fn apply_conflict_resolution(app: &App, path: &str, resolution: &ConflictResolution) -> AppResult<()> {
+    match resolution {
+        ConflictResolution::Mine => svn_resolve(app.svn(), &["--accept", "mine-full", path]),
+        ConflictResolution::Theirs => svn_resolve(app.svn(), &["--accept", "theirs-full", path]),
+        ConflictResolution::Command(command) => {
+            let files = read_conflict_files(app, path)?
+                .ok_or(AppError::Validation(format!("No text conflict found for {}", path.yellow().bold())))?;
+            let original = fs::read(path)?;
+            fs::copy(&files.theirs, path)?;
+            let success = run_external_tool(app, &conflict_shell_command(command), &[(CONFLICT_PATH_VAR, path)]);
+            if !matches!(success, Ok(true)) {
+                fs::write(path, original)?;
+                success?;
+                return Err(AppError::Validation(format!("Regenerating {} with `{}` failed", path.yellow().bold(), command)));
+            }
+            svn_resolve(app.svn(), &["--accept", "working", path])
+        }
+    }
+}
+
+/// 在 shell 中运行冲突规则命令的命令行
+/// - {path} 替换为对环境变量 WS_CONFLICT_PATH 的引用，路径来自仓库，不能直接拼进 shell 命令
+pub fn conflict_shell_command(command: &str) -> Vec<String> {
+    SHELL_COMMAND.iter().map(|s| s.to_string())
+        .chain([command.replace("{path}", SHELL_PATH_REFERENCE)])
+        .collect()
+}
+
+/// 读取内容冲突的三个版本，属性冲突和树冲突返回 None
+pub fn read_conflict_files(app: &App, path: &str) -> AppResult<Option<ConflictFiles>> {
+    parse_conflict_files(&svn_info(app.svn(), &["--xml", path])?, path)
+}
+
+/// 从 svn info --xml 的输出中读取内容冲突的三个版本，path 为冲突文件
+pub fn parse_conflict_files(xml: &str, path: &str) -> AppResult<Option<ConflictFiles>> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let Some(conflict) = doc.descendants().filter(|n| n.has_tag_name("conflict")).find(|n| n.attribute("type").is_none_or(|t| t == "text")) else {
+        return Ok(None);
+    };
+    let file = |tag: &str| conflict.children().find(|c| c.has_tag_name(tag)).and_then(|c| c.text()).map(PathBuf::from);
+
+    let (Some(base), Some(theirs)) = (file("prev-base-file"), file("cur-base-file")) else { return Ok(None) };
+    let mine = file("prev-wc-file").unwrap_or_else(|| PathBuf::from(path));
+    Ok(Some(ConflictFiles { base, mine, theirs }))
+}
+
+/// 与 git 相同，前 8000 个字节中有 0 时视为二进制文件
+pub fn is_binary_file(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|content| content.iter().take(8000).any(|b| *b == 0))
+}
+
+/// 找出文件中的冲突标记，标记不完整的冲突不计入
+pub fn parse_conflict_regions(content: &str) -> Vec<ConflictRegion> {
+    enum Section { Outside, Mine, Base, Theirs }
+
+    let mut regions = Vec::new();
+    let mut section = Section::Outside;
+    let mut current = ConflictRegion { line: 0, mine: Vec::new(), base: Vec::new(), theirs: Vec::new() };
+    for (index, line) in content.lines().enumerate() {
+        match section {
+            _ if line.starts_with("<<<<<<<") => {
+                current = ConflictRegion { line: index + 1, mine: Vec::new(), base: Vec::new(), theirs: Vec::new() };
+                section = Section::Mine;
+            }
+            Section::Outside => {}
+            Section::Mine if line.starts_with("|||||||") => section = Section::Base,
+            Section::Mine | Section::Base if line.starts_with("=======") => section = Section::Theirs,
+            Section::Theirs if line.starts_with(">>>>>>>") => {
+                regions.push(current.clone());
+                section = Section::Outside;
+            }
+            Section::Mine => current.mine.push(line.to_string()),
+            Section::Base => current.base.push(line.to_string()),
+            Section::Theirs => current.theirs.push(line.to_string()),
+        }
+    }
+    regions
+}
+
+/// 文件中是否还有冲突标记
+pub fn has_conflict_markers(path: &str) -> AppResult<bool> {
+    let content = fs::read(path)?;
+    Ok(!parse_conflict_regions(&String::from_utf8_lossy(&content)).is_empty())
+}
+
+/// 把 merge_tool 按空白拆分为命令行，再替换每一段中的占位符，文件路径中可以有空格
+pub fn merge_tool_command(template: &str, files: &ConflictFiles, merged: &str) -> Vec<String> {
+    template.split_whitespace().map(|arg| {
+        arg.replace("{base}", &files.base.to_string_lossy())
+            .replace("{mine}", &files.mine.to_string_lossy())
+            .replace("{theirs}", &files.theirs.to_string_lossy())
+            .replace("{merged}", merged)
+    }).collect()
+}
+
+/// 用编辑器打开 path 的命令行，$VISUAL、$EDITOR 可以带参数，如 code --wait
+pub fn editor_command(path: &str) -> Vec<String> {
+    let editor = ["VISUAL", "EDITOR"].iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    let mut command: Vec<String> = editor.split_whitespace().map(str::to_string).collect();
+    command.push(path.to_string());
+    command
+}
+
+/// 在终端中运行合并工具、编辑器或冲突规则中的命令并等待结束，返回是否正常退出
+/// - envs 为额外设置的环境变量
+pub fn run_external_tool(app: &App, command: &[String], envs: &[(&str, &str)]) -> AppResult<bool> {
+    let (program, args) = command.split_first().ok_or(AppError::Validation("No command to run".to_string()))?;
+    let status = app.ui.suspend(|| Command::new(program).args(args).envs(envs.iter().copied()).status())
+        .map_err(|e| AppError::Validation(format!("Failed to run {}: {}", program.clone().yellow().bold(), e)))?;
+    Ok(status.success())
+}
+
+/// 同时保留两个版本时另一个版本的路径：a/logo.png -> a/logo.theirs.png
+pub fn side_by_side_path(path: &str, label: &str) -> String {
+    let file_start = path.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    match path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => format!("{}.{}{}", &path[..file_start + dot], label, &path[file_start + dot..]),
+        _ => format!("{}.{}", path, label),
+    }
+}
@@ -0,0 +1,171 @@
+//! ws diff 相关工具函数
+//!
+//! svn diff 不包含未受控和缺失的文件，
+//! 按提交时的规则（见 utils_status）为它们补充 diff，这样新文件也会显示
+
+use std::{fs, path::Path};
+
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{utils::{check_url_exists, validate_folder_name}, utils_revision::{ResolvedRevision, resolve_revision}, utils_status::{collect_status_entries, current_ignore_matcher, is_under_paths}},
+    core::{app::App, error::{AppError, AppResult, SvnErrorKind}, svn::{svn_cat, svn_diff}, utils::RevisionExpr},
+    ui::models::{DiffHunk, FileDiff, StatusKind},
+};
+
+/// 比较的对象
+pub enum DiffTarget {
+    /// 工作副本与 BASE 比较
+    WorkingCopy,
+    /// 两个版本之间，to 为 None 时与工作副本比较
+    /// - 版本可以属于同一项目的其他分支，如 feature@r120
+    Revisions { from: RevisionExpr, to: Option<RevisionExpr> },
+    /// 从当前分支到同一项目的另一个分支
+    Branch(String),
+}
+
+/// 生成 diff，paths 为空时比较整个项目
+pub fn build_diff(app: &App, target: &DiffTarget, paths: &[String]) -> AppResult<Vec<FileDiff>> {
+    // svn diff 的参数（不含 paths），以及是否与工作副本比较
+    let (diff_args, include_working_copy) = match target {
+        DiffTarget::WorkingCopy => (Vec::new(), true),
+        DiffTarget::Revisions { from, to } => {
+            let from = resolve_revision(app, from)?;
+            match to {
+                Some(to) => (vec![format!("--old={}", from.url_with_rev()), format!("--new={}", resolve_revision(app, to)?.url_with_rev())], false),
+                None if from.is_current_branch => (vec!["-r".to_string(), from.revision.to_string()], true),
+                None => (vec![format!("--old={}", from.url_with_rev()), "--new=.".to_string()], true),
+            }
+        }
+        DiffTarget::Branch(branch_name) => {
+            validate_folder_name(branch_name, true)?;
+            let old_url = app.svn_ctx.get_current_work_copy_root()?;
+            let new_url = if branch_name == "trunk" {
+                app.svn_ctx.get_current_trunk_url()
+            } else {
+                app.svn_ctx.get_branch_url(branch_name)
+            };
+            if !check_url_exists(app.svn(), &new_url)? {
+                return Err(AppError::Validation(format!("Branch {} does not exist", branch_name.as_str().yellow().bold())));
+            }
+            (vec![format!("--old={}", old_url), format!("--new={}", new_url)], false)
+        }
+    };
+
+    let args: Vec<&str> = diff_args.iter().chain(paths).map(String::as_str).collect();
+    let mut diffs = parse_unified_diff(&svn_diff(app.svn(), &args)?);
+    if include_working_copy {
+        add_auto_scheduled_diffs(app, paths, &mut diffs)?;
+    }
+    Ok(diffs)
+}
+
+/// 一个版本带来的更改，用于 ws show
+/// - 在该版本创建的分支没有上一个版本可以比较，返回空的 diff
+pub fn build_revision_diff(app: &App, target: &ResolvedRevision) -> AppResult<Vec<FileDiff>> {
+    let change = target.revision.to_string();
+    match svn_diff(app.svn(), &["-c", &change, &target.url_with_rev()]) {
+        Ok(diff_text) => Ok(parse_unified_diff(&diff_text)),
+        Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 按 Index: 行把 svn diff 的输出拆分为每个文件的 diff
+pub fn parse_unified_diff(diff_text: &str) -> Vec<FileDiff> {
+    let mut diffs: Vec<FileDiff> = Vec::new();
+    for line in diff_text.lines() {
+        if let Some(path) = line.strip_prefix("Index: ") {
+            diffs.push(FileDiff::new(&path.replace('\\', "/")));
+        }
+        if let Some(diff) = diffs.last_mut() {
+            diff.push_line(line);
+        }
+    }
+    diffs
+}
+
+/// 为提交时会自动添加、删除的文件补充 diff
+fn add_auto_scheduled_diffs(app: &App, paths: &[String], diffs: &mut Vec<FileDiff>) -> AppResult<()> {
+    let ignore_matcher = current_ignore_matcher()?;
+    for entry in collect_status_entries(app.svn(), &ignore_matcher)? {
+        let path = entry.path.replace('\\', "/");
+        if !entry.auto || !is_under_paths(&path, paths) || diffs.iter().any(|d| d.path == path) {
+            continue;
+        }
+
+        match entry.kind {
+            StatusKind::Added if Path::new(&entry.path).is_file() => {
+                diffs.push(whole_file_diff(&path, &fs::read(&entry.path)?, true));
+            }
+            StatusKind::Deleted => {
+                let base = svn_cat(app.svn(), &["-r", "BASE", &entry.path])?;
+                diffs.push(whole_file_diff(&path, &base, false));
+            }
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// 整个文件被添加或删除的 diff
+fn whole_file_diff(path: &str, content: &[u8], is_added: bool) -> FileDiff {
+    let mut diff = FileDiff::new(path);
+    diff.push_line(&format!("Index: {}", path));
+    diff.push_line(&"=".repeat(67));
+    if is_added {
+        diff.push_line(&format!("--- {}\t(nonexistent)", path));
+        diff.push_line(&format!("+++ {}\t(working copy)", path));
+    } else {
+        diff.push_line(&format!("--- {}\t(revision BASE)", path));
+        diff.push_line(&format!("+++ {}\t(nonexistent)", path));
+    }
+
+    let Ok(text) = std::str::from_utf8(content) else {
+        diff.push_line("Cannot display: file is not text.");
+        return diff;
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.is_empty() {
+        return diff;
+    }
+
+    let (sign, hunk) = if is_added {
+        ('+', format!("@@ -0,0 +1,{} @@", lines.len()))
+    } else {
+        ('-', format!("@@ -1,{} +0,0 @@", lines.len()))
+    };
+    diff.push_line(&hunk);
+    for line in lines {
+        diff.push_line(&format!("{}{}", sign, line));
+    }
+    diff
+}
+
+/// 在 base 上只应用选中的修改块，得到要提交的文件内容
+/// - 选中的块直接取 working 中对应的行，保留原来的换行符和编码
+pub fn apply_hunks(base: &[u8], working: &[u8], hunks: &[DiffHunk], selected: &[bool]) -> Vec<u8> {
+    let base_lines: Vec<&[u8]> = base.split_inclusive(|b| *b == b'\n').collect();
+    let working_lines: Vec<&[u8]> = working.split_inclusive(|b| *b == b'\n').collect();
+
+    let mut content = Vec::with_capacity(working.len());
+    let mut copied = 0;
+    for (hunk, is_selected) in hunks.iter().zip(selected) {
+        // 行数为 0 时，起始行是修改块之前的一行
+        let start = if hunk.old_len == 0 { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+        let start = start.clamp(copied, base_lines.len());
+        let end = (start + hunk.old_len).min(base_lines.len());
+        base_lines[copied..start].iter().for_each(|line| content.extend_from_slice(line));
+
+        if *is_selected {
+            let new_start = hunk.new_start.saturating_sub(1).min(working_lines.len());
+            let new_end = (new_start + hunk.new_len).min(working_lines.len());
+            working_lines[new_start..new_end].iter().for_each(|line| content.extend_from_slice(line));
+        } else {
+            base_lines[start..end].iter().for_each(|line| content.extend_from_slice(line));
+        }
+        copied = end;
+    }
+    base_lines[copied..].iter().for_each(|line| content.extend_from_slice(line));
+    content
+}
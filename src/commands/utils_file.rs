@@ -1,54 +1,18 @@
-use std::{fs::{File, OpenOptions}, io::{Read, Seek, SeekFrom, Write}, os::windows::io::AsRawHandle, path::Path};
+use std::{fs::{File, OpenOptions}, io::{Read, Seek, SeekFrom, Write}, path::Path};
 
-use crossterm::style::Stylize;
-use windows_sys::Win32::{Foundation::HANDLE, Storage::FileSystem::{LOCKFILE_EXCLUSIVE_LOCK, LockFileEx, UnlockFileEx}, System::IO::OVERLAPPED};
+use crate::{commands::utils_platform::{lock_file_exclusive, unlock_file}, core::{app::App, error::{AppError, AppResult}}};
 
-use crate::{commands::utils_windows::find_a_project_in_ws_store, core::{app::App, error::{AppError, AppResult}}};
 
-
-fn get_lock_file(file_path: &Path) -> AppResult<(File, HANDLE)> {
+fn get_lock_file(file_path: &Path) -> AppResult<File> {
     let file = OpenOptions::new()
         .read(true)
         .write(true)
         .create(true)
+        .truncate(false)
         .open(file_path)?;
 
-    let handle = file.as_raw_handle() as HANDLE;
-
-    unsafe {
-        let mut overlapped: OVERLAPPED = std::mem::zeroed();
-        let result = LockFileEx(
-            handle,
-            LOCKFILE_EXCLUSIVE_LOCK,
-            0,
-            u32::MAX, // 锁定区域长度低位
-            u32::MAX, // 锁定区域长度高位（锁住整个巨大的范围）
-            &mut overlapped,
-        );
-
-        if result == 0 {
-            return Err(AppError::Validation(format!("Can not lock file: {}", file_path.display())));
-        }
-    }
-    Ok((file, handle))
-}
-
-fn release_lock_file(handle: HANDLE) -> AppResult<()> {
-    unsafe {
-        let mut overlapped: OVERLAPPED = std::mem::zeroed();
-        let result = UnlockFileEx(
-            handle,
-            0, // Reserved
-            u32::MAX,
-            u32::MAX,
-            &mut overlapped,
-        );
-        
-        if result == 0 {
-             return Err(AppError::Validation(format!("Can not unlock file: {:?}", handle)));
-        }
-    }
-    Ok(())
+    lock_file_exclusive(&file, file_path)?;
+    Ok(file)
 }
 
 pub enum ChangeLockType {
@@ -58,7 +22,7 @@ pub enum ChangeLockType {
 }
 
 pub fn change_lock_file(file_path: &Path, lock_type: ChangeLockType) -> AppResult<u64> {
-    let (mut file, handle) = get_lock_file(file_path)?;
+    let mut file = get_lock_file(file_path)?;
     
     // 读取内容
     let mut content = String::new();
@@ -86,10 +50,10 @@ pub fn change_lock_file(file_path: &Path, lock_type: ChangeLockType) -> AppResul
     // 回写
     file.seek(SeekFrom::Start(0))?;
     file.set_len(0)?; // 截断文件
-    file.write(new_val.to_string().as_bytes())?;
+    file.write_all(new_val.to_string().as_bytes())?;
 
     // 释放锁
-    release_lock_file(handle)?;
+    unlock_file(&file, file_path)?;
 
     Ok(new_val)
 }
@@ -109,25 +73,22 @@ pub fn check_is_empty_folder(path: &Path) -> AppResult<bool> {
     Ok(entries.next().is_none())
 }
 
-pub fn ensure_delete(app: &App) -> AppResult<bool> {
-    let current_project_path = match find_a_project_in_ws_store(&app.svn_ctx.get_repo_name()?, app.svn_ctx.get_current_project_name())? {
+/// 检查项目是否可以删除：没有任何工作区在使用该项目
+pub fn ensure_delete(app: &App, project_name: &str) -> AppResult<bool> {
+    let project_path = match app.svn_ctx.find_project_in_ws_store(project_name)? {
         Some(p) => p,
-        None => {
-            return Err(AppError::Validation(format!("Current project {} is not checked out in any workspace.", app.svn_ctx.get_current_project_name().yellow().bold())));
-        }
+        None => return Ok(true), // 从未检出过，不会被任何工作区使用
     };
 
-    let current_lock_file_path = get_lock_file_path(
-        current_project_path.parent().ok_or(AppError::Validation(format!("No parent folder found")))?, 
-        app.svn_ctx.get_current_project_name()
+    let lock_file_path = get_lock_file_path(
+        project_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?,
+        project_name
     )?;
 
-    if change_lock_file(&current_lock_file_path, ChangeLockType::Delete)? > 0 { 
-        Ok(false) 
+    if change_lock_file(&lock_file_path, ChangeLockType::Delete)? > 0 {
+        Ok(false)
     }
-    else { 
-        Ok(true) 
+    else {
+        Ok(true)
     }
-   
 }
-
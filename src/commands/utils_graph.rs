@@ -0,0 +1,249 @@
+//! ws log --graph 的分支图
+//!
+//! - 每个分支一列，trunk 固定在第一列，已经结束的分支的列可以给之后创建的分支使用
+//! - 分支的起点来自分支根目录的 copyfrom-path，合并的来源来自 svn log -g 的嵌套日志
+//! - 合并回来后删除的分支在 HEAD 上已经不存在，以合并记录中来源的版本为 peg 读取它的历史
+//! - 跨分支的边画在子提交的下一行：`'` 为分支的起点，`.` 为合并来源的列在此处开始，`+` 为连到仍在继续的列
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    commands::{models::{LogQuery, SVNLogType}, utils::callback_for_log_xml, utils_branch::{extract_branch_name_from_path, get_branch_renames, get_project_branches}, utils_log::classify_log_entry},
+    core::{app::App, error::AppResult, utils::Revision},
+    ui::models::{GraphCommit, LogEntry, LogEntryKind},
+};
+
+/// 读取项目所有分支的提交，按版本号从新到旧排列
+/// - limit 为所有分支合计的提交数
+/// - 合并回来后已经删除的分支，按合并记录中的版本读取它的历史
+pub fn build_log_graph(app: &App, limit: Option<u32>) -> AppResult<Vec<GraphCommit>> {
+    let project_name = app.svn_ctx.get_current_project_name().to_string();
+    let mut reader = GraphReader { app, limit, commits: Vec::new(), sources: BTreeMap::new() };
+    let mut read_branches = Vec::new();
+
+    for branch in get_project_branches(app, &project_name)? {
+        let name = branch.branch_name;
+        let (url, root) = if name == "trunk" {
+            (app.svn_ctx.get_current_trunk_url(), "/trunk".to_string())
+        } else {
+            (app.svn_ctx.get_branch_url(&name), format!("/branches/{}", name))
+        };
+
+        // 改名过的分支还要读取改名前的部分，改名的移动不算作边
+        let renames = get_branch_renames(app, &name)?;
+        let mut segments = vec![(url, root, None)];
+        for (old_name, last_rev) in &renames {
+            segments.push((format!("{}@{}", app.svn_ctx.get_branch_url(old_name), last_rev), format!("/branches/{}", old_name), Some(*last_rev)));
+        }
+        reader.read_branch(&name, segments, &renames, branch.is_current_branch)?;
+
+        read_branches.extend(renames.into_iter().map(|(old_name, _)| old_name));
+        read_branches.push(name);
+    }
+
+    // 只读取合并在显示范围内的已删除分支，它们的提交又可能合并自其他已删除的分支
+    loop {
+        let shown_from = reader.shown_from();
+        let Some((name, peg)) = reader.sources.iter()
+            .find(|(name, (_, merged_at))| !read_branches.contains(name) && *merged_at >= shown_from)
+            .map(|(name, (peg, _))| (name.clone(), *peg)) else { break };
+
+        let segment = (format!("{}@{}", app.svn_ctx.get_branch_url(&name), peg), format!("/branches/{}", name), Some(peg));
+        reader.read_branch(&name, vec![segment], &[], false)?;
+        read_branches.push(name);
+    }
+
+    let mut commits = reader.commits;
+    commits.sort_by_key(|c| std::cmp::Reverse(c.entry.revision));
+    if let Some(limit) = limit {
+        commits.truncate(limit as usize);
+    }
+    Ok(commits)
+}
+
+/// 读取各分支的提交，并记录合并的来源
+struct GraphReader<'a> {
+    app: &'a App,
+    limit: Option<u32>,
+    commits: Vec<GraphCommit>,
+    /// 合并的来源分支 -> (合并记录中来源存在的最新版本, 最新一次合并的版本)
+    sources: BTreeMap<String, (u64, u64)>,
+}
+
+impl GraphReader<'_> {
+    /// 读取一个分支的提交，segments 为 (url, 分支根目录, 起始版本)，改名前的部分在后
+    fn read_branch(&mut self, name: &str, segments: Vec<(String, String, Option<u64>)>, renames: &[(String, u64)], is_current_branch: bool) -> AppResult<()> {
+        let (app, commits, sources) = (self.app, &mut self.commits, &mut self.sources);
+        let current_rev = app.svn_ctx.get_current_revision();
+
+        for (url, root, start) in segments {
+            let callback = |xml_doc: &roxmltree::Document| -> AppResult<()> {
+                for entry in xml_doc.root_element().children().filter(|n| n.has_tag_name("logentry")) {
+                    let Some(kind) = classify_log_entry(app, entry, name)? else { continue };
+                    let revision: u64 = entry.attribute("revision").and_then(|r| r.parse().ok()).unwrap_or(0);
+                    let text = |tag: &str| entry.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text()).unwrap_or("").to_string();
+
+                    // 复制出分支根目录的提交连到复制的来源，恢复分支时来源是分支自己，不算作边
+                    let copied_from = entry.descendants()
+                        .filter(|n| n.has_tag_name("path") && n.parent().and_then(|p| p.parent()) == Some(entry))
+                        .find(|n| n.text().is_some_and(|p| p.trim_end_matches('/').ends_with(&root)))
+                        .and_then(|n| n.attribute("copyfrom-path"))
+                        .and_then(|p| extract_branch_name_from_path(app, p).ok());
+                    let merged_from = match &kind {
+                        LogEntryKind::Merge { source } | LogEntryKind::Pick { source, .. } => Some(source.clone()),
+                        _ => None,
+                    };
+
+                    // 挑选的版本和 svn log -g 中嵌套的版本都在来源分支上，来源删除后从这里读取它的历史
+                    if let Some(source) = merged_from.as_ref().filter(|from| *from != name) {
+                        let peg = match &kind {
+                            LogEntryKind::Pick { revisions, .. } => revisions.iter().max().copied(),
+                            _ => entry.children()
+                                .filter(|n| n.has_tag_name("logentry"))
+                                .filter(|n| n.descendants().any(|p| p.has_tag_name("path")
+                                    && extract_branch_name_from_path(app, p.text().unwrap_or("")).is_ok_and(|b| b == *source)))
+                                .filter_map(|n| n.attribute("revision")?.parse().ok())
+                                .max(),
+                        }.unwrap_or(revision.saturating_sub(1));
+                        let known = sources.entry(source.clone()).or_insert((peg, revision));
+                        *known = (known.0.max(peg), known.1.max(revision));
+                    }
+
+                    let from_branch = copied_from.or(merged_from).filter(|from| from != name && !renames.iter().any(|(old_name, _)| old_name == from));
+                    commits.push(GraphCommit {
+                        entry: LogEntry {
+                            revision,
+                            date: text("date"),
+                            author: text("author"),
+                            message: text("msg"),
+                            kind,
+                            is_current: is_current_branch && Revision::Number(revision) == *current_rev,
+                            tags: Vec::new(),
+                        },
+                        branch: name.to_string(),
+                        from_branch,
+                    });
+                }
+                Ok(())
+            };
+
+            // 合计的前 N 个提交一定在各分支各自的前 N 个提交中
+            let query = LogQuery { limit: self.limit, start, ..Default::default() };
+            callback_for_log_xml(app.svn(), &url, SVNLogType::WsLog(query), callback)?;
+        }
+        Ok(())
+    }
+
+    /// 已读取的提交中，会显示的最早的版本
+    fn shown_from(&self) -> u64 {
+        let mut revisions: Vec<u64> = self.commits.iter().map(|c| c.entry.revision).collect();
+        revisions.sort_unstable_by(|a, b| b.cmp(a));
+        self.limit.and_then(|limit| revisions.get((limit as usize).checked_sub(1)?)).copied().unwrap_or(0)
+    }
+}
+
+/// 图中的一行
+#[derive(Debug, PartialEq, Eq)]
+pub struct GraphLine {
+    pub graph: String,
+    /// 提交所在的行为提交的下标，连接两个分支的行为 None
+    pub commit: Option<usize>,
+}
+
+/// 分支在图中占据的版本范围 [min, max] 和所在的列
+struct Lane {
+    min: u64,
+    max: u64,
+    column: usize,
+}
+
+impl Lane {
+    /// 在版本 revision 的提交行上穿过
+    fn passes(&self, revision: u64) -> bool {
+        self.min < revision && revision < self.max
+    }
+
+    /// 在版本 revision 的下一行继续
+    fn continues_below(&self, revision: u64) -> bool {
+        self.min < revision && revision <= self.max
+    }
+}
+
+/// 为按版本号从新到旧排列的提交画出分支图
+pub fn layout_graph(commits: &[GraphCommit]) -> Vec<GraphLine> {
+    let mut lanes: HashMap<&str, Lane> = HashMap::new();
+    for commit in commits {
+        let revision = commit.entry.revision;
+        let lane = lanes.entry(commit.branch.as_str()).or_insert(Lane { min: revision, max: revision, column: 0 });
+        lane.min = lane.min.min(revision);
+        lane.max = lane.max.max(revision);
+    }
+
+    // 来源分支在显示范围内更早的版本上存在时才画边，来源的列延续到子提交
+    let edges: Vec<Option<&str>> = commits.iter().map(|commit| {
+        commit.from_branch.as_deref()
+            .filter(|from| lanes.get(from).is_some_and(|lane| lane.min < commit.entry.revision))
+    }).collect();
+    for (commit, from) in commits.iter().zip(&edges) {
+        if let Some(lane) = from.and_then(|from| lanes.get_mut(from)) {
+            lane.max = lane.max.max(commit.entry.revision);
+        }
+    }
+
+    // 按创建顺序分配列，trunk 独占第一列
+    let mut order: Vec<&str> = lanes.keys().copied().collect();
+    order.sort_by_key(|name| (*name != "trunk", lanes[name].min, *name));
+    let mut columns: Vec<Vec<(u64, u64)>> = Vec::new();
+    for name in order {
+        let lane = lanes.get_mut(name).unwrap();
+        let span = if name == "trunk" { (0, u64::MAX) } else { (lane.min, lane.max) };
+        lane.column = match columns.iter().position(|spans| spans.iter().all(|&(min, max)| max < span.0 || span.1 < min)) {
+            Some(column) => column,
+            None => { columns.push(Vec::new()); columns.len() - 1 },
+        };
+        columns[lane.column].push(span);
+    }
+
+    let width = columns.len();
+    let in_column = |column: usize, f: &dyn Fn(&Lane) -> bool| lanes.values().any(|lane| lane.column == column && f(lane));
+
+    let mut lines = Vec::new();
+    for (index, (commit, from)) in commits.iter().zip(&edges).enumerate() {
+        let revision = commit.entry.revision;
+        let lane = &lanes[commit.branch.as_str()];
+
+        let cells: Vec<char> = (0..width).map(|column| {
+            if column == lane.column { '*' }
+            else if in_column(column, &|l| l.passes(revision)) { '|' }
+            else { ' ' }
+        }).collect();
+        lines.push(GraphLine { graph: join_cells(&cells, |_| ' '), commit: Some(index) });
+
+        let Some(from_lane) = from.map(|from| &lanes[from]) else { continue };
+        let (low, high) = (lane.column.min(from_lane.column), lane.column.max(from_lane.column));
+        let cells: Vec<char> = (0..width).map(|column| {
+            let continues = in_column(column, &|l| l.continues_below(revision));
+            if column == lane.column {
+                if lane.min < revision { '|' } else { '\'' }
+            } else if column == from_lane.column {
+                if from_lane.max > revision { '+' } else { '.' }
+            } else if low < column && column < high {
+                if continues { '+' } else { '-' }
+            } else if continues { '|' } else { ' ' }
+        }).collect();
+        lines.push(GraphLine { graph: join_cells(&cells, |column| if low <= column && column < high { '-' } else { ' ' }), commit: None });
+    }
+    lines
+}
+
+/// 每列一个字符，列之间的字符由 separator 决定
+fn join_cells(cells: &[char], separator: impl Fn(usize) -> char) -> String {
+    let mut line = String::new();
+    for (column, cell) in cells.iter().enumerate() {
+        line.push(*cell);
+        if column + 1 < cells.len() {
+            line.push(separator(column));
+        }
+    }
+    line.trim_end().to_string()
+}
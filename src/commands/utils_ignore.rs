@@ -8,7 +8,7 @@ use std::path::Path;
 
 use ignore::{Walk, gitignore::{Gitignore, GitignoreBuilder}};
 
-use crate::core::{error::{AppError, AppResult}, svn::{StatusType, svn_commit_externals, svn_commit_gitignore, svn_propdel, svn_propset, svn_status, svn_update}};
+use crate::core::{error::{AppError, AppResult}, svn::{StatusType, svn_commit_externals, svn_commit_gitignore, svn_propdel, svn_propset, svn_status, svn_update}, svn_executor::SvnExecutor};
 
 /// 构建忽略规则匹配器
 pub fn build_ignore_matcher(target_path: &Path, gitignore_root_path: &Path) -> AppResult<Gitignore> {
@@ -19,7 +19,7 @@ pub fn build_ignore_matcher(target_path: &Path, gitignore_root_path: &Path) -> A
         builder.add(gitignore_root_path.join(".gitignore"));
     }
     else {
-        return Err(AppError::Validation(format!("No gitignore file!")));
+        return Err(AppError::Validation("No gitignore file!".to_string()));
     }
 
     Ok(builder.build()?)
@@ -29,13 +29,7 @@ pub fn build_ignore_matcher(target_path: &Path, gitignore_root_path: &Path) -> A
 pub fn build_folder_walker(item: &Path) -> AppResult<Walk> {
     let filter = |entry: &ignore::DirEntry| {
         let path = entry.file_name();
-        if path == ".git" {
-            return false;
-        }
-        else if path == ".svn" {
-            return false;
-        }
-        true
+        path != ".git" && path != ".svn"
     };
 
     let mut walker = ignore::WalkBuilder::new(item);
@@ -50,38 +44,35 @@ pub fn build_folder_walker(item: &Path) -> AppResult<Walk> {
 }
 
 /// 自动同步 .gitignore 文件的修改
-pub fn auto_sync_ignore_rules(project_name: &str) -> AppResult<()> {
-    let xml_str = svn_status(StatusType::CheckGitignore)?;
+pub fn auto_sync_ignore_rules(svn: &dyn SvnExecutor, project_name: &str) -> AppResult<()> {
+    let xml_str = svn_status(svn, StatusType::CheckGitignore)?;
     let doc = roxmltree::Document::parse(&xml_str)?;
 
     if let Some(wc_status) = doc.descendants().find(|n| n.has_tag_name("wc-status")) {
         let item = wc_status.attribute("item").unwrap_or("");
         // 先执行 svn update 确保是最新的
-        svn_update(&[".gitignore", "--accept", "working"])?;
-        match item {
-            "modified" => {
-                // 提交
-                svn_commit_gitignore()?;
-            },
-            _ => {},
+        svn_update(svn, &[".gitignore", "--accept", "working"])?;
+        if item == "modified" {
+            // 提交
+            svn_commit_gitignore(svn)?;
         };
     }
     else {
         // 没有 .gitignore 文件，使用 svn:external 从项目根目录链接一个
         // svn propset svn:externals "^/{project_name}/.gitignore .gitignore" .
-        svn_propset(&["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), "."])?;
+        svn_propset(svn, &["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), "."])?;
         // 然后应该提交这个 externals 设置
-        svn_commit_externals(".", false)?;
+        svn_commit_externals(svn, ".", false)?;
         // 然后执行 svn update
-        svn_update(&["."])?;
+        svn_update(svn, &["."])?;
     }
 
     Ok(())
 }
 
 /// 处理剩余的未受控文件
-pub fn set_remaining_unversioned_as_ignored(project_name: &str) -> AppResult<()> {
-    let xml_str = svn_status(StatusType::Commit)?;
+pub fn set_remaining_unversioned_as_ignored(svn: &dyn SvnExecutor, project_name: &str) -> AppResult<()> {
+    let xml_str = svn_status(svn, StatusType::Commit)?;
     let doc = roxmltree::Document::parse(&xml_str)?;
     // let mut ignore_targets: HashMap<&str, Vec<&str>> = HashMap::new();
 
@@ -107,10 +98,10 @@ pub fn set_remaining_unversioned_as_ignored(project_name: &str) -> AppResult<()>
                 && wc_status.attribute("switched").unwrap_or("") == "true"
                 && entry.attribute("path").unwrap_or("") == ".gitignore" {
                 // 处理 switched 状态的 .gitignore 文件，删除 svn:externals 属性，update 后重新设置
-                svn_propdel(&["svn:externals", "."])?;
-                svn_update(&["."])?;
-                svn_propset(&["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), "."])?;
-                svn_update(&["."])?;
+                svn_propdel(svn, &["svn:externals", "."])?;
+                svn_update(svn, &["."])?;
+                svn_propset(svn, &["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), "."])?;
+                svn_update(svn, &["."])?;
             }
         }
     }
@@ -128,7 +119,7 @@ pub fn set_remaining_unversioned_as_ignored(project_name: &str) -> AppResult<()>
     //         },
     //     };
     //     let final_value = format!("{}\n{}", names.join("\n"), origin_value);
-    //     svn_propset(&["svn:ignore", &final_value, parent])?;
+    //     svn_propset(svn, &["svn:ignore", &final_value, parent])?;
     // }
     
     Ok(())
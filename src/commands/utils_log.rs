@@ -0,0 +1,181 @@
+//! ws log、ws show 的日志处理
+//!
+//! - 按 [WS-*] 约定识别日志的种类
+//! - 过滤：起始时间和路径交给 svn log，作者、结束时间和 --grep 在识别种类之后检查
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+
+use crate::{
+    commands::{models::{LogFilter, LogQuery}, utils::get_copy_source_rev, utils_branch::{extract_branch_name_from_path, get_branch_source}, utils_pick::parse_pick_message, utils_revert::{parse_rollback_paths_message, parse_undo_message}},
+    core::{app::App, error::{AppError, AppResult}, utils::{Revision, parse_date_arg, parse_revision_arg}},
+    ui::models::{LogEntry, LogEntryKind},
+};
+
+/// 按 [WS-*] 约定和合并记录（svn log -g）识别一条日志的种类
+/// - branch 为日志所在的分支，从其他分支合并过来的提交识别为 Merge
+/// - 返回 None 的日志（[WS-INIT] 等内部提交）不显示在 ws log 中
+pub fn classify_log_entry(app: &App, entry: roxmltree::Node, branch: &str) -> AppResult<Option<LogEntryKind>> {
+    let msg = entry.children().find(|n| n.has_tag_name("msg"))
+        .map(|n| n.text().unwrap_or("")).unwrap_or("");
+
+    let mut merge_source = None;
+    for child in entry.children().filter(|n| n.has_tag_name("logentry")) {
+        if let Some(paths_node) = child.children().find(|n| n.has_tag_name("paths")) {
+            for path_node in paths_node.children().filter(|n| n.has_tag_name("path")) {
+                let path_text = path_node.text().unwrap_or("");
+                if let Ok(source) = extract_branch_name_from_path(app, path_text) {
+                    // 只要找到一个特征，就认定为来源（排除自己）
+                    if source != branch {
+                        merge_source = Some(source);
+                        break;
+                    }
+                }
+            }
+        }
+        if merge_source.is_some() { break; }
+    }
+
+    let kind = if let Some((paths, source_revision)) = parse_rollback_paths_message(msg) {
+        LogEntryKind::Rollback { source_revision: Some(source_revision), paths }
+    }
+    else if msg.starts_with("[WS-ROLLBACK]") {
+        let tag_path = msg.trim_start_matches("[WS-ROLLBACK] ").trim();
+        let source_revision = get_copy_source_rev(app, tag_path)?;
+        LogEntryKind::Rollback { source_revision, paths: Vec::new() }
+    } 
+    else if msg.starts_with("[WS-BRANCH]") {
+        // [WS-BRANCH] Create {}
+        // 优先使用日志中记录的复制来源，分支改名后按提交信息中的旧名称已经找不到分支
+        let copied_from = entry.children().find(|n| n.has_tag_name("paths"))
+            .and_then(|paths| paths.children().find(|n| n.has_tag_name("path") && n.attribute("copyfrom-path").is_some()))
+            .and_then(|n| Some((extract_branch_name_from_path(app, n.attribute("copyfrom-path")?).ok()?, n.attribute("copyfrom-rev")?.parse().ok()?)));
+        let (source, source_revision) = match copied_from {
+            Some(copied_from) => copied_from,
+            None => get_branch_source(app, msg.trim_start_matches("[WS-BRANCH] Create "))?,
+        };
+        LogEntryKind::Branch { source, source_revision }
+    }
+    else if msg.starts_with("[WS-INIT]") {
+        // 直接忽略初始化的提交
+        return Ok(None);
+    }
+    else if msg.starts_with("[WS-INIT-GITIGNORE]") {
+        // 初始化 .gitignore 的提交，作为仓库最初的提交，显示为 Init
+        LogEntryKind::Init { project: app.svn_ctx.get_current_project_name().to_string() }
+    }
+    else if msg.starts_with("[WS-RESOLV-GITIGNORE]") {
+        // 处理 .gitignore externals 的提交，直接忽略
+        return Ok(None);
+    }
+    else if let Some(revisions) = parse_undo_message(msg) {
+        LogEntryKind::Undo { revisions }
+    }
+    else if let Some((source, revisions)) = parse_pick_message(msg) {
+        // 挑选的提交也带有合并记录，要在 Merge 之前识别
+        LogEntryKind::Pick { source, revisions }
+    }
+    else if let Some(source) = merge_source {
+        LogEntryKind::Merge { source }
+    }
+    else {
+        LogEntryKind::Commit
+    };
+    Ok(Some(kind))
+}
+
+/// 把一条 logentry 读取为 LogEntry，种类见 classify_log_entry
+/// - current_rev 为工作副本的版本，日志不在当前分支上时为 None
+pub fn parse_log_entry(app: &App, entry: roxmltree::Node, branch: &str, current_rev: Option<&Revision>) -> AppResult<Option<LogEntry>> {
+    let text = |tag: &str| entry.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text()).unwrap_or("").to_string();
+    let Some(kind) = classify_log_entry(app, entry, branch)? else { return Ok(None) };
+
+    let revision = match parse_revision_arg(entry.attribute("revision").unwrap_or("0"))? {
+        Revision::Number(n) => n,
+        Revision::Head => 0,
+    };
+
+    Ok(Some(LogEntry {
+        revision,
+        date: text("date"),
+        author: text("author"),
+        message: text("msg"),
+        kind,
+        is_current: current_rev.is_some_and(|rev| Revision::Number(revision) == *rev),
+        tags: Vec::new(),
+    }))
+}
+
+/// 不会带来内容的 ws 内部提交：创建、改名分支和初始化项目
+pub fn is_internal_commit(message: &str) -> bool {
+    ["[WS-BRANCH", "[WS-INIT]", "[WS-RESOLV-GITIGNORE]"].iter().any(|prefix| message.starts_with(prefix))
+}
+
+/// 解析后的过滤条件
+pub struct LogMatcher {
+    author: Option<String>,
+    since: Option<DateTime<Local>>,
+    until: Option<DateTime<Local>>,
+    grep: Option<Regex>,
+}
+
+impl LogMatcher {
+    /// 解析过滤条件，相对时间以 now 为基准
+    pub fn new(filter: &LogFilter, now: DateTime<Local>) -> AppResult<Self> {
+        let grep = match &filter.grep {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| AppError::Validation(format!("Invalid --grep pattern: {}", e)))?),
+            None => None,
+        };
+
+        Ok(LogMatcher {
+            author: filter.author.as_ref().map(|a| a.to_lowercase()),
+            since: filter.since.as_deref().map(|s| parse_date_arg(s, now)).transpose()?,
+            until: filter.until.as_deref().map(|s| parse_date_arg(s, now)).transpose()?,
+            grep,
+        })
+    }
+
+    /// 是否只能在读取日志后过滤，此时 svn log 不能使用 --limit
+    pub fn filters_entries(&self) -> bool {
+        self.author.is_some() || self.until.is_some() || self.grep.is_some()
+    }
+
+    /// 交给 svn log 的条件
+    pub fn query(&self, filter: &LogFilter, limit: Option<u32>) -> LogQuery {
+        LogQuery {
+            limit: if self.filters_entries() { None } else { limit },
+            start: None,
+            since: self.since.map(|since| since.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            paths: normalize_log_paths(&filter.paths),
+        }
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(author) = &self.author
+            && !entry.author.to_lowercase().contains(author) {
+            return false;
+        }
+        if let Some(grep) = &self.grep
+            && !grep.is_match(&entry.message) {
+            return false;
+        }
+
+        // svn 的 -r {since} 会包括 since 之前的最后一次提交，这里再精确比较一次
+        if self.since.is_some() || self.until.is_some() {
+            let Ok(date) = DateTime::parse_from_rfc3339(&entry.date) else { return false };
+            if self.since.is_some_and(|since| date < since) || self.until.is_some_and(|until| date > until) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 路径统一使用 /，去掉 ./ 和末尾的 /，项目根目录本身不需要作为路径
+pub fn normalize_log_paths(paths: &[String]) -> Vec<String> {
+    paths.iter()
+        .map(|p| p.replace('\\', "/"))
+        .map(|p| p.trim_start_matches("./").trim_end_matches('/').to_string())
+        .filter(|p| !p.is_empty() && p != ".")
+        .collect()
+}
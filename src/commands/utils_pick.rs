@@ -0,0 +1,113 @@
+//! ### ws pick 相关的工具函数
+//!
+//! - 只把其他分支上选中的版本合并到当前分支，用于把修复移植到发布分支
+//! - 提交信息以 [WS-PICK] r12,r15 from trunk 开头，ws log 据此显示来源
+
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{models::SVNLogType, utils::callback_for_log_xml, utils_branch::extract_branch_name_from_path, utils_log::is_internal_commit, utils_revision::resolve_revision},
+    core::{app::App, error::{AppError, AppResult}, utils::{RevisionBase, parse_revision_expr}},
+};
+
+/// 挑选的一个版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickedRevision {
+    pub revision: u64,
+    /// 原始提交信息
+    pub message: String,
+}
+
+/// 解析 ws pick 的版本列表，返回来源分支和按版本排序的提交
+/// - 每一项是一个版本表达式，没有写分支时使用 from
+/// - 没有 from 时由第一个版本更改的路径确定来源分支
+pub fn resolve_picked_revisions(app: &App, revisions: &str, from: Option<&str>) -> AppResult<(String, Vec<PickedRevision>)> {
+    let current_branch = app.svn_ctx.get_current_branch_name()?;
+    let mut source = from.map(str::to_string);
+    let mut picked: Vec<PickedRevision> = Vec::new();
+
+    for item in revisions.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut expr = parse_revision_expr(item)?;
+        if expr.branch.is_none() {
+            expr.branch = source.clone();
+        }
+        let revision = match (&expr.branch, &expr.base) {
+            (None, RevisionBase::Number(n)) if expr.offset == 0 => *n,
+            (None, _) => return Err(AppError::Validation(format!("Specify the source branch of {} with --from", item.yellow().bold()))),
+            (Some(_), _) => resolve_revision(app, &expr)?.revision,
+        };
+
+        let (branches, message) = read_revision(app, revision)?;
+        let branch = match expr.branch {
+            Some(branch) => branch,
+            None if branches.len() == 1 => branches[0].clone(),
+            None => return Err(AppError::Validation(format!("Cannot tell the source branch of r{}, specify it with --from", revision))),
+        };
+
+        if !branches.contains(&branch) {
+            return Err(AppError::Validation(format!("r{} did not change branch {}", revision, branch.yellow().bold())));
+        }
+        if branch == current_branch {
+            return Err(AppError::Validation(format!("r{} is already on the current branch {}", revision, branch.yellow().bold())));
+        }
+        if source.as_ref().is_some_and(|s| *s != branch) {
+            return Err(AppError::Validation("All picked revisions must come from the same branch".to_string()));
+        }
+        if is_internal_commit(&message) {
+            return Err(AppError::Validation(format!("r{} does not change any files and cannot be picked", revision)));
+        }
+
+        source = Some(branch);
+        if !picked.iter().any(|p| p.revision == revision) {
+            picked.push(PickedRevision { revision, message });
+        }
+    }
+
+    let Some(source) = source.filter(|_| !picked.is_empty()) else {
+        return Err(AppError::Validation("No revisions to pick".to_string()));
+    };
+    picked.sort_by_key(|p| p.revision);
+    Ok((source, picked))
+}
+
+/// 读取一个版本在当前项目中更改的分支和提交信息
+fn read_revision(app: &App, revision: u64) -> AppResult<(Vec<String>, String)> {
+    let project_url = app.svn_ctx.get_current_project_repo_root_url();
+    let callback = |doc: &roxmltree::Document| -> AppResult<Option<(Vec<String>, String)>> {
+        let Some(entry) = doc.root_element().children().find(|n| n.has_tag_name("logentry")) else { return Ok(None) };
+        let message = entry.children().find(|n| n.has_tag_name("msg")).and_then(|n| n.text()).unwrap_or("").to_string();
+
+        // 嵌套的 logentry 是合并进来的版本，只看这个版本自己的路径
+        let mut branches: Vec<String> = Vec::new();
+        for path in entry.children().filter(|n| n.has_tag_name("paths")).flat_map(|n| n.children().filter(|p| p.has_tag_name("path"))) {
+            if let Ok(branch) = extract_branch_name_from_path(app, path.text().unwrap_or(""))
+                && !branches.contains(&branch) {
+                    branches.push(branch);
+                }
+        }
+        Ok(Some((branches, message)))
+    };
+
+    callback_for_log_xml(app.svn(), &project_url, SVNLogType::Revision { revision }, callback)?
+        .ok_or_else(|| AppError::Validation(format!("r{} did not change project {}", revision, app.svn_ctx.get_current_project_name().yellow().bold())))
+}
+
+/// ws pick 的提交信息：第一行记录来源，之后是每个版本原来提交信息的第一行
+pub fn pick_message(source: &str, picked: &[PickedRevision]) -> String {
+    let revisions: Vec<String> = picked.iter().map(|p| format!("r{}", p.revision)).collect();
+    let mut message = format!("[WS-PICK] {} from {}\n", revisions.join(","), source);
+    for p in picked {
+        message.push_str(&format!("\nr{} {}", p.revision, p.message.lines().next().unwrap_or("")));
+    }
+    message
+}
+
+/// 从 [WS-PICK] 提交信息中读取来源分支和版本，见 pick_message
+pub fn parse_pick_message(message: &str) -> Option<(String, Vec<u64>)> {
+    let first_line = message.lines().next()?.strip_prefix("[WS-PICK] ")?;
+    let (revisions, source) = first_line.trim().split_once(" from ")?;
+    let revisions = revisions.split(',')
+        .map(|r| r.trim().trim_start_matches('r').parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((source.trim().to_string(), revisions))
+}
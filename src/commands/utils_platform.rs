@@ -0,0 +1,10 @@
+//! 平台相关的工具函数
+//!
+//! .ws_store 的位置、软链接切换、锁文件等在 Windows 和 Unix 上实现不同，
+//! 调用方统一使用这里导出的函数
+
+#[cfg(windows)]
+pub use super::utils_windows::*;
+
+#[cfg(unix)]
+pub use super::utils_unix::*;
@@ -0,0 +1,192 @@
+//! 提交时的改名识别
+//!
+//! - IDE 中的改名在 svn 看来是一个缺失的文件加一个未受控的文件
+//! - 按内容把它们配对，记录为 svn move，保留历史和 blame
+//! - 缺失目录中的文件都移到同一个新目录时，记录为目录的移动
+
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use crate::{
+    core::{app::App, error::AppResult, svn::{svn_add, svn_cat, svn_info, svn_move, svn_revert}},
+    ui::models::{DetectedRename, StatusEntry, StatusKind},
+};
+
+/// 按内容识别缺失文件和新文件之间的改名，threshold 为相似度的百分比，0 时不识别
+pub fn detect_renames(app: &App, entries: &[StatusEntry], threshold: u8) -> AppResult<Vec<DetectedRename>> {
+    let added: Vec<&StatusEntry> = entries.iter().filter(|e| e.auto && e.kind == StatusKind::Added && Path::new(&e.path).is_file()).collect();
+    let deleted: Vec<&StatusEntry> = entries.iter().filter(|e| e.auto && e.kind == StatusKind::Deleted).collect();
+    if threshold == 0 || added.is_empty() || deleted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // 缺失的目录展开为其中的文件，内容取自 BASE
+    let mut missing_dirs = Vec::new();
+    let mut missing = Vec::new();
+    for entry in deleted {
+        let path = entry.path.replace('\\', "/");
+        let (is_dir, files) = list_versioned_files(app, &path)?;
+        if is_dir {
+            missing_dirs.push(path);
+        }
+        for file in files {
+            if !missing.iter().any(|(p, _): &(String, Vec<u8>)| *p == file) {
+                let content = svn_cat(app.svn(), &["-r", "BASE", &file])?;
+                missing.push((file, content));
+            }
+        }
+    }
+
+    let mut created = Vec::new();
+    for entry in added {
+        created.push((entry.path.replace('\\', "/"), fs::read(&entry.path)?));
+    }
+
+    let renames = pair_renames(&missing, &created, threshold);
+    let is_new_dir = |dir: &str| Path::new(dir).is_dir() && svn_info(app.svn(), &["--show-item", "kind", dir]).is_err();
+    Ok(group_directory_moves(renames, &missing_dirs, is_new_dir))
+}
+
+/// 读取一个受控路径下的所有文件，返回 (是否为目录, 文件列表)
+fn list_versioned_files(app: &App, path: &str) -> AppResult<(bool, Vec<String>)> {
+    let xml_str = svn_info(app.svn(), &["-R", "--xml", path])?;
+    let doc = roxmltree::Document::parse(&xml_str)?;
+    let entries: Vec<(&str, &str)> = doc.descendants()
+        .filter(|n| n.has_tag_name("entry"))
+        .map(|n| (n.attribute("path").unwrap_or(""), n.attribute("kind").unwrap_or("")))
+        .collect();
+
+    let is_dir = entries.first().is_some_and(|(_, kind)| *kind == "dir");
+    let files = entries.into_iter()
+        .filter(|(_, kind)| *kind == "file")
+        .map(|(p, _)| p.replace('\\', "/"))
+        .collect();
+    Ok((is_dir, files))
+}
+
+/// 内容相同的行所占的百分比：2 * 相同的行数 / 总行数，忽略行尾的 \r
+pub fn similarity(a: &[u8], b: &[u8]) -> u8 {
+    if a == b {
+        return 100;
+    }
+    let lines = |content: &[u8]| -> Vec<Vec<u8>> {
+        let content = content.strip_suffix(b"\n").unwrap_or(content);
+        if content.is_empty() {
+            return Vec::new();
+        }
+        content.split(|c| *c == b'\n').map(|line| line.strip_suffix(b"\r").unwrap_or(line).to_vec()).collect()
+    };
+    let (a, b) = (lines(a), lines(b));
+    if a.is_empty() && b.is_empty() {
+        return 99;
+    }
+
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+    for line in &a {
+        *counts.entry(line).or_default() += 1;
+    }
+    let mut common = 0;
+    for line in &b {
+        if let Some(count) = counts.get_mut(line.as_slice()).filter(|c| **c > 0) {
+            *count -= 1;
+            common += 1;
+        }
+    }
+    // 内容不完全相同时不会是 100%
+    ((common * 200 / (a.len() + b.len())) as u8).min(99)
+}
+
+/// 配对缺失的文件和新文件，相似度高的优先，相似度相同时文件名相同的优先
+pub fn pair_renames(missing: &[(String, Vec<u8>)], created: &[(String, Vec<u8>)], threshold: u8) -> Vec<DetectedRename> {
+    let file_name = |path: &str| Path::new(path).file_name().map(|n| n.to_os_string());
+
+    let mut candidates = Vec::new();
+    for (from, old) in missing {
+        for (to, new) in created {
+            let score = similarity(old, new);
+            if score >= threshold {
+                candidates.push((score, file_name(from) == file_name(to), from, to));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)).then(a.3.cmp(b.3)));
+
+    let mut renames: Vec<DetectedRename> = Vec::new();
+    for (score, _, from, to) in candidates {
+        if renames.iter().any(|r| r.from == *from || r.to == *to) {
+            continue;
+        }
+        renames.push(DetectedRename { from: from.clone(), to: to.clone(), similarity: score, is_dir: false });
+    }
+    renames.sort_by(|a, b| a.from.cmp(&b.from));
+    renames
+}
+
+/// 缺失目录中配对的文件都移到同一个新目录的相同位置时，合并为目录的移动
+/// - is_new_dir 检查目标目录是否存在且未受控
+/// - 目录中没有配对的文件在移动后仍会被删除，新目录中多出的文件仍会被添加
+pub fn group_directory_moves(renames: Vec<DetectedRename>, missing_dirs: &[String], is_new_dir: impl Fn(&str) -> bool) -> Vec<DetectedRename> {
+    let mut dirs: Vec<&String> = missing_dirs.iter().collect();
+    dirs.sort_by_key(|dir| dir.len());
+
+    let mut renames = renames;
+    let mut dir_moves: Vec<DetectedRename> = Vec::new();
+    for dir in dirs {
+        let prefix = format!("{}/", dir);
+        if dir_moves.iter().any(|m| dir.starts_with(&format!("{}/", m.from))) {
+            continue;
+        }
+
+        let inside: Vec<&DetectedRename> = renames.iter().filter(|r| r.from.starts_with(&prefix)).collect();
+        let targets: Vec<Option<&str>> = inside.iter().map(|r| {
+            let rel = &r.from[prefix.len()..];
+            r.to.strip_suffix(rel).and_then(|t| t.strip_suffix('/'))
+        }).collect();
+
+        let Some(Some(target)) = targets.first().copied() else { continue };
+        if targets.iter().any(|t| *t != Some(target)) || target == dir || target.starts_with(&prefix) || !is_new_dir(target) {
+            continue;
+        }
+
+        let similarity = (inside.iter().map(|r| r.similarity as usize).sum::<usize>() / inside.len()) as u8;
+        dir_moves.push(DetectedRename { from: dir.clone(), to: target.to_string(), similarity, is_dir: true });
+        renames.retain(|r| !r.from.starts_with(&prefix));
+    }
+
+    dir_moves.extend(renames);
+    dir_moves
+}
+
+/// 把改名记录为 svn move，新路径的内容保持不变
+/// - 先把新路径移开，从 BASE 恢复旧路径后 svn move，再把新路径的内容放回去
+pub fn record_renames(app: &App, renames: &[DetectedRename]) -> AppResult<()> {
+    for rename in renames {
+        let temp = aside_path(Path::new(&rename.to));
+        fs::rename(&rename.to, &temp)?;
+
+        // 新路径所在的目录可能还未受控，先添加目录本身
+        let parent = Path::new(&rename.to).parent().map(|p| p.to_string_lossy().to_string()).filter(|p| !p.is_empty());
+        let result = parent.map_or(Ok(()), |parent| svn_add(app.svn(), &[&parent]))
+            .and_then(|_| svn_revert(app.svn(), &["--depth", "infinity", &rename.from]))
+            .and_then(|_| svn_move(app.svn(), &[&rename.from, &rename.to]));
+
+        // svn move 得到的是旧内容，换回新路径原来的内容
+        let target = Path::new(&rename.to);
+        if target.is_dir() {
+            fs::remove_dir_all(target)?;
+        } else if target.exists() {
+            fs::remove_file(target)?;
+        }
+        fs::rename(&temp, &rename.to)?;
+        result?;
+    }
+    Ok(())
+}
+
+/// 同一目录中不存在的临时路径：.{name}.ws-move、.{name}.ws-move1 ...
+fn aside_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    (0..).map(|i| {
+        let suffix = if i == 0 { String::new() } else { i.to_string() };
+        path.with_file_name(format!(".{}.ws-move{}", name, suffix))
+    }).find(|p| !p.exists()).unwrap()
+}
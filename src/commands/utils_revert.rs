@@ -0,0 +1,178 @@
+//! ### ws revert 相关的工具函数
+//!
+//! - 回滚整个项目时提交信息为 [WS-ROLLBACK] tags/rollback-*，见 handle_revert
+//! - 只回滚部分路径时提交信息为 [WS-ROLLBACK] 2 path(s) from r42，路径每行一个写在正文中
+//! - 撤销单个提交时反向合并这些版本，提交信息为 [WS-UNDO] r40,r42
+
+use std::{fs, path::{Path, PathBuf}};
+
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{models::SVNLogType, utils::callback_for_log_xml, utils_log::{is_internal_commit, normalize_log_paths}, utils_revision::resolve_revision},
+    core::{app::App, error::{AppError, AppResult, SvnErrorKind}, svn::{svn_copy, svn_info, svn_merge}, utils::{RevisionExpr, parse_revision_range}},
+};
+
+/// 整理 ws revert 的路径，路径相对于项目根目录，不能超出项目
+/// - 包括项目根目录时返回空列表，即回滚整个项目
+pub fn normalize_revert_paths(paths: &[String]) -> AppResult<Vec<String>> {
+    let normalized = normalize_log_paths(paths);
+    if let Some(path) = normalized.iter().find(|p| p.starts_with('/') || p.contains(':') || p.split('/').any(|part| part == "..")) {
+        return Err(AppError::Validation(format!("Path {} is outside the project", path.clone().yellow().bold())));
+    }
+    if normalized.len() < paths.len() {
+        return Ok(Vec::new());
+    }
+
+    let mut result: Vec<String> = Vec::new();
+    for path in normalized {
+        if !result.contains(&path) {
+            result.push(path);
+        }
+    }
+    Ok(result)
+}
+
+/// 把 paths 恢复为 branch_url 在 revision 时的内容
+/// - 工作副本中已经删除的路径从 revision 复制回来，保留历史
+/// - 其余路径反向合并 HEAD:revision，之后新增的文件会被删除
+/// - 失败时删除已经复制回来的路径，调用方 svn revert 后工作区恢复原样
+pub fn restore_paths(app: &App, branch_url: &str, paths: &[String], revision: u64) -> AppResult<()> {
+    // 先检查所有路径，避免只恢复了一部分
+    for path in paths {
+        match svn_info(app.svn(), &["--show-item", "kind", &format!("{}/{}@{}", branch_url, path, revision)]) {
+            Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => {
+                return Err(AppError::Validation(format!("{} does not exist in r{}", path.clone().yellow().bold(), revision)));
+            }
+            result => { result?; }
+        }
+    }
+
+    let range = format!("HEAD:{}", revision);
+    let mut copied: Vec<PathBuf> = Vec::new();
+    let result = paths.iter().try_for_each(|path| {
+        match svn_info(app.svn(), &["--show-item", "kind", path]) {
+            Ok(_) => svn_merge(app.svn(), &["--accept", "postpone", "-r", &range, &format!("{}/{}", branch_url, path), path]),
+            Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => {
+                // --parents 创建的上级目录也要记录
+                if let Some(top) = Path::new(path).ancestors().take_while(|p| !p.as_os_str().is_empty() && !p.exists()).last() {
+                    copied.push(top.to_path_buf());
+                }
+                svn_copy(app.svn(), &[&format!("{}/{}@{}", branch_url, path, revision), path, "--parents"])
+            }
+            Err(e) => Err(e),
+        }
+    });
+
+    // svn revert 之后复制回来的文件会作为未受控文件留下，失败时先删除
+    if result.is_err() {
+        for path in &copied {
+            let _ = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+        }
+    }
+    result
+}
+
+/// 只回滚部分路径时的提交信息
+/// - 路径中可以有逗号和空格，所以每行写一个路径，svn 不允许路径中有换行
+pub fn rollback_paths_message(paths: &[String], revision: u64) -> String {
+    let mut message = format!("[WS-ROLLBACK] {} path(s) from r{}\n", paths.len(), revision);
+    for path in paths {
+        message.push_str(&format!("\n{}", path));
+    }
+    message
+}
+
+/// 从只回滚部分路径的提交信息中读取路径和来源版本，见 rollback_paths_message
+/// - 回滚整个项目的提交信息返回 None
+pub fn parse_rollback_paths_message(message: &str) -> Option<(Vec<String>, u64)> {
+    let mut lines = message.lines();
+    let first_line = lines.next()?.strip_prefix("[WS-ROLLBACK] ")?;
+    let (count, revision) = first_line.trim().rsplit_once(" path(s) from r")?;
+    let count: usize = count.parse().ok()?;
+    let revision = revision.parse().ok()?;
+    let paths: Vec<String> = lines.filter(|line| !line.is_empty()).map(str::to_string).collect();
+    (!paths.is_empty() && paths.len() == count).then_some((paths, revision))
+}
+
+/// 解析 ws revert --commit 的版本列表，返回要撤销的 (版本, 提交信息)，从新到旧
+/// - 每一项是当前分支上的版本表达式或 A:B 范围，范围包括两端
+/// - 范围中不会带来内容的内部提交会被跳过
+pub fn resolve_undo_revisions(app: &App, revisions: &str) -> AppResult<Vec<(u64, String)>> {
+    let branch_url = app.svn_ctx.get_current_work_copy_root()?;
+    let mut undone: Vec<(u64, String)> = Vec::new();
+
+    for item in revisions.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (from, to) = parse_revision_range(item)?;
+        let resolve = |expr: &RevisionExpr| -> AppResult<u64> {
+            let resolved = resolve_revision(app, expr)?;
+            if !resolved.is_current_branch {
+                return Err(AppError::Validation(format!("Cannot undo commits of branch {}, switch to it first", resolved.branch.yellow().bold())));
+            }
+            Ok(resolved.revision)
+        };
+        let start = resolve(&from)?;
+        let end = match &to {
+            Some(to) => resolve(to)?,
+            None => start,
+        };
+
+        let callback = |doc: &roxmltree::Document| -> AppResult<Vec<(u64, String)>> {
+            Ok(doc.descendants()
+                .filter(|n| n.has_tag_name("logentry"))
+                .filter_map(|n| {
+                    let revision = n.attribute("revision")?.parse().ok()?;
+                    let message = n.children().find(|c| c.has_tag_name("msg")).and_then(|c| c.text()).unwrap_or("");
+                    Some((revision, message.to_string()))
+                })
+                .collect())
+        };
+        let commits = callback_for_log_xml(app.svn(), &branch_url, SVNLogType::Range { start: start.min(end), end: start.max(end) }, callback)?;
+
+        if to.is_none() {
+            match commits.iter().find(|(revision, _)| *revision == start) {
+                Some((_, message)) if is_internal_commit(message) => {
+                    return Err(AppError::Validation(format!("r{} does not change any files and cannot be undone", start)));
+                }
+                Some(_) => {}
+                None => return Err(AppError::Validation(format!("r{} is not a commit of the current branch", start))),
+            }
+        }
+
+        for (revision, message) in commits {
+            if !is_internal_commit(&message) && !undone.iter().any(|(r, _)| *r == revision) {
+                undone.push((revision, message));
+            }
+        }
+    }
+
+    if undone.is_empty() {
+        return Err(AppError::Validation("No commits to undo".to_string()));
+    }
+    undone.sort_by_key(|(revision, _)| std::cmp::Reverse(*revision));
+    Ok(undone)
+}
+
+/// 反向合并 revisions，从新到旧依次撤销
+pub fn undo_revisions(app: &App, branch_url: &str, revisions: &[u64]) -> AppResult<()> {
+    let changes: Vec<String> = revisions.iter().map(|r| format!("-{}", r)).collect();
+    svn_merge(app.svn(), &["--accept", "postpone", "-c", &changes.join(","), branch_url, "."])
+}
+
+/// 撤销提交的提交信息：第一行记录撤销的版本，之后是每个版本原来提交信息的第一行
+pub fn undo_message(undone: &[(u64, String)]) -> String {
+    let revisions: Vec<String> = undone.iter().map(|(revision, _)| format!("r{}", revision)).collect();
+    let mut message = format!("[WS-UNDO] {}\n", revisions.join(","));
+    for (revision, original) in undone {
+        message.push_str(&format!("\nr{} {}", revision, original.lines().next().unwrap_or("")));
+    }
+    message
+}
+
+/// 从 [WS-UNDO] 提交信息中读取撤销的版本，见 undo_message
+pub fn parse_undo_message(message: &str) -> Option<Vec<u64>> {
+    let first_line = message.lines().next()?.strip_prefix("[WS-UNDO] ")?;
+    first_line.trim().split(',')
+        .map(|r| r.trim().trim_start_matches('r').parse().ok())
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! 解析版本表达式
+//!
+//! HEAD~N、PREV 和日期沿分支自己的提交历史（svn log）解析，
+//! 同一仓库中其他项目的提交不计入
+
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{models::SVNLogType, utils::{callback_for_log_xml, check_url_exists, validate_folder_name}},
+    core::{app::App, error::{AppError, AppResult, SvnErrorKind}, utils::{Revision, RevisionBase, RevisionExpr}},
+};
+
+/// 解析后的版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRevision {
+    pub branch: String,
+    /// 分支的 URL，当前分支为工作副本的 URL
+    pub url: String,
+    pub revision: u64,
+    pub is_current_branch: bool,
+}
+
+impl ResolvedRevision {
+    /// {url}@{revision}
+    pub fn url_with_rev(&self) -> String {
+        format!("{}@{}", self.url, self.revision)
+    }
+}
+
+/// 把版本表达式解析为具体的分支和版本号
+pub fn resolve_revision(app: &App, expr: &RevisionExpr) -> AppResult<ResolvedRevision> {
+    let current_branch = app.svn_ctx.get_current_branch_name()?;
+    let branch = expr.branch.clone().unwrap_or_else(|| current_branch.clone());
+    let is_current_branch = branch == current_branch;
+
+    let url = if is_current_branch {
+        app.svn_ctx.get_current_work_copy_root()?
+    } else {
+        validate_folder_name(&branch, true)?;
+        let url = if branch == "trunk" { app.svn_ctx.get_current_trunk_url() } else { app.svn_ctx.get_branch_url(&branch) };
+        if !check_url_exists(app.svn(), &url)? {
+            return Err(AppError::Validation(format!("Branch {} does not exist", branch.as_str().yellow().bold())));
+        }
+        url
+    };
+
+    // 从 start 开始的第 skip 个提交（start 本身或之前最近的提交为第 0 个）
+    let (start, skip) = match &expr.base {
+        RevisionBase::Number(n) if expr.offset == 0 => {
+            return Ok(ResolvedRevision { branch, url, revision: *n, is_current_branch });
+        }
+        RevisionBase::Number(n) => (n.to_string(), expr.offset),
+        RevisionBase::Head => ("HEAD".to_string(), expr.offset),
+        RevisionBase::Date(date) => (format!("{{{}}}", date), expr.offset),
+        RevisionBase::Prev => {
+            if !is_current_branch {
+                return Err(AppError::RevisionParse(format!("{} (PREV can only be used on the current branch)", expr)));
+            }
+            let current = match app.svn_ctx.get_current_revision() {
+                Revision::Number(n) => n.to_string(),
+                Revision::Head => "HEAD".to_string(),
+            };
+            (current, expr.offset + 1)
+        }
+    };
+
+    let callback = |doc: &roxmltree::Document| -> AppResult<Vec<u64>> {
+        Ok(doc.descendants()
+            .filter(|n| n.has_tag_name("logentry"))
+            .filter_map(|n| n.attribute("revision")?.parse::<u64>().ok())
+            .collect())
+    };
+    let history = match callback_for_log_xml(app.svn(), &url, SVNLogType::History { start, limit: skip + 1 }, callback) {
+        // 分支在该版本还不存在
+        Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => Vec::new(),
+        result => result?,
+    };
+
+    match history.get(skip as usize) {
+        Some(revision) => Ok(ResolvedRevision { branch, url, revision: *revision, is_current_branch }),
+        None => Err(AppError::RevisionParse(format!("{} is beyond the history of branch {}", expr, branch))),
+    }
+}
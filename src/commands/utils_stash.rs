@@ -0,0 +1,367 @@
+//! ### ws stash 相关的工具函数
+//!
+//! - 每个项目的 stash 保存在项目所在的 .ws_store/{repo_name}/.stash/{project_name} 中
+//! - 每个 stash 是一个目录：stash.json、files/ 中保存更改后的文件，base/ 中保存修改和删除的文件、删除的目录中的文件在 BASE 的内容
+//! - 未受控且没有被 .gitignore 忽略的文件也会保存，与 ws status 显示的一致
+//! - 属性的修改和新增的目录记录在 stash.json 中，svn revert 会还原它们
+
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+
+use chrono::Local;
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{models::{StashInfo, StashedProperties}, utils::is_workspace_dirty, utils_status::{collect_status_entries, current_ignore_matcher}},
+    core::{app::App, error::{AppError, AppResult, SvnErrorKind}, svn::{StatusType, svn_add, svn_cat, svn_info, svn_list, svn_propdel, svn_proplist, svn_propset, svn_revert, svn_status}, utils::Revision},
+    ui::models::{StatusEntry, StatusKind},
+};
+
+const STASH_FILE: &str = "stash.json";
+
+/// 项目的 stash 目录
+fn stash_root(app: &App, project_name: &str) -> AppResult<PathBuf> {
+    let project_path = app.svn_ctx.find_project_in_ws_store(project_name)?
+        .ok_or_else(|| AppError::Validation(format!("Project {} is not checked out in any workspace", project_name.yellow().bold())))?;
+    let store = project_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?;
+    Ok(store.join(".stash").join(project_name))
+}
+
+/// 读取项目的 stash，从新到旧，返回 (目录, 信息)
+pub fn list_stashes(app: &App, project_name: &str) -> AppResult<Vec<(PathBuf, StashInfo)>> {
+    let root = stash_root(app, project_name)?;
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut stashes = Vec::new();
+    for entry in fs::read_dir(&root)?.flatten() {
+        let Some(id) = entry.file_name().to_str().and_then(|name| name.parse::<u64>().ok()) else { continue };
+        let Ok(content) = fs::read_to_string(entry.path().join(STASH_FILE)) else { continue };
+        let info: StashInfo = serde_json::from_str(&content)
+            .map_err(|e| AppError::Validation(format!("Stash {} is damaged: {}", entry.path().display(), e)))?;
+        stashes.push((id, entry.path(), info));
+    }
+
+    stashes.sort_by_key(|(id, _, _)| std::cmp::Reverse(*id));
+    Ok(stashes.into_iter().enumerate().map(|(index, (_, dir, mut info))| {
+        info.index = index;
+        (dir, info)
+    }).collect())
+}
+
+/// 按位置读取一个 stash，0 为最新
+pub fn get_stash(app: &App, project_name: &str, index: usize) -> AppResult<(PathBuf, StashInfo)> {
+    list_stashes(app, project_name)?.into_iter().nth(index)
+        .ok_or_else(|| AppError::Validation(format!("Stash {} does not exist, see ws stash list", index.to_string().yellow().bold())))
+}
+
+/// 保存当前工作区的更改并还原工作区，没有更改时返回 None
+/// - auto_pop 为 true 时，ws switch 回到同一个项目和分支会自动恢复
+pub fn push_stash(app: &App, message: Option<&str>, auto_pop: bool) -> AppResult<Option<StashInfo>> {
+    let entries = collect_status_entries(app.svn(), &current_ignore_matcher()?)?;
+    if entries.iter().any(|e| e.kind == StatusKind::Conflicted) {
+        return Err(AppError::Validation("Resolve the conflicts before stashing".to_string()));
+    }
+    // 目录本身没有内容，目录中的文件单独列出
+    let entries: Vec<StatusEntry> = entries.into_iter()
+        .filter(|e| matches!(e.kind, StatusKind::Added | StatusKind::Modified | StatusKind::Deleted))
+        .filter(|e| e.kind == StatusKind::Deleted || !Path::new(&e.path).is_dir())
+        .map(|e| StatusEntry { path: e.path.replace('\\', "/"), ..e })
+        .collect();
+    let TreeChanges { properties, directories } = collect_tree_changes(app)?;
+    if entries.is_empty() && properties.is_empty() && directories.is_empty() {
+        return Ok(None);
+    }
+    let properties = properties.into_iter().map(|(path, is_added)| {
+        let base = if is_added { BTreeMap::new() } else { read_properties(app, &path, true)? };
+        let working = read_properties(app, &path, false)?;
+        Ok(StashedProperties { path, base, working })
+    }).collect::<AppResult<Vec<_>>>()?;
+
+    let project_name = app.svn_ctx.get_current_project_name();
+    let root = stash_root(app, project_name)?;
+    let next_id = fs::read_dir(&root).map(|dir| {
+        dir.flatten().filter_map(|e| e.file_name().to_str()?.parse::<u64>().ok()).max().map_or(0, |id| id + 1)
+    }).unwrap_or(0);
+    let dir = root.join(next_id.to_string());
+
+    let result = save_stash_files(app, &dir, &entries);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    result?;
+
+    let branch = app.svn_ctx.get_current_branch_name()?;
+    let revision = match app.svn_ctx.get_current_revision() {
+        Revision::Number(n) => *n,
+        Revision::Head => 0,
+    };
+    let info = StashInfo {
+        index: 0,
+        message: message.map(str::to_string).unwrap_or_else(|| format!("WIP on {}@r{}", branch, revision)),
+        branch,
+        revision,
+        date: Local::now().to_rfc3339(),
+        auto_pop,
+        entries,
+        properties,
+        directories,
+    };
+    let json = serde_json::to_string_pretty(&info).map_err(|e| AppError::Validation(e.to_string()))?;
+    fs::write(dir.join(STASH_FILE), json)?;
+
+    // 还原工作区：恢复修改和删除的文件，删除新文件
+    svn_revert(app.svn(), &["-R", "."])?;
+    for entry in info.entries.iter().filter(|e| e.kind == StatusKind::Added) {
+        let path = Path::new(&entry.path);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+        remove_empty_parents(app, path);
+    }
+    // 子目录在父目录之后，倒序删除
+    for dir in info.directories.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+    Ok(Some(info))
+}
+
+/// svn revert 会还原、但 ws status 没有单独列出的更改，路径使用 / 分隔
+struct TreeChanges {
+    /// 属性有修改的路径，以及是否为新增的路径
+    properties: Vec<(String, bool)>,
+    /// 新增的目录
+    directories: Vec<String>,
+}
+
+/// 从 svn status --xml 中读取属性的修改和新增的目录
+fn collect_tree_changes(app: &App) -> AppResult<TreeChanges> {
+    let xml = svn_status(app.svn(), StatusType::Commit)?;
+    let doc = roxmltree::Document::parse(&xml)?;
+
+    let mut properties = Vec::new();
+    let mut directories = Vec::new();
+    for entry in doc.descendants().filter(|n| n.has_tag_name("entry")) {
+        let path = entry.attribute("path").unwrap_or("").replace('\\', "/");
+        let Some(wc_status) = entry.children().find(|n| n.has_tag_name("wc-status")) else { continue };
+        let is_added = wc_status.attribute("item") == Some("added");
+        if wc_status.attribute("props") == Some("modified") {
+            properties.push((path.clone(), is_added));
+        }
+        if is_added && Path::new(&path).is_dir() {
+            directories.push(path);
+        }
+    }
+    Ok(TreeChanges { properties, directories })
+}
+
+/// 读取一个路径的全部属性，base 为 true 时读取 BASE 中的属性
+fn read_properties(app: &App, path: &str, base: bool) -> AppResult<BTreeMap<String, String>> {
+    let revision: &[&str] = if base { &["-r", "BASE"] } else { &[] };
+    let xml = svn_proplist(app.svn(), &[&["-v", "--xml"], revision, &[path]].concat())?;
+    parse_proplist(&xml, path)
+}
+
+/// 读取 svn proplist -v --xml 的输出
+/// - 不能作为文本保存的属性（encoding="base64"）返回错误
+pub fn parse_proplist(xml: &str, path: &str) -> AppResult<BTreeMap<String, String>> {
+    let doc = roxmltree::Document::parse(xml)?;
+    doc.descendants().filter(|n| n.has_tag_name("property")).map(|property| {
+        let name = property.attribute("name").unwrap_or("").to_string();
+        if property.attribute("encoding").is_some() {
+            return Err(AppError::Validation(format!("Property {} of {} is binary and cannot be stashed", name.yellow().bold(), path)));
+        }
+        Ok((name, property.text().unwrap_or("").to_string()))
+    }).collect()
+}
+
+/// 保存更改后的文件和 BASE 中的文件
+fn save_stash_files(app: &App, dir: &Path, entries: &[StatusEntry]) -> AppResult<()> {
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        if entry.kind != StatusKind::Deleted {
+            write_file(&dir.join("files").join(path), &fs::read(path)?)?;
+        }
+        if entry.kind == StatusKind::Added {
+            continue;
+        }
+        match svn_info(app.svn(), &["--show-item", "kind", &entry.path]).map(|kind| kind.trim().to_string()).as_deref() {
+            Ok("file") => write_file(&dir.join("base").join(path), &svn_cat(app.svn(), &["-r", "BASE", &entry.path])?)?,
+            // 删除的目录保存其中所有文件在 BASE 的内容，恢复时用来比较
+            Ok("dir") if entry.kind == StatusKind::Deleted => {
+                let listing = svn_list(app.svn(), &["-R", "-r", "BASE", &entry.path])?;
+                for file in listing.lines().map(str::trim).filter(|l| !l.is_empty() && !l.ends_with('/')) {
+                    let file = format!("{}/{}", entry.path, file);
+                    write_file(&dir.join("base").join(&file), &svn_cat(app.svn(), &["-r", "BASE", &file])?)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, content: &[u8]) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// 删除新文件之后，删除因此变空的未受控目录
+fn remove_empty_parents(app: &App, path: &Path) {
+    for dir in path.ancestors().skip(1) {
+        if dir.as_os_str().is_empty() {
+            break;
+        }
+        let is_empty = fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none());
+        if !is_empty || svn_info(app.svn(), &["--show-item", "kind", &dir.to_string_lossy()]).is_ok() {
+            break;
+        }
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// 把 stash 中的更改恢复到工作区，返回没能恢复的路径
+/// - 文件在保存之后被其他提交修改时不覆盖，保存的版本写到 {path}.stash
+/// - 工作区必须是干净的，删除的目录中有保存之后才加入或修改的文件时保留目录
+pub fn apply_stash(app: &App, project_name: &str, dir: &Path, info: &StashInfo) -> AppResult<Vec<String>> {
+    if is_workspace_dirty(app.svn(), project_name)? {
+        return Err(AppError::Validation("Workspace contains uncommitted changes, commit or stash them first".to_string()));
+    }
+
+    let mut conflicts = Vec::new();
+    for entry in &info.entries {
+        let path = Path::new(&entry.path);
+        let base = fs::read(dir.join("base").join(path)).ok();
+
+        if entry.kind == StatusKind::Deleted {
+            // 工作区是干净的，目录中只有 BASE 的内容，与保存时的 BASE 比较即可
+            if path.is_dir() {
+                if directory_matches_base(path, &dir.join("base").join(path))? {
+                    fs::remove_dir_all(path)?;
+                } else {
+                    conflicts.push(entry.path.clone());
+                }
+            } else if path.is_file() {
+                if Some(fs::read(path)?) == base {
+                    fs::remove_file(path)?;
+                } else {
+                    conflicts.push(entry.path.clone());
+                }
+            }
+            continue;
+        }
+
+        let stashed = fs::read(dir.join("files").join(path))?;
+        let current = if path.is_file() { Some(fs::read(path)?) } else { None };
+        if current.as_ref() == Some(&stashed) {
+            continue;
+        }
+        // 新文件不能覆盖已经存在的文件，修改的文件要求内容仍是保存时的 BASE
+        let can_write = match entry.kind {
+            StatusKind::Added => !path.exists(),
+            _ => current.is_some() && current == base,
+        };
+        if can_write {
+            write_file(path, &stashed)?;
+        } else {
+            write_file(&PathBuf::from(format!("{}.stash", entry.path)), &stashed)?;
+            conflicts.push(entry.path.clone());
+        }
+    }
+
+    // 新增的目录重新加入版本控制，其中的新文件与其他新文件一样在提交时添加
+    for directory in &info.directories {
+        fs::create_dir_all(directory)?;
+        svn_add(app.svn(), &[directory])?;
+    }
+    for properties in &info.properties {
+        if !apply_properties(app, dir, properties)? {
+            app.ui.warn(&format!("Properties of {} changed since they were stashed, they are not restored", properties.path));
+            conflicts.push(properties.path.clone());
+        }
+    }
+    Ok(conflicts)
+}
+
+/// 目录中的每个文件在 base 中都有内容相同的副本
+/// - base 中多出的文件在保存之后被其他提交删除，不影响删除目录
+pub fn directory_matches_base(path: &Path, base: &Path) -> AppResult<bool> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let base_path = base.join(entry.file_name());
+        let matches = if entry.file_type()?.is_dir() {
+            directory_matches_base(&entry.path(), &base_path)?
+        } else {
+            fs::read(&base_path).is_ok_and(|content| fs::read(entry.path()).is_ok_and(|current| current == content))
+        };
+        if !matches {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// 恢复一个路径上属性的修改，属性在保存之后被其他提交修改时不恢复，返回 false
+/// - 属性值写入文件再用 svn propset -F 设置，值可以以 - 开头，如 svn:externals 中的 -r
+fn apply_properties(app: &App, dir: &Path, properties: &StashedProperties) -> AppResult<bool> {
+    let path = properties.path.as_str();
+    if !Path::new(path).exists() {
+        return Ok(false);
+    }
+    // 新增的文件恢复后还未受控，先加入版本控制
+    if let Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) = svn_info(app.svn(), &["--show-item", "kind", path]) {
+        svn_add(app.svn(), &[path])?;
+    }
+    let current = read_properties(app, path, false)?;
+    if current == properties.working {
+        return Ok(true);
+    }
+    if current != properties.base {
+        return Ok(false);
+    }
+
+    let value_file = dir.join("property-value");
+    for (name, value) in properties.working.iter().filter(|(name, value)| properties.base.get(*name) != Some(*value)) {
+        fs::write(&value_file, value)?;
+        svn_propset(app.svn(), &[name, "-F", &value_file.to_string_lossy(), path])?;
+    }
+    let _ = fs::remove_file(&value_file);
+    for name in properties.base.keys().filter(|name| !properties.working.contains_key(*name)) {
+        svn_propdel(app.svn(), &[name, path])?;
+    }
+    Ok(true)
+}
+
+/// 删除一个 stash
+pub fn drop_stash(dir: &Path) -> AppResult<()> {
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+/// 回到 project_name 的 branch 时，自动恢复 ensure_clean_workspace 保存的最新的 stash
+/// - 返回完整恢复的 stash；有冲突时保留 stash 并返回 None，冲突的文件由 pop_stash 提示
+pub fn auto_pop_stash(app: &App, project_name: &str, branch: &str) -> AppResult<Option<StashInfo>> {
+    let Some((dir, info)) = list_stashes(app, project_name)?.into_iter().find(|(_, info)| info.auto_pop && info.branch == branch) else {
+        return Ok(None);
+    };
+    let conflicts = pop_stash(app, project_name, &dir, &info)?;
+    Ok(conflicts.is_empty().then_some(info))
+}
+
+/// 恢复 stash，没有冲突时删除它
+pub fn pop_stash(app: &App, project_name: &str, dir: &Path, info: &StashInfo) -> AppResult<Vec<String>> {
+    let conflicts = apply_stash(app, project_name, dir, info)?;
+    if conflicts.is_empty() {
+        drop_stash(dir)?;
+    } else {
+        app.ui.warn(&format!("{} path(s) changed since they were stashed, the stashed versions of files are saved as <file>.stash:", conflicts.len()));
+        for path in &conflicts {
+            app.ui.warn(&format!("  {}", path));
+        }
+        app.ui.warn(&format!("Stash {} is kept, drop it with ws stash drop {} after merging", info.index, info.index));
+    }
+    Ok(conflicts)
+}
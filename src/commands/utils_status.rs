@@ -0,0 +1,98 @@
+//! 工作区状态
+//!
+//! 按提交时的规则整理 svn status：
+//! - 未受控的文件不被 .gitignore 忽略时，提交前会自动 svn add
+//! - 缺失的文件提交前会自动 svn delete
+//!
+//! ws status 和 svn_add_and_delete 共用这里的结果
+
+use std::path::PathBuf;
+
+use ignore::gitignore::Gitignore;
+
+use crate::{
+    commands::utils_ignore::{build_folder_walker, build_ignore_matcher},
+    core::{error::AppResult, svn::{StatusType, svn_status}, svn_executor::SvnExecutor},
+    ui::models::{StatusEntry, StatusKind},
+};
+
+/// 当前工作目录的 .gitignore 规则，没有 .gitignore 时不忽略任何文件
+/// - 只读取规则，不会同步 svn:ignore
+pub fn current_ignore_matcher() -> AppResult<Gitignore> {
+    let gitignore_root = PathBuf::from(".");
+    if gitignore_root.join(".gitignore").exists() {
+        build_ignore_matcher(&gitignore_root, &gitignore_root)
+    } else {
+        Ok(Gitignore::empty())
+    }
+}
+
+/// 路径是否在给出的某个路径之下，没有给出路径时为整个项目
+pub fn is_under_paths(path: &str, paths: &[String]) -> bool {
+    let path = path.replace('\\', "/");
+    paths.is_empty() || paths.iter().any(|p| {
+        let p = p.replace('\\', "/");
+        let p = p.trim_start_matches("./").trim_end_matches('/');
+        p.is_empty() || p == "." || path == p || path.starts_with(&format!("{}/", p))
+    })
+}
+
+/// 读取工作区状态，ignore_matcher 为 .gitignore 规则
+pub fn collect_status_entries(svn: &dyn SvnExecutor, ignore_matcher: &Gitignore) -> AppResult<Vec<StatusEntry>> {
+    let xml_str = svn_status(svn, StatusType::CheckIgnore)?;
+    let doc = roxmltree::Document::parse(&xml_str)?;
+
+    let mut entries = Vec::new();
+    for entry in doc.descendants().filter(|n| n.has_tag_name("entry")) {
+        let path = entry.attribute("path").unwrap_or("");
+        let Some(wc_status) = entry.children().find(|n| n.has_tag_name("wc-status")) else { continue };
+
+        let item = wc_status.attribute("item").unwrap_or("");
+        let props = wc_status.attribute("props").unwrap_or("");
+        let is_conflicted = wc_status.attribute("tree-conflicted") == Some("true")
+            || props == "conflicted"
+            || matches!(item, "conflicted" | "obstructed" | "incomplete");
+
+        if is_conflicted {
+            entries.push(StatusEntry::new(path, StatusKind::Conflicted, false));
+        } else {
+            match item {
+                // 这里也处理 ignored 项目，确保 .gitignore 规则生效
+                "unversioned" | "ignored" => collect_unversioned(path, ignore_matcher, &mut entries)?,
+                "missing" => entries.push(StatusEntry::new(path, StatusKind::Deleted, true)),
+                "added" => entries.push(StatusEntry::new(path, StatusKind::Added, false)),
+                "deleted" => entries.push(StatusEntry::new(path, StatusKind::Deleted, false)),
+                "modified" | "replaced" => entries.push(StatusEntry::new(path, StatusKind::Modified, false)),
+                _ if props == "modified" => entries.push(StatusEntry::new(path, StatusKind::Modified, false)),
+                _ => {},
+            }
+        }
+
+        if wc_status.attribute("switched") == Some("true") {
+            entries.push(StatusEntry::new(path, StatusKind::Switched, false));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// 未受控的文件：被 .gitignore 忽略，或者在提交时自动添加
+/// - 目录本身无需添加，只要里面的文件添加了，目录就会被 SVN 跟踪
+fn collect_unversioned(path: &str, ignore_matcher: &Gitignore, entries: &mut Vec<StatusEntry>) -> AppResult<()> {
+    let item = PathBuf::from(path);
+    let is_dir = item.is_dir();
+    if ignore_matcher.matched(&item, is_dir).is_ignore() {
+        entries.push(StatusEntry::new(path, StatusKind::Ignored, false));
+        return Ok(());
+    }
+
+    if is_dir {
+        // 如果是目录，直接看目录里面的内容是否需要添加
+        for entry in build_folder_walker(&item)?.flatten() {
+            entries.push(StatusEntry::new(&entry.path().to_string_lossy(), StatusKind::Added, true));
+        }
+    } else {
+        entries.push(StatusEntry::new(path, StatusKind::Added, true));
+    }
+    Ok(())
+}
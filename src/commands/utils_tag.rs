@@ -0,0 +1,179 @@
+//! ### 标签相关的工具函数
+//!
+//! - 标签保存在项目的 tags/ 下，是某个分支在某个版本的只读快照
+//! - ws revert 创建的 rollback-* 锚点也是标签，默认不显示
+
+use crossterm::style::Stylize;
+
+use crate::{
+    commands::{models::{SVNLogType, TagInfo}, utils::{callback_for_log_xml, check_url_exists, validate_folder_name}, utils_revision::ResolvedRevision},
+    core::{app::App, error::{AppError, AppResult, SvnErrorKind}, svn::{svn_copy, svn_info, svn_list}},
+    ui::models::LogEntry,
+};
+
+/// ws revert 创建的锚点标签的前缀
+pub const ROLLBACK_TAG_PREFIX: &str = "rollback-";
+
+/// 从版本 from 创建标签，from 为 None 时使用工作副本的版本
+/// - 标签指向来源在该版本最后一次更改的版本，ws log 才能在这条提交上显示标签
+/// - 返回标签指向的版本
+pub fn create_tag(app: &App, tag_name: &str, from: Option<&ResolvedRevision>) -> AppResult<u64> {
+    validate_folder_name(tag_name, false)?;
+    if tag_name.starts_with(ROLLBACK_TAG_PREFIX) {
+        return Err(AppError::Validation(format!("Tag names starting with {} are reserved for revert anchors", ROLLBACK_TAG_PREFIX.yellow().bold())));
+    }
+
+    let tag_url = app.svn_ctx.get_tag_url(tag_name);
+    if check_url_exists(app.svn(), &tag_url)? {
+        return Err(AppError::Validation(format!("Tag {} already exists", tag_name.yellow().bold())));
+    }
+
+    let (source_url, revision) = match from {
+        Some(from) => (from.url.clone(), from.revision.to_string()),
+        None => (app.svn_ctx.get_current_work_copy_root()?, app.svn_ctx.get_current_revision().to_string()),
+    };
+    let last_changed = svn_info(app.svn(), &["--show-item", "last-changed-revision", &format!("{}@{}", source_url, revision)])?;
+    let last_changed: u64 = last_changed.trim().parse()
+        .map_err(|_| AppError::RevisionParse(format!("Failed to read the last changed revision of {}", source_url.clone().yellow().bold())))?;
+
+    let source_url = format!("{}@{}", source_url, last_changed);
+    svn_copy(app.svn(), &[&source_url, &tag_url, "-m", &format!("[WS-TAG] Create {}", tag_name), "--parents"])?;
+    Ok(last_changed)
+}
+
+/// 获取项目的标签，按名称排序
+/// - 只读取 tags/ 的列表，revision 和 date 为标签最后一次更改，没有来源，需要时用 add_tag_sources 读取
+/// - include_rollback 为 false 时不包括 rollback-* 锚点
+pub fn get_project_tags(app: &App, project_name: &str, include_rollback: bool) -> AppResult<Vec<TagInfo>> {
+    let tags_url = app.svn_ctx.get_project_tags_url(project_name);
+    let xml = match svn_list(app.svn(), &["--xml", &tags_url]) {
+        Ok(xml) => xml,
+        Err(AppError::SvnCommandFailed { kind: SvnErrorKind::PathNotFound, .. }) => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut tags = parse_tag_list(&xml)?;
+    tags.retain(|tag| include_rollback || !tag.is_rollback);
+    if app.svn_ctx.get_current_project_name() == project_name
+        && let Some(current_tag) = app.svn_ctx.get_current_tag_name()? {
+            for tag in tags.iter_mut() {
+                tag.is_current = tag.name == current_tag;
+            }
+        }
+    Ok(tags)
+}
+
+/// 从 tags 目录的 svn list --xml 中读取标签，按名称排序
+pub fn parse_tag_list(xml: &str) -> AppResult<Vec<TagInfo>> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let mut tags: Vec<TagInfo> = doc.descendants()
+        .filter(|n| n.has_tag_name("entry") && n.attribute("kind") == Some("dir"))
+        .filter_map(|entry| {
+            let name = entry.children().find(|n| n.has_tag_name("name"))?.text()?;
+            let commit = entry.children().find(|n| n.has_tag_name("commit"));
+            Some(TagInfo {
+                name: name.to_string(),
+                source: None,
+                source_revision: None,
+                revision: commit.and_then(|c| c.attribute("revision")).and_then(|r| r.parse().ok()).unwrap_or(0),
+                date: commit.and_then(|c| c.children().find(|n| n.has_tag_name("date"))).and_then(|n| n.text()).unwrap_or("").to_string(),
+                is_rollback: name.starts_with(ROLLBACK_TAG_PREFIX),
+                is_current: false,
+            })
+        })
+        .collect();
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tags)
+}
+
+/// 读取标签的来源，以及创建标签的版本和时间
+/// - 每个标签只读取复制之后的日志，只对要显示来源的标签调用
+pub fn add_tag_sources(app: &App, project_name: &str, tags: &mut [TagInfo]) -> AppResult<()> {
+    let tags_url = app.svn_ctx.get_project_tags_url(project_name);
+    for tag in tags.iter_mut() {
+        let tag_url = format!("{}/{}", tags_url, tag.name);
+        let names = [tag.name.clone()];
+        let created = callback_for_log_xml(app.svn(), &tag_url, SVNLogType::SinceCopy, |doc| Ok(parse_tag_log(doc, project_name, &names)))?;
+        if let Some(created) = created.into_iter().next() {
+            *tag = TagInfo { is_current: tag.is_current, ..created };
+        }
+    }
+    Ok(())
+}
+
+/// 从 tags 目录或标签的 svn log -v 中读取每个标签最近一次的创建记录
+/// - names 为仓库中现有的标签，日志中已经删除的标签不会出现在结果中
+pub fn parse_tag_log(doc: &roxmltree::Document, project_name: &str, names: &[String]) -> Vec<TagInfo> {
+    let tags_dir = format!("/{}/tags", project_name);
+    let mut tags: Vec<TagInfo> = Vec::new();
+
+    // 日志从新到旧，每个标签第一次出现的添加就是它现在的来源
+    for entry in doc.descendants().filter(|n| n.has_tag_name("logentry")) {
+        let revision = entry.attribute("revision").and_then(|r| r.parse().ok()).unwrap_or(0);
+        let date = entry.children().find(|n| n.has_tag_name("date")).and_then(|n| n.text()).unwrap_or("");
+
+        for path in entry.descendants().filter(|n| n.has_tag_name("path")) {
+            if !matches!(path.attribute("action"), Some("A") | Some("R")) {
+                continue;
+            }
+            let Some((parent, name)) = path.text().unwrap_or("").trim_end_matches('/').rsplit_once('/') else { continue };
+            if !parent.ends_with(&tags_dir) || !names.iter().any(|n| n == name) || tags.iter().any(|t| t.name == name) {
+                continue;
+            }
+
+            tags.push(TagInfo {
+                name: name.to_string(),
+                source: path.attribute("copyfrom-path").and_then(|p| source_branch(p, project_name)),
+                source_revision: path.attribute("copyfrom-rev").and_then(|r| r.parse().ok()),
+                revision,
+                date: date.to_string(),
+                is_rollback: name.starts_with(ROLLBACK_TAG_PREFIX),
+                is_current: false,
+            });
+        }
+    }
+
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    tags
+}
+
+/// 复制来源路径所在的分支：/{project}/trunk... 为 trunk，/{project}/branches/{name}... 为 name
+fn source_branch(copyfrom_path: &str, project_name: &str) -> Option<String> {
+    let (_, rel_path) = copyfrom_path.split_once(&format!("/{}/", project_name))?;
+    if rel_path == "trunk" || rel_path.starts_with("trunk/") {
+        Some("trunk".to_string())
+    } else {
+        rel_path.strip_prefix("branches/").and_then(|p| p.split('/').next()).filter(|n| !n.is_empty()).map(str::to_string)
+    }
+}
+
+/// 给 branch 上的日志加上指向它们的标签
+/// - 标签的版本不早于它指向的提交，只读取可能指向这些日志的标签的来源
+pub fn tag_log_entries(app: &App, entries: &mut [LogEntry], branch: &str) -> AppResult<()> {
+    let Some(oldest) = entries.iter().map(|e| e.revision).min() else { return Ok(()) };
+    let project_name = app.svn_ctx.get_current_project_name();
+    let mut tags = get_project_tags(app, project_name, false)?;
+    tags.retain(|tag| tag.revision >= oldest);
+    add_tag_sources(app, project_name, &mut tags)?;
+    decorate_log_with_tags(entries, &tags, branch);
+    Ok(())
+}
+
+/// 给 branch 上的日志加上指向它们的标签，tags 需要有来源
+pub fn decorate_log_with_tags(entries: &mut [LogEntry], tags: &[TagInfo], branch: &str) {
+    for tag in tags.iter().filter(|t| t.source.as_deref() == Some(branch)) {
+        if let Some(entry) = entries.iter_mut().find(|e| Some(e.revision) == tag.source_revision) {
+            entry.tags.push(tag.name.clone());
+        }
+    }
+}
+
+/// 标签是只读的，在标签上不能提交
+pub fn ensure_not_on_tag(app: &App) -> AppResult<()> {
+    match app.svn_ctx.get_current_tag_name()? {
+        Some(tag_name) => Err(AppError::Validation(format!(
+            "Tag {} is read-only, create a branch from it with ws branch <name> to make changes", tag_name.yellow().bold()
+        ))),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,221 @@
+//! Unix 平台相关实现
+//!
+//! 通过 utils_platform 对外暴露
+
+use std::{fs::{self, File}, os::unix::{fs::{MetadataExt, symlink}, io::AsRawFd}, path::{Path, PathBuf}};
+
+use crossterm::style::Stylize;
+
+use crate::{commands::workspace::handle_link_folder, core::error::{AppError, AppResult}};
+
+/// 空设备，用于 svnmucc put 空文件
+pub const NULL_DEVICE: &str = "/dev/null";
+
+/// 没有设置 $VISUAL 和 $EDITOR 时用来编辑冲突文件的编辑器
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// 运行冲突规则中的命令所用的 shell
+pub const SHELL_COMMAND: [&str; 2] = ["sh", "-c"];
+
+/// 命令中引用冲突文件路径的写法，路径通过环境变量 WS_CONFLICT_PATH 传入，不会被 shell 解析
+pub const SHELL_PATH_REFERENCE: &str = "\"$WS_CONFLICT_PATH\"";
+
+/// 获取当前工作目录
+/// - getcwd 会解析软链接，而 shell 的 $PWD 保留了软链接本身的路径
+/// - 两者指向同一个目录时优先使用 $PWD
+pub fn get_working_dir() -> AppResult<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+    if let Some(pwd) = std::env::var_os("PWD").map(PathBuf::from)
+        && pwd.is_absolute()
+        && fs::canonicalize(&pwd).ok() == fs::canonicalize(&current_dir).ok() {
+        return Ok(pwd);
+    }
+    Ok(current_dir)
+}
+
+/// 获取当前工作目录对应的 .ws_store 根目录
+/// - 配置了 store_root：直接使用
+/// - 与 $HOME 在同一个文件系统上：$HOME/.ws_store
+/// - 否则：工作目录所在挂载点下的 .ws_store
+pub fn get_ws_store_root(store_root: Option<&Path>) -> AppResult<PathBuf> {
+    if let Some(store_root) = store_root {
+        return Ok(store_root.to_path_buf());
+    }
+
+    let current_dir = std::env::current_dir()?;
+    let dev = fs::metadata(&current_dir)?.dev();
+
+    if let Some(home) = get_home_dir()
+        && fs::metadata(&home).map(|m| m.dev() == dev).unwrap_or(false) {
+        return Ok(home.join(".ws_store"));
+    }
+
+    Ok(find_mount_point(&current_dir, dev).join(".ws_store"))
+}
+
+fn get_home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|h| !h.is_empty()).map(PathBuf::from)
+}
+
+/// 系统配置文件
+pub fn get_system_config_path() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc/ws-tool/config.toml"))
+}
+
+/// 用户配置文件：$XDG_CONFIG_HOME/ws-tool/config.toml，默认为 ~/.config/ws-tool/config.toml
+pub fn get_user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME").filter(|h| !h.is_empty()).map(PathBuf::from)
+        .or_else(|| get_home_dir().map(|home| home.join(".config")))?;
+    Some(config_home.join("ws-tool").join("config.toml"))
+}
+
+/// 向上查找，直到设备号变化为止，得到 path 所在的挂载点
+fn find_mount_point(path: &Path, dev: u64) -> PathBuf {
+    let mut mount_point = path.to_path_buf();
+    for ancestor in path.ancestors().skip(1) {
+        match fs::metadata(ancestor) {
+            Ok(meta) if meta.dev() == dev => mount_point = ancestor.to_path_buf(),
+            _ => break,
+        }
+    }
+    mount_point
+}
+
+/// 所有可能存在 .ws_store 的根目录：$HOME 和所有挂载点
+fn get_ws_store_candidates() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(home) = get_home_dir() {
+        roots.push(home);
+    }
+
+    match fs::read_to_string("/proc/self/mounts") {
+        Ok(mounts) => {
+            for line in mounts.lines() {
+                // 挂载点中的空格会被转义为 \040
+                if let Some(mount_point) = line.split_whitespace().nth(1) {
+                    roots.push(PathBuf::from(mount_point.replace("\\040", " ")));
+                }
+            }
+        }
+        Err(_) => roots.push(PathBuf::from("/")),
+    }
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        let candidate = root.join(".ws_store");
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// origin_dir_path: 工作文件夹路径
+/// current_dir: .ws_store/{repo_name}
+/// vault_target: .ws_store/{repo_name}/{project_name}
+///
+/// Unix 下正在使用的目录也可以移动，不需要结束父进程再由新进程完成，
+/// 直接在当前进程中执行 __link_folder 的逻辑
+pub fn spawn_internal_switcher(project_name: &str, target_repo_name: &str, store_root: Option<&Path>) -> AppResult<()> {
+    let origin_dir_path = get_working_dir()?;
+    let vault_root = get_ws_store_root(store_root)?.join(target_repo_name);
+    if !vault_root.exists() {
+        fs::create_dir_all(&vault_root)?;
+    }
+
+    std::env::set_current_dir(&vault_root)?;
+    handle_link_folder(project_name, origin_dir_path.to_string_lossy().as_ref())?;
+
+    // 重新进入工作目录，此时它已经是指向项目的软链接
+    std::env::set_current_dir(&origin_dir_path)?;
+    Ok(())
+}
+
+pub fn make_symlink(src: &Path, dst: &Path) -> AppResult<()> {
+    symlink(src, dst)?;
+    Ok(())
+}
+
+/// 删除软链接；如果是普通目录，只有为空时才能删除
+pub fn remove_symlink(path: &Path) -> AppResult<()> {
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        fs::remove_file(path)?;
+    } else {
+        fs::remove_dir(path)?;
+    }
+    Ok(())
+}
+
+/// 对整个文件加独占锁，阻塞直到拿到锁
+pub fn lock_file_exclusive(file: &File, file_path: &Path) -> AppResult<()> {
+    let result = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) };
+    if result != 0 {
+        return Err(AppError::Validation(format!("Can not lock file: {}", file_path.display())));
+    }
+    Ok(())
+}
+
+/// 释放 lock_file_exclusive 加的锁
+pub fn unlock_file(file: &File, file_path: &Path) -> AppResult<()> {
+    let result = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) };
+    if result != 0 {
+        return Err(AppError::Validation(format!("Can not unlock file: {}", file_path.display())));
+    }
+    Ok(())
+}
+
+/// 进程是否仍在运行，没有权限向它发信号的进程也算在运行
+pub fn is_process_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    if pid <= 0 {
+        return false;
+    }
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// 无法替用户打开新终端，提示用户重新进入工作目录
+pub fn launch_terminal(work_dir: &Path) -> AppResult<()> {
+    println!("{} Run {} to enter the project", "[INFO]".blue().bold(), format!("cd \"{}\"", work_dir.display()).yellow().bold());
+    Ok(())
+}
+
+/// 没有需要通知的文件管理器
+pub fn refresh_explorer_view(_path: &Path) {}
+
+pub fn report_error_gui(msg: &str) {
+    eprintln!("{} {}", "[ERR!]".red().bold(), msg);
+}
+
+// 使用软链接切换项目
+pub fn switch_project_via_symlink(target_path: &Path) -> AppResult<()> {
+    let current_dir = get_working_dir()?;
+
+    // 删除当前文件夹 remove_link
+    remove_symlink(&current_dir)?;
+
+    // 创建软链接 link_to_target
+    make_symlink(target_path, &current_dir)?;
+
+    // 进程的工作目录仍然指向旧项目，重新进入软链接
+    std::env::set_current_dir(&current_dir)?;
+
+    Ok(())
+}
+
+// 找到一个项目
+pub fn find_a_project_in_ws_store(store_root: Option<&Path>, repo_name: &str, target_project: &str) -> AppResult<Option<PathBuf>> {
+    // 1. 依次查找配置的 store_root、$HOME 和每个挂载点的 .ws_store/{repo_name}，找到 target_project 对应的路径
+    for ws_store in store_root.map(Path::to_path_buf).into_iter().chain(get_ws_store_candidates()) {
+        let potential_path = ws_store.join(repo_name).join(target_project);
+        if potential_path.is_dir() {
+            return Ok(Some(potential_path));
+        }
+    }
+    Ok(None)
+}
+
+/// 以 . 开头的目录已经是隐藏的
+pub fn set_hidden_attribute(_path: &Path) -> AppResult<()> {
+    Ok(())
+}
@@ -1,20 +1,50 @@
-#[cfg(windows)]
-use std::{fs::remove_dir, os::windows::fs::symlink_dir};
-use std::{ffi::OsString, fs, os::windows::{ffi::OsStrExt, process::CommandExt}, path::{Path, PathBuf}, process::Command};
+//! Windows 平台相关实现
+//!
+//! 通过 utils_platform 对外暴露
 
-use windows_sys::Win32::{Foundation::{CloseHandle, FALSE, TRUE}, Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM, GetFileAttributesW, GetLogicalDriveStringsW, INVALID_FILE_ATTRIBUTES, SetFileAttributesW}, System::{Diagnostics::ToolHelp::{CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW, TH32CS_SNAPPROCESS}, Threading::{GetCurrentProcessId, OpenProcess, PROCESS_TERMINATE, TerminateProcess}}, UI::Shell::{SHCNE_UPDATEDIR, SHCNF_PATHW, SHChangeNotify}};
+use std::{ffi::OsString, fs::{self, File, remove_dir}, os::windows::{ffi::OsStrExt, fs::symlink_dir, io::AsRawHandle, process::CommandExt}, path::{Path, PathBuf}, process::Command};
+
+use windows_sys::Win32::{Foundation::{CloseHandle, FALSE, HANDLE, STILL_ACTIVE, TRUE}, Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM, GetFileAttributesW, GetLogicalDriveStringsW, INVALID_FILE_ATTRIBUTES, LOCKFILE_EXCLUSIVE_LOCK, LockFileEx, SetFileAttributesW, UnlockFileEx}, System::{Diagnostics::ToolHelp::{CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW, TH32CS_SNAPPROCESS}, IO::OVERLAPPED, Threading::{GetCurrentProcessId, GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE, TerminateProcess}}, UI::Shell::{SHCNE_UPDATEDIR, SHCNF_PATHW, SHChangeNotify}};
 
 use crate::core::error::{AppError, AppResult};
 
+/// 空设备，用于 svnmucc put 空文件
+pub const NULL_DEVICE: &str = "NUL";
+
+/// 没有设置 $VISUAL 和 $EDITOR 时用来编辑冲突文件的编辑器
+pub const DEFAULT_EDITOR: &str = "notepad";
+
+/// 运行冲突规则中的命令所用的 shell，/V:ON 打开延迟展开，!VAR! 在解析命令之后才展开
+pub const SHELL_COMMAND: [&str; 3] = ["cmd", "/V:ON", "/C"];
+
+/// 命令中引用冲突文件路径的写法，路径通过环境变量 WS_CONFLICT_PATH 传入，不会被 shell 解析
+pub const SHELL_PATH_REFERENCE: &str = "\"!WS_CONFLICT_PATH!\"";
+
+/// 获取当前工作目录
+pub fn get_working_dir() -> AppResult<PathBuf> {
+    Ok(std::env::current_dir()?)
+}
+
+/// 获取当前工作目录所在盘符的 .ws_store 根目录
+/// - 配置了 store_root：直接使用
+/// - {drive}\.ws_store
+pub fn get_ws_store_root(store_root: Option<&Path>) -> AppResult<PathBuf> {
+    if let Some(store_root) = store_root {
+        return Ok(store_root.to_path_buf());
+    }
+    let current_dir_path = std::env::current_dir()?;
+    let root_path = current_dir_path.components().next().ok_or(AppError::Validation("Cannot determine current directory root".to_string()))?.as_os_str().to_string_lossy();
+    Ok(PathBuf::from(root_path.as_ref()).join("\\.ws_store"))
+}
+
 /// origin_dir_path: 工作文件夹路径
 /// current_dir: .ws_store/{repo_name}
 /// vault_target: .ws_store/{repo_name}/{project_name}
-pub fn spawn_internal_switcher(project_name: &str, target_repo_name: &str) -> AppResult<()> {
+pub fn spawn_internal_switcher(project_name: &str, target_repo_name: &str, store_root: Option<&Path>) -> AppResult<()> {
     let current_exe = std::env::current_exe()?;
     let parent_pid = get_parent_pid_to_kill()?;
-    let origin_dir_path = std::env::current_dir()?;
-    let root_path = origin_dir_path.components().next().ok_or(AppError::Validation("Cannot determine current directory root".to_string()))?.as_os_str().to_string_lossy();
-    let vault_root = std::path::PathBuf::from(root_path.as_ref()).join("\\.ws_store").join(target_repo_name);
+    let origin_dir_path = get_working_dir()?;
+    let vault_root = get_ws_store_root(store_root)?.join(target_repo_name);
     if !vault_root.exists() {
         fs::create_dir_all(&vault_root)?;
     }
@@ -29,6 +59,16 @@ pub fn spawn_internal_switcher(project_name: &str, target_repo_name: &str) -> Ap
     Ok(())
 }
 
+/// 系统配置文件：%ProgramData%\ws-tool\config.toml
+pub fn get_system_config_path() -> Option<PathBuf> {
+    std::env::var_os("ProgramData").filter(|p| !p.is_empty()).map(|p| PathBuf::from(p).join("ws-tool").join("config.toml"))
+}
+
+/// 用户配置文件：%USERPROFILE%\.config\ws-tool\config.toml
+pub fn get_user_config_path() -> Option<PathBuf> {
+    std::env::var_os("USERPROFILE").filter(|p| !p.is_empty()).map(|p| PathBuf::from(p).join(".config").join("ws-tool").join("config.toml"))
+}
+
 pub fn get_windows_drive_letters() -> Vec<String> {
     let mut drives = Vec::new();
     let mut buffer = [0u16; 256]; // 缓冲区，足够大
@@ -103,18 +143,73 @@ pub fn kill_process_by_pid(pid: u32) -> AppResult<()> {
     }
 }
 
+/// 进程是否仍在运行
+pub fn is_process_running(pid: u32) -> bool {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut exit_code = 0;
+        let result = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        result != FALSE && exit_code == STILL_ACTIVE as u32
+    }
+}
+
 pub fn make_symlink(src: &Path, dst: &Path) -> AppResult<()> {
-     #[cfg(windows)]
     symlink_dir(src, dst)?;
     Ok(())
 }
 
 pub fn remove_symlink(path: &Path) -> AppResult<()> {
-    #[cfg(windows)]
     remove_dir(path)?;
     Ok(())
 }
 
+/// 对整个文件加独占锁，阻塞直到拿到锁
+pub fn lock_file_exclusive(file: &File, file_path: &Path) -> AppResult<()> {
+    let handle = file.as_raw_handle() as HANDLE;
+
+    unsafe {
+        let mut overlapped: OVERLAPPED = std::mem::zeroed();
+        let result = LockFileEx(
+            handle,
+            LOCKFILE_EXCLUSIVE_LOCK,
+            0,
+            u32::MAX, // 锁定区域长度低位
+            u32::MAX, // 锁定区域长度高位（锁住整个巨大的范围）
+            &mut overlapped,
+        );
+
+        if result == 0 {
+            return Err(AppError::Validation(format!("Can not lock file: {}", file_path.display())));
+        }
+    }
+    Ok(())
+}
+
+/// 释放 lock_file_exclusive 加的锁
+pub fn unlock_file(file: &File, file_path: &Path) -> AppResult<()> {
+    let handle = file.as_raw_handle() as HANDLE;
+
+    unsafe {
+        let mut overlapped: OVERLAPPED = std::mem::zeroed();
+        let result = UnlockFileEx(
+            handle,
+            0, // Reserved
+            u32::MAX,
+            u32::MAX,
+            &mut overlapped,
+        );
+        
+        if result == 0 {
+             return Err(AppError::Validation(format!("Can not unlock file: {}", file_path.display())));
+        }
+    }
+    Ok(())
+}
+
 // 启动终端的策略
 pub fn launch_terminal(work_dir: &Path) -> AppResult<()> {
     let dir_str = work_dir.to_string_lossy();
@@ -170,7 +265,7 @@ pub fn report_error_gui(msg: &str) {
 
 // 使用软链接切换项目
 pub fn switch_project_via_symlink(target_path: &Path) -> AppResult<()> {
-    let current_dir = std::env::current_dir()?;
+    let current_dir = get_working_dir()?;
 
     // 删除当前文件夹 remove_link
     remove_symlink(&current_dir)?;
@@ -185,7 +280,13 @@ pub fn switch_project_via_symlink(target_path: &Path) -> AppResult<()> {
 }
 
 // 找到一个项目
-pub fn find_a_project_in_ws_store(repo_name: &str, target_project: &str) -> AppResult<Option<PathBuf>> {
+pub fn find_a_project_in_ws_store(store_root: Option<&Path>, repo_name: &str, target_project: &str) -> AppResult<Option<PathBuf>> {
+    // 0. 优先查找配置的 store_root
+    if let Some(potential_path) = store_root.map(|root| root.join(repo_name).join(target_project))
+        && potential_path.is_dir() {
+        return Ok(Some(potential_path));
+    }
+
     // 1. 遍历每个盘符的 .ws_store/{repo_name} 下的所有项目文件夹，找到 target_project 对应的路径
     let drives = get_windows_drive_letters();
     let mut target_path: Option<PathBuf> = None;
@@ -6,28 +6,29 @@
 //! 
 //! - list: 列出工作区中的所有项目
 //! - new: 在工作区中创建一个新项目
-//! - switch: 切换当前工作区到另一个项目
+//! - switch: 切换当前工作区到另一个项目、分支或标签
 //! - delete: 删除工作区中的一个项目
 //! - restore: 恢复一个被删除的项目
 //! 
 
-use std::{collections::HashSet, env, fs, process::Stdio};
+use std::{collections::HashSet, env, fs};
 
 use crossterm::style::Stylize;
 
 use crate::{
     commands::{
-        models::{ProjectStatus, SVNLogType}, utils::{callback_for_log_xml, check_project_exists, check_url_exists, validate_folder_name}, utils_branch::get_project_branches, utils_clean_workspace::ensure_clean_workspace, utils_file::{ChangeLockType, change_lock_file, check_is_empty_folder, ensure_delete, get_lock_file_path}, utils_windows::{find_a_project_in_ws_store, launch_terminal, make_symlink, refresh_explorer_view, remove_symlink, report_error_gui, set_hidden_attribute, spawn_internal_switcher, switch_project_via_symlink}
+        models::{ProjectStatus, SVNLogType}, utils::{callback_for_log_xml, check_project_exists, check_url_exists, validate_folder_name}, utils_branch::{add_ahead_behind, get_project_branches}, utils_tag::get_project_tags, utils_clean_workspace::ensure_clean_workspace, utils_stash::auto_pop_stash, utils_file::{ChangeLockType, change_lock_file, check_is_empty_folder, ensure_delete, get_lock_file_path}, utils_platform::{NULL_DEVICE, get_working_dir, launch_terminal, make_symlink, refresh_explorer_view, remove_symlink, report_error_gui, set_hidden_attribute, spawn_internal_switcher, switch_project_via_symlink}
     },
     core::{
-        app::App, context::check_and_repair_workspace, error::{AppError, AppResult}, svn::{svn_checkout, svn_cleanup, svn_cleanup_workspace, svn_commit_externals, svn_copy, svn_delete, svn_list, svn_mkdir, svn_propset, svn_svnmucc, svn_switch, svn_update}, svn_repo::{svnadmin_create, svnadmin_dump, svnadmin_load, svndumpfilter}
-    }, ui::models::ProjectInfo,
+        app::App, context::check_and_repair_workspace, error::{AppError, AppResult}, svn::{svn_checkout, svn_cleanup, svn_cleanup_workspace, svn_commit_externals, svn_copy, svn_delete, svn_list, svn_mkdir, svn_propset, svn_svnmucc, svn_switch, svn_update}, svn_repo::{svnadmin_create, svnadmin_filter_into}
+    }, ui::{models::ProjectInfo, policy::PromptKind},
 };
 
 /// 列出工作区中的所有项目
-pub fn handle_list(app: &App, list_all: bool) -> AppResult<()> {
+/// - compare 时显示每个分支领先和落后于 trunk 的提交数
+pub fn handle_list(app: &App, list_all: bool, compare: bool) -> AppResult<()> {
     // 1. Active projects
-    let active_string = svn_list(&[app.svn_ctx.get_repo_root_url()])?;
+    let active_string = svn_list(app.svn(), &[app.svn_ctx.get_repo_root_url()])?;
     let mut active_projects = Vec::new();
     let mut active_names = HashSet::new();
 
@@ -35,13 +36,18 @@ pub fn handle_list(app: &App, list_all: bool) -> AppResult<()> {
         let name = line.trim_matches('/');
         if !name.is_empty() && name != ".ws_empty" {
             active_names.insert(name.to_string());
-            let branch_info = get_project_branches(app, name)?;
+            let mut branch_info = get_project_branches(app, name)?;
+            if compare {
+                add_ahead_behind(app, name, &mut branch_info)?;
+            }
+            let tag_info = get_project_tags(app, name, false)?;
             if app.svn_ctx.get_current_project_name() == name {
                 active_projects.insert(0, ProjectInfo {
                     name: name.to_string(),
                     is_deleted: false,
                     is_current: true,
-                    branches: Some(branch_info)
+                    branches: Some(branch_info),
+                    tags: Some(tag_info),
                 });
             }
             else {
@@ -49,7 +55,8 @@ pub fn handle_list(app: &App, list_all: bool) -> AppResult<()> {
                     name: name.to_string(),
                     is_deleted: false,
                     is_current: false,
-                    branches: Some(branch_info)
+                    branches: Some(branch_info),
+                    tags: Some(tag_info),
                 });
             }
         }
@@ -81,6 +88,7 @@ pub fn handle_list(app: &App, list_all: bool) -> AppResult<()> {
                             is_deleted: true,
                             is_current: false,
                             branches: None,
+                            tags: None,
                         });
                     }
                 }
@@ -89,7 +97,7 @@ pub fn handle_list(app: &App, list_all: bool) -> AppResult<()> {
         Ok(())
     };
 
-    callback_for_log_xml(app.svn_ctx.get_repo_root_url(), SVNLogType::Default, callback)?;
+    callback_for_log_xml(app.svn(), app.svn_ctx.get_repo_root_url(), SVNLogType::Default, callback)?;
 
     app.ui.show_project_list(active_projects, Some(deleted_projects));
     Ok(())
@@ -100,7 +108,7 @@ pub fn handle_new(app: &App, project_name: &str) -> AppResult<()> {
     validate_folder_name(project_name, true)?;
     
     let project_root_url = app.svn_ctx.get_project_root_url(project_name);
-    let project_exists = check_url_exists(&project_root_url)?;
+    let project_exists = check_url_exists(app.svn(), &project_root_url)?;
     app.ui.update_step("Checking project existence");
     if project_exists {
         app.ui.success(&format!("Project {} already exists, nothing to do.", project_name.yellow().bold()));
@@ -112,11 +120,11 @@ pub fn handle_new(app: &App, project_name: &str) -> AppResult<()> {
         let branches_url = format!("{}/branches", project_root_url);
         let tags_url = format!("{}/tags", project_root_url);
 
-        svn_mkdir(&["--parents", &trunk_url, &branches_url, &tags_url, "-m", &format!("[WS-INIT] {}", project_name)])?;
+        svn_mkdir(app.svn(), &["--parents", &trunk_url, &branches_url, &tags_url, "-m", &format!("[WS-INIT] {}", project_name)])?;
 
         // 添加一个默认的 .gitignore 文件
-        svn_svnmucc(&[
-            "put", "NUL", &format!("{}/.gitignore", project_root_url),
+        svn_svnmucc(app.svn(), &[
+            "put", NULL_DEVICE, &format!("{}/.gitignore", project_root_url),
             "-m", "[WS-INIT] Add default .gitignore file",
         ])?;
 
@@ -124,22 +132,20 @@ pub fn handle_new(app: &App, project_name: &str) -> AppResult<()> {
 
         // 使用 checkout 将项目检出到 .ws_store/{project_name} 中
         app.ui.update_step("Checking out the new project");
-        let current_dir_path = std::env::current_dir()?;
-        let root_path = current_dir_path.components().next().ok_or(AppError::Validation("Cannot determine current directory root".to_string()))?.as_os_str().to_string_lossy();
-        let ws_store_path = std::path::PathBuf::from(root_path.as_ref()).join("\\.ws_store");
+        let ws_store_path = app.svn_ctx.get_ws_store_root()?;
         let vault_root = ws_store_path.join(app.svn_ctx.get_repo_name()?);
         let project_dir = vault_root.join(project_name);
         if !ws_store_path.exists() {
             fs::create_dir(&ws_store_path)?;
             set_hidden_attribute(&ws_store_path)?;
         }
-        svn_checkout(&[&trunk_url, project_dir.to_string_lossy().as_ref()])?;
+        svn_checkout(app.svn(), &[&trunk_url, project_dir.to_string_lossy().as_ref()])?;
         // checkout 成功，有 .svn 目录了，链接根目录的 .gitignore 文件
-        svn_propset(&["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), project_dir.to_string_lossy().as_ref()])?;
+        svn_propset(app.svn(), &["svn:externals", &format!("^/{}/.gitignore .gitignore", project_name), project_dir.to_string_lossy().as_ref()])?;
         // 更新 externals
-        svn_update(&[project_dir.to_string_lossy().as_ref()])?;
+        svn_update(app.svn(), &[project_dir.to_string_lossy().as_ref()])?;
         // 提交 externals 设置
-        svn_commit_externals(project_dir.to_string_lossy().as_ref(), true)?;
+        svn_commit_externals(app.svn(), project_dir.to_string_lossy().as_ref(), true)?;
 
         app.ui.success(&format!("Checked out to the new project {}", project_name.yellow().bold()));
     }
@@ -149,12 +155,12 @@ pub fn handle_new(app: &App, project_name: &str) -> AppResult<()> {
             app.ui.info("Use 'ws checkout <project_name>' to switch to the project.");
         }
         else {
-            spawn_internal_switcher(project_name, &app.svn_ctx.get_repo_name()?)?;
+            spawn_internal_switcher(project_name, &app.svn_ctx.get_repo_name()?, app.svn_ctx.get_config().store_root.as_deref())?;
         }
     }
     else {
         // 当前已有项目，询问是否切换过去
-        let switch_to_new = app.ui.selector_yes_or_no("Switch to the project now?")?;
+        let switch_to_new = app.ui.selector_yes_or_no(PromptKind::Confirm, "Switch to the project now?")?;
         if switch_to_new {
             handle_switch(app, Some(project_name), None)?;
         }
@@ -169,7 +175,7 @@ pub fn handle_checkout(app: &App, project_name: &str) -> AppResult<()> {
 
     app.ui.update_step("Checking project existence");
     let project_trunk_url = app.svn_ctx.get_project_trunk_url(project_name);
-    let project_exists = check_url_exists(&project_trunk_url)?;
+    let project_exists = check_url_exists(app.svn(), &project_trunk_url)?;
 
     if !project_exists {
         app.ui.error(&format!("Project {} does not exist.", project_name.yellow().bold()));
@@ -183,7 +189,7 @@ pub fn handle_checkout(app: &App, project_name: &str) -> AppResult<()> {
         }
 
         // 查找 .ws_store/{repo_name}/{project_name}/.svn 是否存在，如果不存在则 checkout
-        let target_path = match find_a_project_in_ws_store(&app.svn_ctx.get_repo_name()?, project_name)? {
+        let target_path = match app.svn_ctx.find_project_in_ws_store(project_name)? {
             Some(p) => p,
             None => {
                 return Err(AppError::Validation(format!("Project {} is not found in any workspace", project_name.yellow().bold())));
@@ -192,10 +198,10 @@ pub fn handle_checkout(app: &App, project_name: &str) -> AppResult<()> {
         let svn_dir = target_path.join(".svn");
         if !svn_dir.exists() {
             app.ui.update_step("Checking out the project");
-            svn_checkout(&[&project_trunk_url, target_path.to_string_lossy().as_ref()])?;
+            svn_checkout(app.svn(), &[&project_trunk_url, target_path.to_string_lossy().as_ref()])?;
         }
 
-        spawn_internal_switcher(project_name, &app.svn_ctx.get_repo_name()?)?;
+        spawn_internal_switcher(project_name, &app.svn_ctx.get_repo_name()?, app.svn_ctx.get_config().store_root.as_deref())?;
     }
     else {
         handle_switch(app, Some(project_name), None)?;
@@ -215,9 +221,9 @@ pub fn handle_uncheckout(app: &App) -> AppResult<()> {
     ensure_clean_workspace(app)?;
 
     app.ui.update_step("Cleanup workspace");
-    svn_cleanup_workspace()?;
+    svn_cleanup_workspace(app.svn())?;
 
-    let current_project_path = match find_a_project_in_ws_store(&app.svn_ctx.get_repo_name()?, app.svn_ctx.get_current_project_name())? {
+    let current_project_path = match app.svn_ctx.find_project_in_ws_store(app.svn_ctx.get_current_project_name())? {
         Some(p) => p,
         None => {
             return Err(AppError::Validation(format!("Current project {} is not checked out in any workspace.", app.svn_ctx.get_current_project_name().yellow().bold())));
@@ -225,7 +231,7 @@ pub fn handle_uncheckout(app: &App) -> AppResult<()> {
     };
 
     let current_lock_file_path = get_lock_file_path(
-        current_project_path.parent().ok_or(AppError::Validation(format!("No parent folder found")))?, 
+        current_project_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?, 
         app.svn_ctx.get_current_project_name()
     )?;
 
@@ -235,7 +241,7 @@ pub fn handle_uncheckout(app: &App) -> AppResult<()> {
     if current_lock_value == 0 {
         // 切换到 .ws_empty
         let empty_url = format!("{}/.ws_empty", app.svn_ctx.get_repo_root_url());
-        svn_switch(&empty_url)?;
+        svn_switch(app.svn(), &empty_url)?;
 
         // 删除 .svn 目录
         let svn_dir = std::env::current_dir()?.join(".svn");
@@ -251,7 +257,7 @@ pub fn handle_uncheckout(app: &App) -> AppResult<()> {
     }
 
     // 删除软链接
-    remove_symlink(&env::current_dir()?)?;
+    remove_symlink(&get_working_dir()?)?;
 
     app.ui.success(&format!("Unchecked out from project {}", current_project_name.yellow().bold()));
     Ok(())
@@ -265,11 +271,27 @@ pub fn handle_switch(app: &App, project_name: Option<&str>, branch: Option<Strin
     validate_folder_name(target_project, true)?;
     validate_folder_name(&target_branch, true)?;
     let target_subpath = if target_branch == "trunk" { target_branch } else { format!("branches/{}", target_branch) };
+    switch_to(app, target_project, &target_subpath)
+}
+
+/// 切换当前工作区到某个项目的标签，标签是只读的
+pub fn handle_switch_tag(app: &App, project_name: Option<&str>, tag_name: &str) -> AppResult<()> {
+    app.ui.update_step("Parsing target project");
+    let target_project = project_name.unwrap_or(app.svn_ctx.get_current_project_name());
+    validate_folder_name(target_project, true)?;
+    validate_folder_name(tag_name, true)?;
+    switch_to(app, target_project, &format!("tags/{}", tag_name))?;
+    app.ui.info("Tags are read-only, create a branch with ws branch <name> to make changes");
+    Ok(())
+}
+
+/// 切换到 target_project 的 target_subpath（trunk、branches/{name} 或 tags/{name}）
+fn switch_to(app: &App, target_project: &str, target_subpath: &str) -> AppResult<()> {
     let target_full_url = format!("{}/{}/{}", app.svn_ctx.get_repo_root_url(), target_project, target_subpath);
 
     app.ui.update_step("Checking project existence");
-    if !check_url_exists(&target_full_url)? {
-        app.ui.warn(&format!("The target project {} does not exist or branch {} do not exist in that project", format!("{}", target_project).yellow().bold(), format!("{}", target_subpath).yellow().bold()));
+    if !check_url_exists(app.svn(), &target_full_url)? {
+        app.ui.warn(&format!("The target project {} does not exist or branch {} do not exist in that project", target_project.to_string().yellow().bold(), target_subpath.to_string().yellow().bold()));
         return Ok(())
     }
     
@@ -282,29 +304,29 @@ pub fn handle_switch(app: &App, project_name: Option<&str>, branch: Option<Strin
     ensure_clean_workspace(app)?;
 
     app.ui.update_step("Cleanup workspace");
-    svn_cleanup_workspace()?;
+    svn_cleanup_workspace(app.svn())?;
 
     app.ui.update_step(&format!("Switching to {}", target_project));
     // 如果是跨项目移动，用软链接先把项目切换过去
     if target_project != app.svn_ctx.get_current_project_name() {
-        let current_project_path = match find_a_project_in_ws_store(&app.svn_ctx.get_repo_name()?, app.svn_ctx.get_current_project_name())? {
+        let current_project_path = match app.svn_ctx.find_project_in_ws_store(app.svn_ctx.get_current_project_name())? {
             Some(p) => p,
             None => {
                 return Err(AppError::Validation(format!("Current project {} is not checked out in any workspace.", app.svn_ctx.get_current_project_name().yellow().bold())));
             }
         };
-        let target_project_path = match find_a_project_in_ws_store(&app.svn_ctx.get_repo_name()?, target_project)? {
+        let target_project_path = match app.svn_ctx.find_project_in_ws_store(target_project)? {
             Some(p) => p,
             None => {
                 return Err(AppError::Validation(format!("Target project {} is not checked out in any workspace.", target_project.yellow().bold())));
             }
         };
         let current_lock_file_path = get_lock_file_path(
-            current_project_path.parent().ok_or(AppError::Validation(format!("No parent folder found")))?, 
+            current_project_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?, 
             app.svn_ctx.get_current_project_name()
         )?;
         let target_lock_file_path = get_lock_file_path(
-            target_project_path.parent().ok_or(AppError::Validation(format!("No parent folder found")))?, 
+            target_project_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?, 
             target_project
         )?;
 
@@ -314,7 +336,7 @@ pub fn handle_switch(app: &App, project_name: Option<&str>, branch: Option<Strin
         if current_lock_value == 0 {
             // 首先切换到仓库根目录的 .ws_empty 文件夹，以清空当前工作副本，最后删除 .svn 目录，.gitignore 也要删除
             let empty_url = format!("{}/.ws_empty", app.svn_ctx.get_repo_root_url());
-            svn_switch(&empty_url)?;
+            svn_switch(app.svn(), &empty_url)?;
             let svn_dir = std::env::current_dir()?.join(".svn");
             if svn_dir.exists() {
                 fs::remove_dir_all(svn_dir)?;
@@ -331,32 +353,50 @@ pub fn handle_switch(app: &App, project_name: Option<&str>, branch: Option<Strin
         // 如果目标项目的 lock 值为 1，说明没有其他工作区在使用该项目，应该做 checkout
         if target_lock_value == 1 {
             app.ui.update_step("Checking out target project");
-            svn_checkout(&[&target_full_url, target_project_path.to_string_lossy().as_ref(), "--force"])?;
+            svn_checkout(app.svn(), &[&target_full_url, target_project_path.to_string_lossy().as_ref(), "--force"])?;
         }
     }
     else {
         // 项目内直接 svn switch 到指定的分支
-        svn_switch(&target_full_url)?;
+        svn_switch(app.svn(), &target_full_url)?;
         refresh_explorer_view(&env::current_dir()?);
     }
 
     app.ui.update_step("Final cleanup");
-    svn_cleanup()?;
+    svn_cleanup(app.svn())?;
 
     app.ui.success(&format!("Switched to the latest revision of project {}, branch: {}", target_project.yellow().bold(), target_subpath.yellow().bold()));
+
+    // 回到离开时的分支，恢复 ensure_clean_workspace 保存的更改
+    let target_branch = if target_subpath == "trunk" { Some("trunk") } else { target_subpath.strip_prefix("branches/") };
+    if let Some(branch) = target_branch {
+        app.ui.update_step("Restoring stashed changes");
+        if let Some(stash) = auto_pop_stash(app, target_project, branch)? {
+            app.ui.info(&format!("Restored stashed changes: {}", stash.message));
+        }
+    }
     Ok(())
 }
 
 /// 软删除工作区中的一个项目，保留其历史记录
-fn soft_delete(target_url: &str, project_name: &str) -> AppResult<()> {
-    svn_delete(&[target_url, "-m", &format!("Delete project {}", project_name)])
+fn soft_delete(app: &App, target_url: &str, project_name: &str) -> AppResult<()> {
+    svn_delete(app.svn(), &[target_url, "-m", &app.svn_ctx.get_config().messages.delete_project_message(project_name)])
 }
 
 /// 强制删除工作区中的一个项目，永久删除其历史记录
 fn force_delete(app: &App, project_name: &str) -> AppResult<()> {
+    // 重写历史需要 svnadmin 直接访问仓库，远程仓库只能软删除
+    let repo_fs_path = match app.svn_ctx.get_repo_fs_path("delete --force") {
+        Ok(path) => path,
+        Err(e) => {
+            app.ui.info("Use 'delete' without '--force' to remove the project while keeping its history");
+            return Err(e);
+        }
+    };
+
     if app.svn_ctx.check_review_state() {
         app.ui.warn(&format!("Not in newest project revision. Need switch to latest revision of project {} first.", project_name.yellow().bold()));
-        if !app.ui.selector_yes_or_no("Continue to switch?")? {
+        if !app.ui.selector_yes_or_no(PromptKind::Confirm, "Continue to switch?")? {
             return Err(AppError::OperationCancelled);
         }
 
@@ -366,13 +406,12 @@ fn force_delete(app: &App, project_name: &str) -> AppResult<()> {
     app.ui.warn("This operation will rewrite the entire repository history");
     app.ui.warn(&format!("Project {} will be permanently removed and cannot be restored", project_name.yellow().bold()));
 
-    if !app.ui.selector_yes_or_no(&format!("Confirm to PERMANENTLY delete project {}", project_name.yellow().bold()))? {
+    if !app.ui.selector_yes_or_no(PromptKind::Confirm, &format!("Confirm to PERMANENTLY delete project {}", project_name.yellow().bold()))? {
         return Err(AppError::OperationCancelled);
     }
 
     // 1. Create temp repo
     app.ui.update_step("Creating temporary repository");
-    let repo_fs_path = app.svn_ctx.get_repo_fs_path();
     let repo_parent = repo_fs_path.parent().ok_or_else(|| AppError::Validation("Cannot determine repository parent directory".to_string()))?;
     let repo_name = repo_fs_path.file_name().ok_or_else(|| AppError::Validation("Cannot determine repository name".to_string()))?.to_string_lossy();
     
@@ -382,28 +421,14 @@ fn force_delete(app: &App, project_name: &str) -> AppResult<()> {
         fs::remove_dir_all(&temp_repo_path)?;
     }
     let temp_repo_path_str = temp_repo_path.to_str().ok_or_else(|| AppError::Validation("Temporary repository path is not valid UTF-8".to_string()))?;
-    svnadmin_create(temp_repo_path_str)?;
+    svnadmin_create(app.svn(), temp_repo_path_str)?;
 
-    // 2. Dump
-    app.ui.update_step("Dumping repository");
+    // 2. Dump | Filter | Load
+    app.ui.update_step("Filtering repository into temporary repository");
     let repo_path_str = repo_fs_path.to_str().ok_or_else(|| AppError::Validation("Repository path is not valid UTF-8".to_string()))?;
-    let mut dump_child = svnadmin_dump(&[repo_path_str, "--quiet"])?;
-    let dump_stdout = dump_child.stdout.take().ok_or_else(|| AppError::Validation("Failed to capture dump output".to_string()))?;
-
-    // 3. Filter
-    app.ui.update_step("Filtering dump");
-    let mut filter_child = svndumpfilter(&["exclude", project_name, "--drop-empty-revs", "--renumber-revs", "--quiet"], Stdio::from(dump_stdout))?;
-    let filter_stdout = filter_child.stdout.take().ok_or_else(|| AppError::Validation("Failed to capture filter output".to_string()))?;
-
-    // 4. Load
-    app.ui.update_step("Loading into temporary repository");
-    let mut load_child = svnadmin_load(&[temp_repo_path_str, "--quiet", "--ignore-uuid"], Stdio::from(filter_stdout))?;
-    let load_status = load_child.wait()?;
-    if !load_status.success() {
-        return Err(AppError::Validation("Loading into temporary repository failed".to_string()));
-    }
+    svnadmin_filter_into(app.svn(), repo_path_str, &["exclude", project_name, "--drop-empty-revs", "--renumber-revs", "--quiet"], temp_repo_path_str)?;
     
-    // 5. Replace
+    // 3. Replace
     app.ui.update_step("Replacing original repository");
     let backup_path = repo_parent.join(format!("{}_backup", repo_name));
     if backup_path.exists() {
@@ -419,13 +444,13 @@ fn force_delete(app: &App, project_name: &str) -> AppResult<()> {
         return Err(AppError::Io(e));
     }
 
-    // 6. Fix working copy
+    // 4. Fix working copy
     app.ui.update_step("Repairing workspace");
     check_and_repair_workspace(&app.svn_ctx)?;
     app.ui.success(&format!("Repository cleaned successfully. Original repository backed up at: {}", backup_path.to_string_lossy().yellow()));
 
-    // 7. Delete .ws_store/{repo_name}/{project_name} folder
-    if let Some(target_path) = find_a_project_in_ws_store(&repo_name, project_name)? {
+    // 5. Delete .ws_store/{repo_name}/{project_name} folder
+    if let Some(target_path) = app.svn_ctx.find_project_in_ws_store(project_name)? {
         match fs::remove_dir_all(&target_path) {
             Ok(_) => {},
             Err(e) => {
@@ -434,7 +459,7 @@ fn force_delete(app: &App, project_name: &str) -> AppResult<()> {
             }
         }
         let lock_file = get_lock_file_path(
-            target_path.parent().ok_or(AppError::Validation(format!("No parent folder found")))?, 
+            target_path.parent().ok_or(AppError::Validation("No parent folder found".to_string()))?, 
             project_name
         )?;
 
@@ -459,14 +484,14 @@ pub fn handle_delete(app: &App, project_name: &str, force: bool) -> AppResult<()
     validate_folder_name(project_name, true)?;
 
     let target_url = app.svn_ctx.get_project_root_url(project_name);
-    let project_status = check_project_exists(&app.svn_ctx, &target_url, project_name, false)?;
+    let project_status = check_project_exists(app, &target_url, project_name, false)?;
 
     match project_status {
         ProjectStatus::NonExistent => {
             app.ui.info(&format!("The project {} does not exist", project_name.yellow().bold()));
         },
         ProjectStatus::Active => {
-            if !ensure_delete(app)? {
+            if !ensure_delete(app, project_name)? {
                 app.ui.error(&format!("The project {} is still in use by other workspaces. Cannot delete", project_name.yellow().bold()));
                 return Ok(());
             }
@@ -474,14 +499,14 @@ pub fn handle_delete(app: &App, project_name: &str, force: bool) -> AppResult<()
                 app.ui.info("This will remove the project in the latest revision, but history will be preserved.");
                 app.ui.info("Use '--force' or '-f' option to permanently delete the project.");
                 app.ui.update_step(&format!("Deleting project: {}", project_name));
-                soft_delete(&target_url, project_name)?;
+                soft_delete(app, &target_url, project_name)?;
                 app.ui.success(&format!("Project {} is marked as deleted", project_name.yellow().bold()));
             } else {
                 force_delete(app, project_name)?;
             }
         },
         ProjectStatus::Deleted => {
-            if !ensure_delete(app)? {
+            if !ensure_delete(app, project_name)? {
                 app.ui.error(&format!("The project {} is still in use by other workspaces. Cannot delete", project_name.yellow().bold()));
                 return Ok(());
             }
@@ -503,7 +528,7 @@ pub fn handle_restore(app: &App, project_name: &str) -> AppResult<()> {
     let target_url = format!("{}/{}", app.svn_ctx.get_repo_root_url(), project_name);
 
     app.ui.update_step("Checking project status");
-    if let ProjectStatus::Active = check_project_exists(&app.svn_ctx, &target_url, project_name, true)? {
+    if let ProjectStatus::Active = check_project_exists(app, &target_url, project_name, true)? {
         app.ui.success(&format!("Project {} is not deleted, no need to restore", project_name));
         return Ok(());
     }
@@ -517,11 +542,10 @@ pub fn handle_restore(app: &App, project_name: &str) -> AppResult<()> {
                     if path_node.attribute("action") == Some("D") {
                         let path_txt = path_node.text().unwrap_or("");
                         let clean_name = path_txt.trim_start_matches('/').split('/').next().unwrap_or("");
-                        if clean_name == project_name {
-                            if let Some(rev_str) = entry.attribute("revision") {
+                        if clean_name == project_name
+                            && let Some(rev_str) = entry.attribute("revision") {
                                 return Ok(rev_str.parse::<u64>().unwrap_or(0));
                             }
-                        }
                     }
                 }
             }
@@ -529,7 +553,7 @@ pub fn handle_restore(app: &App, project_name: &str) -> AppResult<()> {
         Ok(0)
     };
 
-    let deleted_rev = callback_for_log_xml(app.svn_ctx.get_repo_root_url(), SVNLogType::Default, callback)?;
+    let deleted_rev = callback_for_log_xml(app.svn(), app.svn_ctx.get_repo_root_url(), SVNLogType::Default, callback)?;
 
     if deleted_rev == 0 {
         return Err(AppError::Validation(format!("Could not find deletion record for project {}", project_name.yellow().bold())));
@@ -539,10 +563,10 @@ pub fn handle_restore(app: &App, project_name: &str) -> AppResult<()> {
     let restore_rev = deleted_rev - 1;
     let src_url = format!("{}@{}", target_url, restore_rev);
 
-    svn_copy(&[&src_url, &target_url, "-m", &format!("Restore project {}", project_name)])?;
+    svn_copy(app.svn(), &[&src_url, &target_url, "-m", &app.svn_ctx.get_config().messages.restore_project_message(project_name)])?;
     app.ui.success(&format!("Project {} has been restored successfully", project_name.yellow().bold()));
 
-    if app.ui.selector_yes_or_no("Switch to the restored project?")? {
+    if app.ui.selector_yes_or_no(PromptKind::Confirm, "Switch to the restored project?")? {
         handle_switch(app, Some(project_name), None)?;
     }
 
@@ -571,7 +595,7 @@ pub fn handle_link_folder(project_name: &str, origin_dir_path: &str) -> AppResul
             break;
         }
 
-        if let Ok(_) = fs_extra::dir::move_dir(&origin_path, &vault_target, &options) {
+        if fs_extra::dir::move_dir(&origin_path, &vault_target, &options).is_ok() {
             success_delete = true;
             break;
         }
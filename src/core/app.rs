@@ -1,31 +1,66 @@
 
-use crate::{core::{context::{SvnContext, check_and_repair_workspace, get_svn_context}, error::AppResult}, ui::display::AppUI};
+use std::rc::Rc;
+
+use crate::{core::{config::Config, context::{SvnContext, check_and_repair_workspace, get_svn_context}, error::AppResult, svn_executor::{CommandExecutor, SvnExecutor}}, ui::display::AppUI};
 
 pub struct App {
     pub ui: AppUI,
+    /// 执行 svn 命令的执行器
+    svn: Rc<dyn SvnExecutor>,
     pub svn_ctx: SvnContext,
 }
 
 impl App {
     pub fn new() -> AppResult<Self> {
-        let svn_ctx = get_svn_context()?;
+        Self::with_executor(Rc::new(CommandExecutor))
+    }
+
+    /// 使用指定的执行器初始化 App
+    pub fn with_executor(svn: Rc<dyn SvnExecutor>) -> AppResult<Self> {
+        let config = Config::load()?;
+        let svn_ctx = get_svn_context(svn.clone(), config)?;
         // println!("svn_ctx: {:?}", svn_ctx);
         check_and_repair_workspace(&svn_ctx)?;
 
         Ok(App {
-            ui: AppUI::new(),
+            ui: new_ui(&svn_ctx),
+            svn,
             svn_ctx,
         })
     }
 
+    /// 直接由各部分组装 App，用于测试
+    #[cfg(test)]
+    pub fn from_parts(ui: AppUI, svn: Rc<dyn SvnExecutor>, svn_ctx: SvnContext) -> Self {
+        App { ui, svn, svn_ctx }
+    }
+
+    /// 获取执行 svn 命令的执行器
+    pub fn svn(&self) -> &dyn SvnExecutor {
+        self.svn.as_ref()
+    }
+
     /// 用于初始化一个默认的 App 实例
     pub fn default(repo_name: Option<&str>) -> AppResult<Self> {
-        let svn_ctx = SvnContext::default(repo_name)?;
+        Self::default_with_executor(Rc::new(CommandExecutor), repo_name)
+    }
+
+    /// 使用指定的执行器初始化一个默认的 App 实例
+    pub fn default_with_executor(svn: Rc<dyn SvnExecutor>, repo_name: Option<&str>) -> AppResult<Self> {
+        let config = Config::load()?;
+        let svn_ctx = SvnContext::default(svn.clone(), config, repo_name)?;
 
         Ok(App {
-            ui: AppUI::new(),
+            ui: new_ui(&svn_ctx),
+            svn,
             svn_ctx,
         })
     }
 }
 
+/// 创建 AppUI，并应用配置中的提交信息模板
+fn new_ui(svn_ctx: &SvnContext) -> AppUI {
+    let mut ui = AppUI::new();
+    ui.set_messages(svn_ctx.get_config().messages.clone());
+    ui
+}
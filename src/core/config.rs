@@ -0,0 +1,289 @@
+//! ### 配置文件
+//!
+//! 按以下顺序逐层合并，后面的覆盖前面的：
+//! 1. 系统配置：/etc/ws-tool/config.toml（Windows: %ProgramData%\ws-tool\config.toml）
+//! 2. 用户配置：~/.config/ws-tool/config.toml
+//! 3. 工作区配置：工作目录的上级目录中最近的 .ws-tool.toml
+//! 4. 环境变量：WS_TOOL_*
+//!
+//! 命令行参数（--on-dirty 等）优先于所有配置
+
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+
+use chrono::Local;
+use serde::Deserialize;
+
+use crate::{
+    commands::{utils::validate_folder_name, utils_platform::{get_system_config_path, get_user_config_path, get_working_dir}},
+    core::{error::{AppError, AppResult}, utils_default_repo::is_repo_url},
+    ui::policy::{ConflictPolicy, DirtyPolicy},
+};
+
+/// 工作区配置文件名
+pub const WORKSPACE_CONFIG_FILE: &str = ".ws-tool.toml";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// 仓库所在的目录，None 时为 exe 所在目录
+    pub repo_root: Option<PathBuf>,
+    /// .ws_store 所在的目录，None 时按平台规则查找
+    pub store_root: Option<PathBuf>,
+    /// 不指定 --repo 时使用的仓库，可以是仓库名、[repos] 中登记的名字或 URL
+    pub default_repo: String,
+    /// [repos] 中登记的仓库：名字 -> URL
+    /// - svn://、http(s):// 等远程仓库可以由多人共享
+    pub repos: BTreeMap<String, String>,
+    /// ws log 默认显示的条数
+    pub log_limit: u32,
+    /// 提交时把缺失的文件和新文件识别为改名的相似度（百分比），0 时不识别
+    pub rename_similarity: u8,
+    /// 解决冲突时使用的外部合并工具，{base}、{mine}、{theirs}、{merged} 替换为对应的文件
+    pub merge_tool: Option<String>,
+    pub messages: MessageTemplates,
+    pub prompt: PromptDefaults,
+}
+
+/// 提交信息模板
+/// - {datetime}: 当前时间
+/// - {project}: 项目名
+/// - {branch}: 分支名
+#[derive(Debug, Clone)]
+pub struct MessageTemplates {
+    /// 提交信息为空时使用的自动信息
+    pub auto_commit: String,
+    pub delete_project: String,
+    pub restore_project: String,
+    pub restore_branch: String,
+}
+
+/// 交互的默认策略，对应的命令行参数未给出时使用
+#[derive(Debug, Clone, Default)]
+pub struct PromptDefaults {
+    pub non_interactive: bool,
+    pub on_dirty: Option<DirtyPolicy>,
+    pub conflicts: Option<ConflictPolicy>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            repo_root: None,
+            store_root: None,
+            default_repo: "repo".to_string(),
+            repos: BTreeMap::new(),
+            log_limit: 100,
+            rename_similarity: 50,
+            merge_tool: None,
+            messages: MessageTemplates::default(),
+            prompt: PromptDefaults::default(),
+        }
+    }
+}
+
+impl Default for MessageTemplates {
+    fn default() -> Self {
+        MessageTemplates {
+            auto_commit: "Auto commit {datetime}".to_string(),
+            delete_project: "Delete project {project}".to_string(),
+            restore_project: "Restore project {project}".to_string(),
+            restore_branch: "Restore branch {branch}".to_string(),
+        }
+    }
+}
+
+impl MessageTemplates {
+    /// 生成自动提交信息
+    pub fn auto_commit_message(&self) -> String {
+        render(&self.auto_commit, &[])
+    }
+
+    pub fn delete_project_message(&self, project_name: &str) -> String {
+        render(&self.delete_project, &[("project", project_name)])
+    }
+
+    pub fn restore_project_message(&self, project_name: &str) -> String {
+        render(&self.restore_project, &[("project", project_name)])
+    }
+
+    pub fn restore_branch_message(&self, branch_name: &str) -> String {
+        render(&self.restore_branch, &[("branch", branch_name)])
+    }
+}
+
+/// 替换模板中的 {name} 占位符，{datetime} 总是可用
+fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut message = template.replace("{datetime}", &Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+    for (name, value) in vars {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+/// 一层配置文件的内容，没有写的字段保持上一层的值
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    repo_root: Option<PathBuf>,
+    store_root: Option<PathBuf>,
+    default_repo: Option<String>,
+    repos: BTreeMap<String, String>,
+    log_limit: Option<u32>,
+    rename_similarity: Option<u8>,
+    merge_tool: Option<String>,
+    messages: MessagesFile,
+    prompt: PromptFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MessagesFile {
+    auto_commit: Option<String>,
+    delete_project: Option<String>,
+    restore_project: Option<String>,
+    restore_branch: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PromptFile {
+    non_interactive: Option<bool>,
+    on_dirty: Option<String>,
+    conflicts: Option<String>,
+}
+
+impl Config {
+    /// 依次合并系统、用户、工作区配置和环境变量
+    pub fn load() -> AppResult<Self> {
+        let mut config = Config::default();
+
+        let workspace_config = get_working_dir().ok().and_then(|dir| find_workspace_config(&dir));
+        for path in [get_system_config_path(), get_user_config_path(), workspace_config].into_iter().flatten() {
+            if path.is_file() {
+                config.merge_file(&path)?;
+            }
+        }
+
+        config.merge_env(|key| std::env::var(key).ok())?;
+        Ok(config)
+    }
+
+    /// 合并一个配置文件，相对路径以配置文件所在目录为基准
+    pub fn merge_file(&mut self, path: &Path) -> AppResult<()> {
+        let content = fs::read_to_string(path)?;
+        let file: ConfigFile = toml::from_str(&content)
+            .map_err(|e| AppError::Config(format!("{}: {}", path.display(), e.message())))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        let source = path.display().to_string();
+
+        if let Some(repo_root) = file.repo_root {
+            self.repo_root = Some(base.join(repo_root));
+        }
+        if let Some(store_root) = file.store_root {
+            self.store_root = Some(base.join(store_root));
+        }
+        if let Some(default_repo) = file.default_repo {
+            self.default_repo = default_repo;
+        }
+        for (name, url) in file.repos {
+            if !is_repo_url(&url) {
+                return Err(AppError::Config(format!("{}: invalid repos.{} '{}', expected a svn://, svn+ssh://, http(s):// or file:// URL", source, name, url)));
+            }
+            validate_folder_name(&name, true).map_err(|_| AppError::Config(format!("{}: invalid repository name '{}'", source, name)))?;
+            self.repos.insert(name, url);
+        }
+        if let Some(log_limit) = file.log_limit {
+            self.log_limit = log_limit;
+        }
+        if let Some(rename_similarity) = file.rename_similarity {
+            self.rename_similarity = parse_value(&source, "rename_similarity", &rename_similarity.to_string(), parse_percent)?;
+        }
+        if let Some(merge_tool) = file.merge_tool {
+            self.merge_tool = Some(merge_tool).filter(|tool| !tool.trim().is_empty());
+        }
+
+        let messages = file.messages;
+        for (target, value) in [
+            (&mut self.messages.auto_commit, messages.auto_commit),
+            (&mut self.messages.delete_project, messages.delete_project),
+            (&mut self.messages.restore_project, messages.restore_project),
+            (&mut self.messages.restore_branch, messages.restore_branch),
+        ] {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+
+        if let Some(non_interactive) = file.prompt.non_interactive {
+            self.prompt.non_interactive = non_interactive;
+        }
+        if let Some(on_dirty) = file.prompt.on_dirty {
+            self.prompt.on_dirty = Some(parse_value(&source, "prompt.on_dirty", &on_dirty, DirtyPolicy::parse)?);
+        }
+        if let Some(conflicts) = file.prompt.conflicts {
+            self.prompt.conflicts = Some(parse_value(&source, "prompt.conflicts", &conflicts, ConflictPolicy::parse)?);
+        }
+        Ok(())
+    }
+
+    /// 合并 WS_TOOL_* 环境变量，lookup 返回变量的值
+    pub fn merge_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> AppResult<()> {
+        let get = |key: &str| lookup(key).filter(|v| !v.is_empty());
+
+        if let Some(repo_root) = get("WS_TOOL_REPO_ROOT") {
+            self.repo_root = Some(PathBuf::from(repo_root));
+        }
+        if let Some(store_root) = get("WS_TOOL_STORE_ROOT") {
+            self.store_root = Some(PathBuf::from(store_root));
+        }
+        if let Some(default_repo) = get("WS_TOOL_DEFAULT_REPO") {
+            self.default_repo = default_repo;
+        }
+        if let Some(log_limit) = get("WS_TOOL_LOG_LIMIT") {
+            self.log_limit = parse_value("environment", "WS_TOOL_LOG_LIMIT", &log_limit, |v| v.parse::<u32>().map_err(|_| "expected a positive number".to_string()))?;
+        }
+        if let Some(rename_similarity) = get("WS_TOOL_RENAME_SIMILARITY") {
+            self.rename_similarity = parse_value("environment", "WS_TOOL_RENAME_SIMILARITY", &rename_similarity, parse_percent)?;
+        }
+        if let Some(merge_tool) = get("WS_TOOL_MERGE_TOOL") {
+            self.merge_tool = Some(merge_tool);
+        }
+        if let Some(auto_commit) = get("WS_TOOL_AUTO_COMMIT_MESSAGE") {
+            self.messages.auto_commit = auto_commit;
+        }
+        if let Some(non_interactive) = get("WS_TOOL_NON_INTERACTIVE") {
+            self.prompt.non_interactive = parse_value("environment", "WS_TOOL_NON_INTERACTIVE", &non_interactive, parse_bool)?;
+        }
+        if let Some(on_dirty) = get("WS_TOOL_ON_DIRTY") {
+            self.prompt.on_dirty = Some(parse_value("environment", "WS_TOOL_ON_DIRTY", &on_dirty, DirtyPolicy::parse)?);
+        }
+        if let Some(conflicts) = get("WS_TOOL_CONFLICTS") {
+            self.prompt.conflicts = Some(parse_value("environment", "WS_TOOL_CONFLICTS", &conflicts, ConflictPolicy::parse)?);
+        }
+        Ok(())
+    }
+}
+
+/// 从工作目录的上级目录开始向上查找 .ws-tool.toml
+/// - 工作目录本身就是项目，放在里面的文件会被提交，所以不查找
+pub fn find_workspace_config(working_dir: &Path) -> Option<PathBuf> {
+    working_dir.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(WORKSPACE_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+fn parse_value<T>(source: &str, key: &str, value: &str, parse: impl Fn(&str) -> Result<T, String>) -> AppResult<T> {
+    parse(value).map_err(|e| AppError::Config(format!("{}: invalid {} '{}', {}", source, key, value, e)))
+}
+
+fn parse_percent(value: &str) -> Result<u8, String> {
+    value.parse::<u8>().ok().filter(|v| *v <= 100).ok_or_else(|| "expected a percentage from 0 to 100".to_string())
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
@@ -1,26 +1,32 @@
 //! ### SVN 上下文相关操作
 //! 获取当前工作副本和仓库相关信息
 
-use std::path::{Path, PathBuf};
+use std::{path::{Path, PathBuf}, rc::Rc};
 
-use crate::{commands::utils::is_workspace_dirty, core::utils_default_repo::{get_repo_path, get_repo_url}};
+use crate::{commands::{utils::is_workspace_dirty, utils_platform::{find_a_project_in_ws_store, get_ws_store_root}}, core::utils_default_repo::{RepoLocation, get_repo_name_for_url, get_repo_url, repo_location_from_url, resolve_repo}};
 
-use super::{error::{AppResult, AppError}, svn::{svn_checkout, svn_info}, utils::{Revision, parse_revision_arg}};
+use super::{config::Config, error::{AppResult, AppError}, svn::{svn_checkout, svn_info}, svn_executor::SvnExecutor, utils::{Revision, parse_revision_arg}};
 
 #[derive(Debug)]
 pub struct SvnContext {
+    /// 执行 svn 命令的执行器，与 App 共享
+    svn: Rc<dyn SvnExecutor>,
     /// 当前仓库 URL
     /// {repo_url}
     repo_root_url: String,
     /// 当前项目名称
     /// {project_name}
     current_project_name: String,
-    /// 仓库在本地文件系统中的路径
-    repo_fs_path: PathBuf,
+    /// 仓库名，也是 .ws_store 下的目录名
+    repo_name: String,
+    /// 仓库在本地文件系统中的路径，远程仓库为 None
+    repo_fs_path: Option<PathBuf>,
     /// 当前工作副本的版本
     current_revision: Revision,
     /// 仓库的最新版本
     latest_revision: Revision,
+    /// 启动时加载的配置
+    config: Config,
 }
 
 impl SvnContext {
@@ -53,7 +59,7 @@ impl SvnContext {
     /// - {repo_url}/{current_project_name}/branches/{branch_name}
     pub fn get_current_work_copy_root(&self) -> AppResult<String> {
         // &self.work_copy_root
-        let work_copy_root = svn_info(&["--show-item", "url"])?;
+        let work_copy_root = svn_info(self.svn.as_ref(), &["--show-item", "url"])?;
         Ok(urlencoding::decode(&work_copy_root)?.to_string())
     }
 
@@ -75,6 +81,18 @@ impl SvnContext {
         self.get_project_branches_url(&self.current_project_name)
     }
 
+    /// 获取指定项目的 tags URL
+    /// - {repo_url}/{project_name}/tags
+    pub fn get_project_tags_url(&self, project_name: &str) -> String {
+        format!("{}/{}/tags", self.repo_root_url, project_name)
+    }
+
+    /// 获取当前项目的某标签 URL
+    /// - {repo_url}/{current_project_name}/tags/{tag_name}
+    pub fn get_tag_url(&self, tag_name: &str) -> String {
+        format!("{}/{}/tags/{}", self.repo_root_url, self.current_project_name, tag_name)
+    }
+
     /// 获取当前项目的仓库 URL
     /// - {repo_url}/{current_project_name}
     pub fn get_current_project_repo_root_url(&self) -> String {
@@ -84,11 +102,10 @@ impl SvnContext {
     /// 获取仓库名称
     /// - {repo_name}
     pub fn get_repo_name(&self) -> AppResult<String> {
-        if let Some(name) = self.repo_fs_path.file_name() {
-            Ok(name.to_string_lossy().to_string())
-        } else {
-            Err(AppError::Validation(format!("Cannot determine repository name")))
+        if self.repo_name.is_empty() {
+            return Err(AppError::Validation("Cannot determine repository name".to_string()));
         }
+        Ok(self.repo_name.clone())
     }
     
     /// 获取当前项目名称
@@ -106,15 +123,26 @@ impl SvnContext {
         if rel_url.starts_with("trunk") {
             Ok("trunk".to_string())
         } else if rel_url.starts_with("branches/") {
-            return Ok(rel_url.trim_start_matches("branches/").to_string());
+            Ok(rel_url.trim_start_matches("branches/").to_string())
         } else {
             Ok("unknown".to_string())
         }
     }
 
+    /// 获取当前工作副本所在的标签名，不在 tags/{tag_name} 上时返回 None
+    pub fn get_current_tag_name(&self) -> AppResult<Option<String>> {
+        let work_copy_root = self.get_current_work_copy_root()?;
+        let rel_url = work_copy_root.trim_start_matches(&format!("{}/", self.get_current_project_repo_root_url()));
+        Ok(rel_url.strip_prefix("tags/").map(|name| name.split('/').next().unwrap_or(name).to_string()))
+    }
+
     /// 获取仓库在本地文件系统中的路径
-    pub fn get_repo_fs_path(&self) -> &Path {
-        &self.repo_fs_path
+    /// - 远程仓库无法直接访问，operation 用于错误信息
+    pub fn get_repo_fs_path(&self, operation: &str) -> AppResult<&Path> {
+        self.repo_fs_path.as_deref().ok_or_else(|| AppError::RemoteRepository {
+            operation: operation.to_string(),
+            url: self.repo_root_url.clone(),
+        })
     }
 
     /// 获取当前工作副本的版本
@@ -127,9 +155,24 @@ impl SvnContext {
         &self.latest_revision
     }
 
+    /// 获取配置
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
+    /// 获取当前工作目录对应的 .ws_store 根目录，配置了 store_root 时直接使用
+    pub fn get_ws_store_root(&self) -> AppResult<PathBuf> {
+        get_ws_store_root(self.config.store_root.as_deref())
+    }
+
+    /// 在 .ws_store/{repo_name} 中查找项目，优先查找配置的 store_root
+    pub fn find_project_in_ws_store(&self, project_name: &str) -> AppResult<Option<PathBuf>> {
+        find_a_project_in_ws_store(self.config.store_root.as_deref(), &self.get_repo_name()?, project_name)
+    }
+
     /// 判断工作副本是否有未提交的更改
     pub fn is_dirty(&self) -> AppResult<bool> {
-        is_workspace_dirty(&self.current_project_name)
+        is_workspace_dirty(self.svn.as_ref(), &self.current_project_name)
     }
 
     /// 检查当前工作副本是否处于 Review 模式
@@ -138,59 +181,82 @@ impl SvnContext {
     }
 
     /// 用于初始化一个默认的 SvnContext 实例
-    pub fn default(repo_name: Option<&str>) -> AppResult<Self> {
-        let default_repo_url = get_repo_url(repo_name)?;
-        let default_repo_fs = get_repo_path(repo_name)?;
+    /// - repo_name 可以是仓库名、配置中登记的名字或 URL，见 resolve_repo
+    pub fn default(svn: Rc<dyn SvnExecutor>, config: Config, repo_name: Option<&str>) -> AppResult<Self> {
+        let target = resolve_repo(&config, repo_name)?;
+        let default_repo_url = get_repo_url(svn.as_ref(), &target)?;
 
         Ok(SvnContext {
+            svn,
             repo_root_url: default_repo_url,
             current_project_name: String::new(),
-            repo_fs_path: default_repo_fs,
+            repo_fs_path: target.local_path().map(Path::to_path_buf),
+            repo_name: target.name,
             current_revision: Revision::Number(0),
             latest_revision: Revision::Number(0),
+            config,
         })
     }
 
+    /// 用于初始化一个指向指定仓库、但不在任何项目中的 SvnContext 实例
+    #[cfg(test)]
+    pub fn for_repo(svn: Rc<dyn SvnExecutor>, repo_root_url: &str, repo_fs_path: &Path, config: Config) -> Self {
+        SvnContext {
+            svn,
+            repo_root_url: repo_root_url.to_string(),
+            current_project_name: String::new(),
+            repo_name: repo_fs_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            repo_fs_path: Some(repo_fs_path.to_path_buf()),
+            current_revision: Revision::Number(0),
+            latest_revision: Revision::Number(0),
+            config,
+        }
+    }
 
 }
 
-pub fn get_svn_context() -> AppResult<SvnContext> {
+pub fn get_svn_context(svn: Rc<dyn SvnExecutor>, config: Config) -> AppResult<SvnContext> {
     // let work_copy_root = svn_info(&["--show-item", "url"])?;
     // let work_copy_root_decode = urlencoding::decode(&work_copy_root)?.to_string();
 
-    let repo_root_url = svn_info(&["--show-item", "repos-root-url"])?;
+    let repo_root_url = svn_info(svn.as_ref(), &["--show-item", "repos-root-url"])?;
     let repo_root_url_decode = urlencoding::decode(&repo_root_url)?.to_string();
 
-    let path_part = repo_root_url_decode.trim_start_matches("file://");
-    let path_str = if cfg!(windows) && path_part.starts_with('/') { &path_part[1..] } else { path_part };
-    let repo_fs_path = PathBuf::from(path_str);
+    // file:// 之外的仓库只能通过 URL 访问
+    let (repo_name, repo_fs_path) = match repo_location_from_url(&repo_root_url_decode) {
+        RepoLocation::Local(path) => (path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(), Some(path)),
+        RepoLocation::Remote(url) => (get_repo_name_for_url(&config, &url), None),
+    };
 
-    let rel_url_raw = svn_info(&["--show-item", "relative-url"])?;
+    let rel_url_raw = svn_info(svn.as_ref(), &["--show-item", "relative-url"])?;
     let parts: Vec<&str> = rel_url_raw.trim_start_matches('^').trim_start_matches('/').split('/').collect();
     let project_name_encoded = parts.first().ok_or(AppError::Validation("Could not determine project name from relative URL".to_string()))?;
     let current_project_name = urlencoding::decode(project_name_encoded)?.to_string();
 
-    let current_revision = get_current_revision()?;
-    let latest_revision = get_latest_revision()?;
+    let current_revision = get_current_revision(svn.as_ref())?;
+    let latest_revision = get_latest_revision(svn.as_ref())?;
 
     Ok(SvnContext {
+        svn,
         repo_root_url: repo_root_url_decode,
         current_project_name,
+        repo_name,
         repo_fs_path,
         current_revision,
         latest_revision,
+        config,
     })
 }
 
 pub fn check_and_repair_workspace(ctx: &SvnContext) -> AppResult<()> {
-    let local_uuid = svn_info(&["--show-item", "repos-uuid"])?;
-    let remote_uuid = svn_info(&["--show-item", "repos-uuid", &ctx.repo_root_url])?;
+    let local_uuid = svn_info(ctx.svn.as_ref(), &["--show-item", "repos-uuid"])?;
+    let remote_uuid = svn_info(ctx.svn.as_ref(), &["--show-item", "repos-uuid", &ctx.repo_root_url])?;
 
 
 
     if local_uuid != remote_uuid {
         let current_work_copy_root = ctx.get_current_work_copy_root()?;
-        let target_path = match crate::commands::utils_windows::find_a_project_in_ws_store(&ctx.get_repo_name()?, &ctx.current_project_name)? {
+        let target_path = match ctx.find_project_in_ws_store(&ctx.current_project_name)? {
             Some(p) => p,
             None => {
                 return Err(AppError::Validation(format!("Cannot find project '{}' in any .ws_store/{} folder to repair workspace", ctx.current_project_name, ctx.get_repo_name()?)));
@@ -206,20 +272,20 @@ pub fn check_and_repair_workspace(ctx: &SvnContext) -> AppResult<()> {
         if gitignore_path.exists() {
             std::fs::remove_file(gitignore_path)?;
         }
-        svn_checkout(&[&current_work_copy_root, target_path.to_string_lossy().as_ref(), "--force"])?;
+        svn_checkout(ctx.svn.as_ref(), &[&current_work_copy_root, target_path.to_string_lossy().as_ref(), "--force"])?;
     }
 
     Ok(())
 }
 
-fn get_latest_revision() -> AppResult<Revision> {
-    let rev_str = svn_info(&["-r", "HEAD", "--show-item", "last-changed-revision"])?;
+fn get_latest_revision(svn: &dyn SvnExecutor) -> AppResult<Revision> {
+    let rev_str = svn_info(svn, &["-r", "HEAD", "--show-item", "last-changed-revision"])?;
     let revision = parse_revision_arg(&rev_str)?;
     Ok(revision)
 }
 
-fn get_current_revision() -> AppResult<Revision> {
-    let rev_str = svn_info(&["--show-item", "last-changed-revision"])?;
+fn get_current_revision(svn: &dyn SvnExecutor) -> AppResult<Revision> {
+    let rev_str = svn_info(svn, &["--show-item", "last-changed-revision"])?;
     let revision = parse_revision_arg(&rev_str)?;
     Ok(revision)
 }
@@ -9,16 +9,32 @@ pub enum AppError {
     /// A UTF-8 parsing error occurred.
     Utf8(std::string::FromUtf8Error),
     /// An SVN command executed successfully but returned a non-zero status,
-    /// indicating a logical failure.
+    /// indicating a logical failure. `kind` is classified from the
+    /// `svn: E######` codes in stderr.
     SvnCommandFailed {
         command: String,
+        kind: SvnErrorKind,
         _stdout: String,
-        _stderr: String,
+        stderr: String,
     },
     /// The user cancelled the operation from a UI prompt.
     OperationCancelled,
+    /// A prompt needs an answer, but the command runs in non-interactive mode
+    /// and no policy flag answers it.
+    PromptRequired {
+        prompt: String,
+        hint: String,
+    },
     /// Failed to parse a revision string.
     RevisionParse(String),
+    /// A configuration file or WS_TOOL_* environment variable is invalid.
+    Config(String),
+    /// The operation needs filesystem access to the repository (svnadmin),
+    /// but the repository is only reachable by URL.
+    RemoteRepository {
+        operation: String,
+        url: String,
+    },
     /// A business logic validation error occurred.
     Validation(String),
     /// An XML parsing error occurred.
@@ -37,18 +53,21 @@ impl fmt::Display for AppError {
         match self {
             AppError::Io(err) => write!(f, "I/O Error: {}", err),
             AppError::Utf8(err) => write!(f, "UTF-8 Conversion Error: {}", err),
-            AppError::SvnCommandFailed { command, _stdout, _stderr } => {
-                write!(f, "SVN command failed: {}\n", command)?;
-                // if !stdout.is_empty() {
-                //     write!(f, "--- STDOUT ---\n{}\n", stdout)?;
-                // }
-                // if !stderr.is_empty() {
-                //     write!(f, "--- STDERR ---\n{}\n", stderr)?;
-                // }
+            AppError::SvnCommandFailed { command, kind, stderr, .. } => {
+                write!(f, "SVN command failed: {}", command)?;
+                if let Some(hint) = kind.hint() {
+                    write!(f, "\n{}", hint)?;
+                }
+                for line in stderr.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    write!(f, "\n  {}", line)?;
+                }
                 Ok(())
             }
             AppError::OperationCancelled => write!(f, "Operation cancelled"),
+            AppError::PromptRequired { prompt, hint } => write!(f, "Prompt \"{}\" needs an answer in non-interactive mode, {}", prompt, hint),
             AppError::RevisionParse(rev) => write!(f, "Failed to parse revision: {}", rev),
+            AppError::Config(msg) => write!(f, "Config Error: {}", msg),
+            AppError::RemoteRepository { operation, url } => write!(f, "{} needs filesystem access to the repository, but {} is a remote repository. Run it on the server that hosts the repository", operation, url),
             AppError::Validation(msg) => write!(f, "Error: {}", msg),
             AppError::XmlParse(err) => write!(f, "XML Parsing Error: {}", err),
             AppError::UrlDecode(err) => write!(f, "URL/Path Decoding Error: {}", err),
@@ -58,6 +77,101 @@ impl fmt::Display for AppError {
     }
 }
 
+impl AppError {
+    /// 进程退出码，脚本可以依赖这些值
+    /// - 1: 其他错误
+    /// - 3: 操作被取消
+    /// - 4: 非交互模式下缺少策略
+    /// - 10 ~ 17: SVN 命令失败，见 SvnErrorKind::exit_code
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::OperationCancelled => 3,
+            AppError::PromptRequired { .. } => 4,
+            AppError::SvnCommandFailed { kind, .. } => kind.exit_code(),
+            _ => 1,
+        }
+    }
+}
+
+/// 由 svn 的错误码（svn: E###### 或 svn: warning: W######）分类的失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvnErrorKind {
+    /// 工作副本或提交的内容不是最新的
+    OutOfDate,
+    /// 工作副本被锁定，或者上一次操作被中断
+    WorkingCopyLocked,
+    /// 认证失败或没有权限
+    AuthFailed,
+    /// 路径或 URL 不存在
+    PathNotFound,
+    /// 不是工作副本
+    NotWorkingCopy,
+    /// 存在未解决的冲突（包括树冲突）
+    Conflict,
+    /// 工作副本不能合并：有本地修改、混合版本或切换过的子目录
+    MergeNotReady,
+    /// 其他错误
+    Other,
+}
+
+impl SvnErrorKind {
+    /// 从 stderr 中解析错误码，返回第一个能识别的分类
+    pub fn from_stderr(stderr: &str) -> Self {
+        stderr.lines()
+            .filter_map(|line| {
+                let rest = line.trim().strip_prefix("svn: ")?;
+                let rest = rest.strip_prefix("warning: ").unwrap_or(rest);
+                let code = rest.get(..7)?;
+                let valid = (code.starts_with('E') || code.starts_with('W')) && code[1..].bytes().all(|b| b.is_ascii_digit());
+                valid.then(|| Self::from_code(&code[1..]))
+            })
+            .find(|kind| *kind != SvnErrorKind::Other)
+            .unwrap_or(SvnErrorKind::Other)
+    }
+
+    /// 错误码（不含 E/W 前缀）对应的分类
+    fn from_code(code: &str) -> Self {
+        match code {
+            "155011" | "160024" | "160028" | "170004" | "160042" => SvnErrorKind::OutOfDate,
+            "155004" | "155037" | "200033" | "200034" => SvnErrorKind::WorkingCopyLocked,
+            "170001" | "215004" | "175013" | "230001" | "160039" => SvnErrorKind::AuthFailed,
+            "170000" | "160013" | "155010" | "195012" => SvnErrorKind::PathNotFound,
+            "155007" => SvnErrorKind::NotWorkingCopy,
+            "155015" | "155035" => SvnErrorKind::Conflict,
+            "195016" => SvnErrorKind::MergeNotReady,
+            _ => SvnErrorKind::Other,
+        }
+    }
+
+    /// 给用户的提示
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            SvnErrorKind::OutOfDate => Some("The working copy is out of date. Run 'ws pull' and try again."),
+            SvnErrorKind::WorkingCopyLocked => Some("The working copy is locked, another operation may be running or a previous one was interrupted. Run 'svn cleanup' and try again."),
+            SvnErrorKind::AuthFailed => Some("Authentication failed. Check your credentials and permissions for the repository."),
+            SvnErrorKind::PathNotFound => Some("The path or URL does not exist in the repository."),
+            SvnErrorKind::NotWorkingCopy => Some("The current directory is not an SVN working copy."),
+            SvnErrorKind::Conflict => Some("There are unresolved conflicts (including tree conflicts). Resolve them and try again."),
+            SvnErrorKind::MergeNotReady => Some("The working copy is not ready to merge: it has local changes, mixed revisions or switched subtrees. Commit or update it and try again."),
+            SvnErrorKind::Other => None,
+        }
+    }
+
+    /// 进程退出码
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SvnErrorKind::OutOfDate => 10,
+            SvnErrorKind::WorkingCopyLocked => 11,
+            SvnErrorKind::AuthFailed => 12,
+            SvnErrorKind::PathNotFound => 13,
+            SvnErrorKind::NotWorkingCopy => 14,
+            SvnErrorKind::Conflict => 15,
+            SvnErrorKind::Other => 16,
+            SvnErrorKind::MergeNotReady => 17,
+        }
+    }
+}
+
 impl From<std::io::Error> for AppError {
     fn from(err: std::io::Error) -> Self {
         AppError::Io(err)
@@ -1,8 +1,10 @@
 
 pub mod app;
+pub mod config;
 pub mod context;
 pub mod error;
 pub mod svn;
+pub mod svn_executor;
 pub mod svn_repo;
 pub mod utils;
 pub mod utils_default_repo;
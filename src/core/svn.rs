@@ -2,31 +2,18 @@
 //! 
 //! 单个函数应该只执行一个操作，返回相应的结果
 
-use std::process::{Command, Output};
+use crate::core::{error::AppResult, svn_executor::SvnExecutor, utils::auto_decode};
 
-use crate::core::{utils::auto_decode, error::{AppError, AppResult}};
-
-/// Helper function to execute a command and handle errors.
-fn execute_command(mut command: Command) -> AppResult<Output> {
-    let output = command.output()?;
-
-    if !output.status.success() {
-        return Err(AppError::SvnCommandFailed {
-            command: format!("{:?}", command),
-            _stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            _stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        });
-    }
-
-    Ok(output)
+/// Helper function to execute an svn command and handle errors.
+fn execute_command(svn: &dyn SvnExecutor, args: &[&str]) -> AppResult<Vec<u8>> {
+    svn.execute("svn", args)
 }
 
 /// ### svn cleanup
 /// 清理工作副本，.svn 目录
-pub fn svn_cleanup() -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.args(&["cleanup", "."]);
-    execute_command(command)?;
+pub fn svn_cleanup(svn: &dyn SvnExecutor) -> AppResult<()> {
+    let command = ["cleanup", "."];
+    execute_command(svn, &command)?;
     Ok(())
 }
 
@@ -35,23 +22,21 @@ pub fn svn_cleanup() -> AppResult<()> {
 /// 因为我们有了 .gitignore，所以是否理论上应该不需要这个功能了?
 /// 如果工作区出现了未版本控制的文件，但程序却没有报 dirty
 /// 是否可以认为是被 .gitignore 忽略了，但没有在 svn:ignore 中设置?
-pub fn svn_cleanup_workspace() -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.args(&["cleanup", "."]);
-    execute_command(command)?;
+pub fn svn_cleanup_workspace(svn: &dyn SvnExecutor) -> AppResult<()> {
+    let command = ["cleanup", "."];
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn update
-pub fn svn_update(update_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("update").args(update_args);
-    execute_command(command)?;
+pub fn svn_update(svn: &dyn SvnExecutor, update_args: &[&str]) -> AppResult<()> {
+    let command = [&["update"], update_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
-/// ### svn update .gitignore
-/// 更新 .gitignore 文件
+// ### svn update .gitignore
+// 更新 .gitignore 文件
 // pub fn svn_update_gitignore() -> AppResult<()> {
 //     let mut command = Command::new("svn");
 //     command.args(&["update", ".gitignore", "--accept", "working"]);
@@ -67,195 +52,219 @@ pub enum StatusType {
 
 /// ### svn status
 /// 返回解码后的 svn status 信息
-pub fn svn_status(status_type: StatusType) -> AppResult<String> {
+pub fn svn_status(svn: &dyn SvnExecutor, status_type: StatusType) -> AppResult<String> {
     let args = match status_type {
         StatusType::CheckIgnore => vec!["status", "--xml", "--no-ignore"],
         StatusType::Commit => vec!["status", "--xml"],
         StatusType::CheckGitignore => vec!["status", "--xml", ".gitignore"],
     };
     
-    let mut command = Command::new("svn");
-    command.args(&args);
-
-    let output = execute_command(command)?;
-    let decoded_output = auto_decode(&output.stdout)?;
+    let output = execute_command(svn, &args)?;
+    let decoded_output = auto_decode(&output)?;
 
     Ok(decoded_output)
 }
 
 /// ### svn log
 /// 返回解码后的 svn log 信息，xml 格式
-pub fn svn_log(log_args: &[&str]) -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.arg("log").args(log_args);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn svn_log(svn: &dyn SvnExecutor, log_args: &[&str]) -> AppResult<String> {
+    let command = [&["log"], log_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn info
 /// 返回解码后的 svn info 信息
-pub fn svn_info(info_args: &[&str]) -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.arg("info").args(info_args);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn svn_info(svn: &dyn SvnExecutor, info_args: &[&str]) -> AppResult<String> {
+    let command = [&["info"], info_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn list
 /// 返回解码后的 svn list 信息
-pub fn svn_list(list_args: &[&str]) -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.arg("list").args(list_args);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn svn_list(svn: &dyn SvnExecutor, list_args: &[&str]) -> AppResult<String> {
+    let command = [&["list"], list_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
+}
+
+/// ### svn diff
+/// 返回解码后的 unified diff，总是使用内置的 diff 以便解析
+pub fn svn_diff(svn: &dyn SvnExecutor, diff_args: &[&str]) -> AppResult<String> {
+    let command = [&["diff", "--internal-diff"], diff_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
+}
+
+/// ### svn mergeinfo
+/// 返回解码后的 svn mergeinfo 信息
+pub fn svn_mergeinfo(svn: &dyn SvnExecutor, mergeinfo_args: &[&str]) -> AppResult<String> {
+    let command = [&["mergeinfo"], mergeinfo_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
+}
+
+/// ### svn cat
+/// 返回文件内容的原始字节
+pub fn svn_cat(svn: &dyn SvnExecutor, cat_args: &[&str]) -> AppResult<Vec<u8>> {
+    let command = [&["cat"], cat_args].concat();
+    execute_command(svn, &command)
 }
 
 /// ### svn add
 /// 添加新文件到版本控制
-pub fn svn_add(add_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("add").args(&["--parents", "--depth", "empty", "--force"]).args(add_args);
-    execute_command(command)?;
+pub fn svn_add(svn: &dyn SvnExecutor, add_args: &[&str]) -> AppResult<()> {
+    let command = [&["add", "--parents", "--depth", "empty", "--force"], add_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn commit
 /// 提交当前工作副本的更改
-pub fn svn_commit(commit_info: &str) -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.args(&["commit", "-m", commit_info]);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn svn_commit(svn: &dyn SvnExecutor, commit_info: &str) -> AppResult<String> {
+    let command = ["commit", "-m", commit_info];
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
+}
+
+/// ### svn commit for paths
+/// 只提交指定的路径，不递归提交目录下的其他更改
+pub fn svn_commit_paths(svn: &dyn SvnExecutor, commit_info: &str, paths: &[&str]) -> AppResult<String> {
+    let command = [&["commit", "--depth", "empty", "-m", commit_info], paths].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn commit for .gitignore
 /// 提交 .gitignore 文件的更改
-pub fn svn_commit_gitignore() -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.args(&["commit", ".gitignore", "-m", "Auto update .gitignore"]);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn svn_commit_gitignore(svn: &dyn SvnExecutor) -> AppResult<String> {
+    let command = ["commit", ".gitignore", "-m", "Auto update .gitignore"];
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn commit for svn externals .gitignore
 /// 提交 .gitignore externals 设置的更改
-pub fn svn_commit_externals(path: &str, is_new: bool) -> AppResult<String> {
-    let mut command = Command::new("svn");
+pub fn svn_commit_externals(svn: &dyn SvnExecutor, path: &str, is_new: bool) -> AppResult<String> {
     let message = if is_new {
         "[WS-INIT-GITIGNORE] Set svn:externals for .gitignore"
     } else {
         "[WS-RESOLV-GITIGNORE] Update svn:externals for .gitignore"
     };
-    command.args(&["commit", path, "-m", message]);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+    let command = ["commit", path, "-m", message];
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn delete
 /// 删除指定文件或目录
-pub fn svn_delete(delete_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("delete").args(delete_args);
-    execute_command(command)?;
+pub fn svn_delete(svn: &dyn SvnExecutor, delete_args: &[&str]) -> AppResult<()> {
+    let command = [&["delete"], delete_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn switch
 /// 切换当前工作副本到指定的 URL
-pub fn svn_switch(target_url: &str) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.args(&["switch", &target_url, ".", "--ignore-ancestry"]);
-    execute_command(command)?;
+pub fn svn_switch(svn: &dyn SvnExecutor, target_url: &str) -> AppResult<()> {
+    let command = ["switch", target_url, ".", "--ignore-ancestry"];
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn merge
 /// 合并指定 URL 的更改到当前工作副本
-pub fn svn_merge(merge_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("merge").args(merge_args);
-    execute_command(command)?;
+pub fn svn_merge(svn: &dyn SvnExecutor, merge_args: &[&str]) -> AppResult<()> {
+    let command = [&["merge"], merge_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn revert
 /// 恢复当前工作副本的更改
-pub fn svn_revert(revert_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("revert").args(revert_args);
-    execute_command(command)?;
+pub fn svn_revert(svn: &dyn SvnExecutor, revert_args: &[&str]) -> AppResult<()> {
+    let command = [&["revert"], revert_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn mkdir
 /// 在仓库中创建新目录
-pub fn svn_mkdir(mkdir_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("mkdir").args(mkdir_args);
-    execute_command(command)?;
+pub fn svn_mkdir(svn: &dyn SvnExecutor, mkdir_args: &[&str]) -> AppResult<()> {
+    let command = [&["mkdir"], mkdir_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn copy
 /// 在仓库中复制文件或目录
-pub fn svn_copy(copy_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("copy").args(copy_args);
-    execute_command(command)?;
+pub fn svn_copy(svn: &dyn SvnExecutor, copy_args: &[&str]) -> AppResult<()> {
+    let command = [&["copy"], copy_args].concat();
+    execute_command(svn, &command)?;
+    Ok(())
+}
+
+/// ### svn move
+/// 移动或改名，保留历史
+pub fn svn_move(svn: &dyn SvnExecutor, move_args: &[&str]) -> AppResult<()> {
+    let command = [&["move"], move_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn checkout
 /// 检出 SVN 仓库到当前目录
-pub fn svn_checkout(checkout_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("checkout").args(checkout_args);
-    execute_command(command)?;
+pub fn svn_checkout(svn: &dyn SvnExecutor, checkout_args: &[&str]) -> AppResult<()> {
+    let command = [&["checkout"], checkout_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn resolve
 /// 解决冲突
-pub fn svn_resolve(resolve_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("resolve").args(resolve_args);
-    execute_command(command)?;
+pub fn svn_resolve(svn: &dyn SvnExecutor, resolve_args: &[&str]) -> AppResult<()> {
+    let command = [&["resolve"], resolve_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn propget
 /// 获取属性值
-pub fn _svn_propget(prop_args: &[&str]) -> AppResult<String> {
-    let mut command = Command::new("svn");
-    command.arg("propget").args(prop_args);
-    let output = execute_command(command)?;
-    Ok(auto_decode(&output.stdout)?)
+pub fn _svn_propget(svn: &dyn SvnExecutor, prop_args: &[&str]) -> AppResult<String> {
+    let command = [&["propget"], prop_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
+}
+
+/// ### svn proplist
+/// 返回解码后的属性列表
+pub fn svn_proplist(svn: &dyn SvnExecutor, proplist_args: &[&str]) -> AppResult<String> {
+    let command = [&["proplist"], proplist_args].concat();
+    let output = execute_command(svn, &command)?;
+    auto_decode(&output)
 }
 
 /// ### svn propset
 /// 设置属性值
-pub fn svn_propset(propset_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("propset").args(propset_args);
-    execute_command(command)?;
+pub fn svn_propset(svn: &dyn SvnExecutor, propset_args: &[&str]) -> AppResult<()> {
+    let command = [&["propset"], propset_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 /// ### svn propdel
 /// 删除属性
-pub fn svn_propdel(propdel_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svn");
-    command.arg("propdel").args(propdel_args);
-    execute_command(command)?;
+pub fn svn_propdel(svn: &dyn SvnExecutor, propdel_args: &[&str]) -> AppResult<()> {
+    let command = [&["propdel"], propdel_args].concat();
+    execute_command(svn, &command)?;
     Ok(())
 }
 
 
 /// ### svn svnmucc
 /// svnmucc 操作
-pub fn svn_svnmucc(svnmucc_args: &[&str]) -> AppResult<()> {
-    let mut command = Command::new("svnmucc");
-    command.args(svnmucc_args);
-    execute_command(command)?;
+pub fn svn_svnmucc(svn: &dyn SvnExecutor, svnmucc_args: &[&str]) -> AppResult<()> {
+    svn.execute("svnmucc", svnmucc_args)?;
     Ok(())
 }
\ No newline at end of file
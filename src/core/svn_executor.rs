@@ -0,0 +1,95 @@
+//! ### SVN 命令执行器
+//!
+//! svn、svnadmin、svnmucc、svndumpfilter 都通过 SvnExecutor 执行，
+//! 默认实现直接启动进程，也可以替换为返回预设输出的实现
+
+use std::process::{Child, Command, Stdio};
+
+use crate::core::error::{AppError, AppResult, SvnErrorKind};
+
+pub trait SvnExecutor: std::fmt::Debug {
+    /// 执行命令并等待结束，返回 stdout
+    /// - 非零退出码返回 AppError::SvnCommandFailed
+    fn execute(&self, program: &str, args: &[&str]) -> AppResult<Vec<u8>>;
+
+    /// 把多个命令用管道连接起来执行并等待全部结束，如 svnadmin dump | svndumpfilter | svnadmin load
+    /// - 最后一个命令的 stdout 被丢弃，stderr 直接输出到终端
+    /// - 任一命令失败返回 AppError::SvnCommandFailed
+    fn execute_piped(&self, stages: &[(&str, &[&str])]) -> AppResult<()>;
+}
+
+/// 直接启动进程的执行器
+#[derive(Debug, Default)]
+pub struct CommandExecutor;
+
+impl SvnExecutor for CommandExecutor {
+    fn execute(&self, program: &str, args: &[&str]) -> AppResult<Vec<u8>> {
+        let output = Command::new(program).args(args).output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(AppError::SvnCommandFailed {
+                command: format_command(program, args),
+                kind: SvnErrorKind::from_stderr(&stderr),
+                _stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr,
+            });
+        }
+
+        Ok(output.stdout)
+    }
+
+    fn execute_piped(&self, stages: &[(&str, &[&str])]) -> AppResult<()> {
+        let mut children: Vec<(String, Child)> = Vec::new();
+        let mut input = Stdio::null();
+        for (index, (program, args)) in stages.iter().enumerate() {
+            let stdout = if index + 1 == stages.len() { Stdio::null() } else { Stdio::piped() };
+            let spawned = Command::new(program).args(*args).stdin(input).stdout(stdout).stderr(Stdio::inherit()).spawn();
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(e) => {
+                    // 已经启动的命令不能留在后台
+                    for (_, mut child) in children {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    return Err(e.into());
+                }
+            };
+            input = child.stdout.take().map_or_else(Stdio::null, Stdio::from);
+            children.push((format_command(program, args), child));
+        }
+
+        // 前面的命令可能因为后面的命令退出而失败，所以列出所有失败的命令
+        let mut failed = Vec::new();
+        for (command, mut child) in children {
+            if !child.wait()?.success() {
+                failed.push(command);
+            }
+        }
+        if !failed.is_empty() {
+            return Err(AppError::SvnCommandFailed {
+                command: failed.join(" | "),
+                kind: SvnErrorKind::Other,
+                _stdout: String::new(),
+                stderr: String::new(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// 格式化命令，用于错误信息
+pub fn format_command(program: &str, args: &[&str]) -> String {
+    let mut command = program.to_string();
+    for arg in args {
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            command.push_str(&format!(" \"{}\"", arg));
+        } else {
+            command.push(' ');
+            command.push_str(arg);
+        }
+    }
+    command
+}
@@ -1,70 +1,35 @@
 //! ### 执行 SVN 仓库相关操作
 //! 使用 svnadmin, svndumpfilter 等工具操作 SVN 仓库
 
-use std::process::{Child, Command, Stdio};
-use crate::core::error::{AppResult, AppError};
-
-/// ### svnadmin create
-/// 创建一个新的 SVN 仓库
-pub fn svnadmin_create(repo_path: &str) -> AppResult<()> {
-    let output = Command::new("svnadmin")
-        .args(&["create", repo_path])
-        .output()?; // Handles command execution errors
-
-    if !output.status.success() {
-        return Err(AppError::SvnCommandFailed {
-            command: format!("svnadmin create {}", repo_path),
-            _stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            _stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+use crate::core::{error::{AppError, AppResult}, svn_executor::SvnExecutor, utils_default_repo::is_repo_url};
+
+/// svnadmin 只能操作本地文件系统中的仓库，传入 URL 时不启动进程，直接报错
+fn ensure_local_repo(subcommand: &str, repo_path: &str) -> AppResult<()> {
+    if is_repo_url(repo_path) {
+        return Err(AppError::RemoteRepository {
+            operation: format!("svnadmin {}", subcommand),
+            url: repo_path.to_string(),
         });
     }
-
     Ok(())
 }
 
-/// ### svnadmin dump
-/// 导出 SVN 仓库的 dump 文件
-pub fn svnadmin_dump(dump_args: &[&str]) -> AppResult<Child> {
-    let child = Command::new("svnadmin")
-        .arg("dump")
-        .args(dump_args)
-        .stdout(Stdio::piped())
-        .spawn()?; // Automatically converts io::Error to AppError::Io
-
-    Ok(child)
-}
-
-/// ### svnadmin load
-/// 导入 SVN 仓库的 dump 文件
-pub fn svnadmin_load(load_args: &[&str], input: Stdio) -> AppResult<Child> {
-    let child = Command::new("svnadmin")
-        .arg("load")
-        .args(load_args)
-        .stdin(input)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    Ok(child)
+/// ### svnadmin create
+/// 创建一个新的 SVN 仓库
+pub fn svnadmin_create(svn: &dyn SvnExecutor, repo_path: &str) -> AppResult<()> {
+    ensure_local_repo("create", repo_path)?;
+    svn.execute("svnadmin", &["create", repo_path])?;
+    Ok(())
 }
 
-
-/// ### svndumpfilter
-/// 使用 svndumpfilter
-pub fn svndumpfilter(filter_args: &[&str], input: Stdio) -> AppResult<Child> {
-    let child = Command::new("svndumpfilter")
-        .args(filter_args)
-        .stdin(input)
-        .stdout(Stdio::piped())
-        .spawn()?;
-
-    Ok(child)
+/// ### svnadmin dump | svndumpfilter | svnadmin load
+/// 导出仓库，经 svndumpfilter 过滤后导入另一个仓库
+pub fn svnadmin_filter_into(svn: &dyn SvnExecutor, repo_path: &str, filter_args: &[&str], target_path: &str) -> AppResult<()> {
+    ensure_local_repo("dump", repo_path)?;
+    ensure_local_repo("load", target_path)?;
+    svn.execute_piped(&[
+        ("svnadmin", &["dump", repo_path, "--quiet"]),
+        ("svndumpfilter", filter_args),
+        ("svnadmin", &["load", target_path, "--quiet", "--ignore-uuid"]),
+    ])
 }
-
-
-
-
-
-
-
-
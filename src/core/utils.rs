@@ -3,6 +3,7 @@
 
 use std::{fmt::Display, io};
 
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone};
 use crossterm::execute;
 
 use super::error::{AppResult, AppError};
@@ -30,7 +31,7 @@ pub fn parse_revision_arg(input: &str) -> AppResult<Revision> {
         return Ok(Revision::Head);
     }
 
-    let target_rev: u64 = match s.trim_start_matches(|c| c == 'r' || c == 'R').parse() {
+    let target_rev: u64 = match s.trim_start_matches(['r', 'R']).parse() {
         Ok(r) => r,
         Err(_) => {
             return Err(AppError::RevisionParse(input.to_string()));
@@ -40,6 +41,146 @@ pub fn parse_revision_arg(input: &str) -> AppResult<Revision> {
     Ok(Revision::Number(target_rev))
 }
 
+/// 版本表达式：`[分支@]基准[~N]`
+/// - 基准：HEAD、PREV、100 / r100、{2025-03-01} / {2025-03-01 12:00}
+/// - ~N 沿分支自己的提交历史向前回退 N 次提交，~ 等同于 ~1
+/// - 不写分支时为当前分支
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionExpr {
+    pub branch: Option<String>,
+    pub base: RevisionBase,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevisionBase {
+    /// 分支的最新提交
+    Head,
+    Number(u64),
+    /// 工作副本当前提交的上一次提交，只能用于当前分支
+    Prev,
+    /// 该时刻生效的提交，格式与 svn 的 {DATE} 相同
+    Date(String),
+}
+
+impl Display for RevisionExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(branch) = &self.branch {
+            write!(f, "{}@", branch)?;
+        }
+        match &self.base {
+            RevisionBase::Head => write!(f, "HEAD")?,
+            RevisionBase::Number(n) => write!(f, "r{}", n)?,
+            RevisionBase::Prev => write!(f, "PREV")?,
+            RevisionBase::Date(date) => write!(f, "{{{}}}", date)?,
+        }
+        if self.offset > 0 {
+            write!(f, "~{}", self.offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析版本表达式，见 RevisionExpr
+pub fn parse_revision_expr(input: &str) -> AppResult<RevisionExpr> {
+    let err = || AppError::RevisionParse(input.to_string());
+    let s = input.trim();
+
+    // 日期中不会出现 @，分支名中也不允许出现
+    let (branch, rest) = match s.split_once('@') {
+        Some((branch, rest)) if !branch.trim().is_empty() => (Some(branch.trim().to_string()), rest.trim()),
+        Some(_) => return Err(err()),
+        None => (None, s),
+    };
+
+    let (base_str, offsets) = match rest.find('~') {
+        Some(pos) => rest.split_at(pos),
+        None => (rest, ""),
+    };
+
+    let mut offset: u64 = 0;
+    for step in offsets.split('~').skip(1) {
+        let n = if step.is_empty() { 1 } else { step.parse::<u64>().map_err(|_| err())? };
+        offset = offset.checked_add(n).ok_or_else(err)?;
+    }
+
+    let base_str = base_str.trim();
+    let base = if base_str.eq_ignore_ascii_case("HEAD") {
+        RevisionBase::Head
+    } else if base_str.eq_ignore_ascii_case("PREV") {
+        RevisionBase::Prev
+    } else if let Some(date) = base_str.strip_prefix('{').and_then(|d| d.strip_suffix('}')) {
+        RevisionBase::Date(parse_revision_date(date.trim()).ok_or_else(err)?)
+    } else {
+        match parse_revision_arg(base_str)? {
+            Revision::Number(n) => RevisionBase::Number(n),
+            Revision::Head => RevisionBase::Head,
+        }
+    };
+
+    Ok(RevisionExpr { branch, base, offset })
+}
+
+/// 解析 A 或 A:B，日期中的 : 不作为分隔符
+pub fn parse_revision_range(input: &str) -> AppResult<(RevisionExpr, Option<RevisionExpr>)> {
+    let mut depth = 0;
+    let separator = input.char_indices().find(|(_, c)| {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ':' if depth == 0 => return true,
+            _ => {},
+        }
+        false
+    });
+
+    match separator {
+        Some((pos, _)) => Ok((parse_revision_expr(&input[..pos])?, Some(parse_revision_expr(&input[pos + 1..])?))),
+        None => Ok((parse_revision_expr(input)?, None)),
+    }
+}
+
+/// 检查日期格式，返回交给 svn 的日期
+/// - 2025-03-01、2025-03-01 12:00、2025-03-01T12:00:00
+fn parse_revision_date(date: &str) -> Option<String> {
+    parse_naive_datetime(date).map(|_| date.replacen(' ', "T", 1))
+}
+
+/// 解析本地时间，只有日期时为当天 00:00
+fn parse_naive_datetime(date: &str) -> Option<NaiveDateTime> {
+    let normalized = date.replacen(' ', "T", 1);
+    if let Ok(day) = NaiveDate::parse_from_str(&normalized, "%Y-%m-%d") {
+        return day.and_hms_opt(0, 0, 0);
+    }
+    ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"].iter().find_map(|fmt| NaiveDateTime::parse_from_str(&normalized, fmt).ok())
+}
+
+/// 解析 --since、--until 等日期参数
+/// - 相对时间：12h、3d、2w、1m、1y（一个月按 30 天，一年按 365 天）
+/// - 日期：与版本表达式中的日期相同，可以省略 {}
+pub fn parse_date_arg(input: &str, now: DateTime<Local>) -> AppResult<DateTime<Local>> {
+    let err = || AppError::Validation(format!("Invalid date '{}', expected a duration like 2w or 3d, or a date like 2025-03-01", input));
+    let s = input.trim();
+    let s = s.strip_prefix('{').and_then(|d| d.strip_suffix('}')).unwrap_or(s).trim();
+
+    if let Some(unit) = s.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        let amount: u32 = s[..s.len() - 1].parse().map_err(|_| err())?;
+        let hours = match unit.to_ascii_lowercase() {
+            'h' => 1,
+            'd' => 24,
+            'w' => 24 * 7,
+            'm' => 24 * 30,
+            'y' => 24 * 365,
+            _ => return Err(err()),
+        };
+        let duration = TimeDelta::try_hours(i64::from(amount) * hours).ok_or_else(err)?;
+        return now.checked_sub_signed(duration).ok_or_else(err);
+    }
+
+    let naive = parse_naive_datetime(s).ok_or_else(err)?;
+    Local.from_local_datetime(&naive).earliest().ok_or_else(err)
+}
+
 pub fn auto_decode(input: &[u8]) -> AppResult<String> {
     if let Ok(s) = String::from_utf8(input.to_vec()) {
         return Ok(s.trim().to_string());
@@ -1,35 +1,132 @@
-use std::env;
-
-use crate::{commands::utils::validate_folder_name, core::{error::{AppError, AppResult}, svn_repo::svnadmin_create}};
-
-
-/// 获取默认仓库路径 (exe 所在目录下的 "repo" 文件夹)
-/// - C:\...
-pub fn get_repo_path(repo_name: Option<&str>) -> AppResult<std::path::PathBuf> {
-    // 获取当前可执行文件的路径
-    let exe_path = env::current_exe()?;
-    // 获取父目录 (ws.exe 所在的文件夹)
-    let exe_dir = exe_path.parent().ok_or(
-        AppError::Validation(format!("Cannot find executable directory"))
-    )?;
-    
-    if let Some(repo) = repo_name {
-        validate_folder_name(repo, true)?;
-        return Ok(exe_dir.join(repo));
+use std::{env, path::{Path, PathBuf}};
+
+use crate::{commands::utils::{check_url_exists, validate_folder_name}, core::{config::Config, error::{AppError, AppResult}, svn_executor::SvnExecutor, svn_repo::svnadmin_create}};
+
+/// 仓库的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoLocation {
+    /// 本地文件系统中的仓库，可以使用 svnadmin 直接操作
+    Local(PathBuf),
+    /// 通过 svn://、svn+ssh://、http(s):// 访问的远程仓库
+    Remote(String),
+}
+
+/// --repo 参数解析后的仓库
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoTarget {
+    /// 仓库名，也是 .ws_store 下的目录名
+    pub name: String,
+    pub location: RepoLocation,
+}
+
+impl RepoTarget {
+    /// 本地仓库的路径，远程仓库返回 None
+    pub fn local_path(&self) -> Option<&Path> {
+        match &self.location {
+            RepoLocation::Local(path) => Some(path),
+            RepoLocation::Remote(_) => None,
+        }
+    }
+}
+
+/// 是否为仓库 URL（包括 file://）
+pub fn is_repo_url(value: &str) -> bool {
+    ["file://", "svn://", "svn+ssh://", "http://", "https://"].iter().any(|scheme| value.starts_with(scheme))
+}
+
+/// 由仓库根目录 URL 得到仓库位置
+/// - file:// 为本地仓库，其余为远程仓库
+pub fn repo_location_from_url(url: &str) -> RepoLocation {
+    match url.strip_prefix("file://") {
+        Some(path_part) => {
+            let path_str = if cfg!(windows) && path_part.starts_with('/') { &path_part[1..] } else { path_part };
+            RepoLocation::Local(PathBuf::from(path_str))
+        }
+        None => RepoLocation::Remote(url.trim_end_matches('/').to_string()),
+    }
+}
+
+/// 远程仓库的名称
+/// - 在配置 [repos] 中登记过时使用登记的名字
+/// - 否则为 URL 的最后一段，svnserve -r 直接指向仓库时为主机名
+pub fn get_repo_name_for_url(config: &Config, url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if let Some((name, _)) = config.repos.iter().find(|(_, registered)| registered.trim_end_matches('/') == url) {
+        return name.clone();
+    }
+
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let name = match path.rsplit('/').find(|segment| !segment.is_empty()) {
+        Some(segment) => segment,
+        // 去掉主机名中的用户名和端口
+        None => host.rsplit('@').next().unwrap_or(host).split(':').next().unwrap_or(host),
+    };
+    urlencoding::decode(name).map(|s| s.to_string()).unwrap_or_else(|_| name.to_string())
+}
+
+/// 解析 --repo 参数，不指定时使用配置的 default_repo（默认为 "repo"）
+/// - URL：直接使用
+/// - 配置 [repos] 中登记的名字：使用登记的 URL
+/// - 其他：本地仓库，见 get_repo_path
+pub fn resolve_repo(config: &Config, repo_name: Option<&str>) -> AppResult<RepoTarget> {
+    let repo = repo_name.unwrap_or(&config.default_repo);
+
+    if is_repo_url(repo) {
+        let name = match repo_location_from_url(repo) {
+            RepoLocation::Local(path) => path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            RepoLocation::Remote(url) => get_repo_name_for_url(config, &url),
+        };
+        validate_folder_name(&name, true)?;
+        return Ok(RepoTarget { name, location: repo_location_from_url(repo) });
     }
-    else {
-        return Ok(exe_dir.join("repo"));
+
+    if let Some(url) = config.repos.get(repo) {
+        return Ok(RepoTarget { name: repo.to_string(), location: repo_location_from_url(url) });
     }
+
+    Ok(RepoTarget { name: repo.to_string(), location: RepoLocation::Local(get_repo_path(config, Some(repo))?) })
 }
 
-/// 获取默认仓库的 URL
-/// - file://...
-pub fn get_repo_url(repo_name: Option<&str>) -> AppResult<String> {
-    let path = get_repo_path(repo_name)?;
+/// 获取本地仓库路径
+/// - 配置了 repo_root 时为 {repo_root}/{repo_name}
+/// - 否则为 exe 所在目录下的 {repo_name}
+/// - 不指定 repo_name 时使用配置的 default_repo（默认为 "repo"）
+pub fn get_repo_path(config: &Config, repo_name: Option<&str>) -> AppResult<PathBuf> {
+    let repo_root = match &config.repo_root {
+        Some(root) => root.clone(),
+        None => {
+            // 获取当前可执行文件的路径
+            let exe_path = env::current_exe()?;
+            // 获取父目录 (ws.exe 所在的文件夹)
+            exe_path.parent().ok_or(
+                AppError::Validation("Cannot find executable directory".to_string())
+            )?.to_path_buf()
+        }
+    };
+
+    let repo = repo_name.unwrap_or(&config.default_repo);
+    validate_folder_name(repo, true)?;
+    Ok(repo_root.join(repo))
+}
+
+/// 获取仓库的 URL
+/// - 本地仓库：file://...，不存在时创建
+/// - 远程仓库：仓库 URL，缺少 .ws_empty 时创建
+pub fn get_repo_url(svn: &dyn SvnExecutor, target: &RepoTarget) -> AppResult<String> {
+    let path = match &target.location {
+        RepoLocation::Remote(url) => {
+            if !check_url_exists(svn, &format!("{}/.ws_empty", url))? {
+                create_ws_empty(svn, url)?;
+            }
+            return Ok(url.clone());
+        }
+        RepoLocation::Local(path) => path,
+    };
 
     // 转换路径为 URL 格式
     let path_str = path.to_string_lossy().replace('\\', "/");
-    
+
     // Windows 盘符前通常需要加一个 /，例如 file:///C:/...
     let url = if cfg!(windows) && !path_str.starts_with('/') {
          format!("file:///{}", path_str)
@@ -41,14 +138,17 @@ pub fn get_repo_url(repo_name: Option<&str>) -> AppResult<String> {
         return Ok(url);
     }
 
-    svnadmin_create(path.to_str().unwrap())?;
-    // 添加一个默认的 .ws_empty 文件夹到仓库根目录，用于切换时清空工作副本
-    crate::core::svn::svn_svnmucc(&[
-        "mkdir", &format!("{}/.ws_empty", url),
-        "-m", "Add default .ws_empty folder",
-    ])?;
+    svnadmin_create(svn, path.to_str().unwrap())?;
+    create_ws_empty(svn, &url)?;
 
     Ok(url)
 }
 
-
+/// 添加一个默认的 .ws_empty 文件夹到仓库根目录，用于切换时清空工作副本
+fn create_ws_empty(svn: &dyn SvnExecutor, url: &str) -> AppResult<()> {
+    crate::core::svn::svn_svnmucc(svn, &[
+        "mkdir", &format!("{}/.ws_empty", url),
+        "-m", "Add default .ws_empty folder",
+    ])?;
+    Ok(())
+}
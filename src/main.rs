@@ -1,49 +1,146 @@
 use clap::{Parser, Subcommand};
 
 use crate::{
-    commands::{project::*, workspace::*},
+    commands::{models::LogFilter, project::*, utils_commit::recover_partial_commit, workspace::*},
     core::{app::App, error::{AppError, AppResult}},
+    ui::{models::OutputFormat, policy::{ConflictPolicy, DirtyPolicy, PromptPolicy}},
 };
 
 mod core;
 mod commands;
 mod ui;
 
+#[cfg(test)]
+mod tests;
+
 
 #[derive(Parser, Debug)]
 #[command(name = "Workspace Manager")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Never open a prompt; fail when a prompt has no answer from the flags below. Confirmations are answered with yes
+    #[arg(long, visible_alias = "yes", short = 'y', global = true, default_value_t = false)]
+    non_interactive: bool,
+
+    /// How to handle uncommitted changes: commit, branch:<name>, stash, discard or abort. Committing on an older revision takes commit, branch:<name> or abort
+    #[arg(long, global = true, value_parser = DirtyPolicy::parse)]
+    on_dirty: Option<DirtyPolicy>,
+
+    /// How to resolve conflicted files: mine, theirs or abort
+    #[arg(long, global = true, value_parser = ConflictPolicy::parse)]
+    conflicts: Option<ConflictPolicy>,
+
+    /// Commit message, used by commit and whenever changes need to be committed
+    #[arg(short, long, global = true)]
+    message: Option<String>,
+
+    /// Output format of query commands such as log and list
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+impl Cli {
+    fn prompt_policy(&self) -> PromptPolicy {
+        PromptPolicy {
+            non_interactive: self.non_interactive,
+            on_dirty: self.on_dirty.clone(),
+            conflicts: self.conflicts,
+            message: self.message.clone(),
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Show all history logs
     Log {
+        /// Only show changes under these paths, relative to the project root
+        paths: Vec<String>,
+
         /// Show all logs including those before the branch was created
         #[arg(short, long, default_value_t = false)]
         all: bool,
+
+        /// Only show commits whose author contains this text (case-insensitive)
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Only show commits after this time (e.g., "2w", "3d", "12h" or "2025-03-01")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only show commits before this time (e.g., "1w" or "2025-03-01 18:00")
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only show commits whose message matches this regular expression
+        #[arg(long)]
+        grep: Option<String>,
+
+        /// Show at most N commits (defaults to log_limit from the config, unlimited with --all)
+        #[arg(short = 'n', long)]
+        limit: Option<u32>,
+
+        /// Draw trunk and all branches as lanes, with branch points and merges
+        #[arg(long, conflicts_with_all = ["paths", "all", "author", "since", "until", "grep"])]
+        graph: bool,
     },
-    /// Commit changes to the repository
-    Commit {
-        /// Commit message
+    /// Show the author, date, full message, changed paths and diff of a revision
+    Show {
+        /// Target revision (e.g., "r100", "HEAD~1" or "feature@r120")
+        #[arg(default_value = "HEAD")]
+        revision: String,
+    },
+    /// Show the changes that commit would act on, following .gitignore
+    Status {},
+    /// Show changes in the working copy, between revisions or against another branch
+    Diff {
+        /// Only show these paths, relative to the project root
+        paths: Vec<String>,
+
+        /// Revision or range to compare (e.g., "100" compares r100 with the working copy, "100:120" compares two revisions)
+        #[arg(short, long, conflicts_with = "branch")]
+        revision: Option<String>,
+
+        /// Compare the current branch with another branch of this project (e.g., trunk)
         #[arg(short, long)]
-        message: Option<String>,
+        branch: Option<String>,
+
+        /// Only show the number of changed lines per file
+        #[arg(long, default_value_t = false)]
+        stat: bool,
+    },
+    /// Commit changes to the repository, use --message to set the commit message
+    Commit {
+        /// Only commit changes under these paths, other changes stay in the working copy
+        paths: Vec<String>,
+
+        /// Choose the files, and the hunks of text files, to commit
+        #[arg(short, long, default_value_t = false)]
+        interactive: bool,
     },
     /// Review a specific revision in the project
     Review {
-        /// Target revision (e.g., "100" or "r100")
+        /// Target revision (e.g., "r100", "HEAD~3", "PREV", "{2025-03-01}" or "feature@r120")
         #[arg(short, long)]
         revision: String,
     },
-    /// Revert local changes in the workspace
+    /// Revert the project or some paths to an older revision, or undo single commits with --commit
     Revert {
-        /// Target revision (e.g., "100" or "r100")
-        #[arg(short, long)]
-        revision: String,
+        /// Target revision on the current branch (e.g., "r100", "HEAD~3", "PREV" or "{2025-03-01}")
+        #[arg(short, long, required_unless_present = "commit")]
+        revision: Option<String>,
+
+        /// Only restore these files or directories, relative to the project root
+        paths: Vec<String>,
+
+        /// Undo only these commits and keep later ones, comma-separated revisions or ranges (e.g., "r42" or "r40:r42,r50")
+        #[arg(long, conflicts_with_all = ["revision", "paths"])]
+        commit: Option<String>,
     },
-    /// Create, delete branches for the current project
+    /// Create, delete, restore or rename branches for the current project
     Branch {
         /// Branch name
         name: Option<String>,
@@ -59,6 +156,39 @@ enum Commands {
         /// Restores the specified branch, with higher priority than the 'new' and 'delete' parameters
         #[arg(short, long, default_value_t = false)]
         restore: bool,
+
+        /// Create the new branch from this revision instead of the current one (e.g., "HEAD~2" or "trunk@r120")
+        #[arg(long)]
+        revision: Option<String>,
+
+        /// Rename branch OLD to NEW, keeping its history
+        #[arg(long, num_args = 2, value_names = ["OLD", "NEW"], conflicts_with_all = ["name", "delete", "restore", "revision"])]
+        rename: Option<Vec<String>>,
+
+        /// List the commits each side can still merge into the other, B defaults to the current branch
+        #[arg(long, num_args = 1..=2, value_names = ["A", "B"], conflicts_with_all = ["name", "delete", "restore", "revision", "rename"])]
+        compare: Option<Vec<String>>,
+    },
+    /// Create, list and delete tags of the current project, lists tags when no name is given
+    Tag {
+        /// Tag name
+        name: Option<String>,
+
+        /// Create the tag from this revision instead of the current one (e.g., "r100", "HEAD~2" or "feature@r120")
+        #[arg(short, long)]
+        revision: Option<String>,
+
+        /// List tags with their source revision and date
+        #[arg(short, long, default_value_t = false, conflicts_with_all = ["name", "revision", "delete"])]
+        list: bool,
+
+        /// Delete the specified tag
+        #[arg(short, long, default_value_t = false, requires = "name", conflicts_with = "revision")]
+        delete: bool,
+
+        /// Also list the rollback-* anchors created by revert
+        #[arg(long, default_value_t = false, conflicts_with_all = ["name", "revision", "delete"])]
+        all: bool,
     },
 
     /// Pull updates from the repository, or pull updates from a specified branch
@@ -73,6 +203,20 @@ enum Commands {
         #[arg(short, long)]
         target: Option<String>,
     },
+    /// Set local changes aside and restore them later, saves them when no action is given
+    Stash {
+        #[command(subcommand)]
+        action: Option<StashAction>,
+    },
+    /// Merge only the chosen revisions of another branch into the current branch and commit them
+    Pick {
+        /// Comma-separated revisions to pick (e.g., "r120,r125" or "trunk@HEAD")
+        revisions: String,
+
+        /// Source branch of the revisions, detected from the first revision when omitted
+        #[arg(short, long)]
+        from: Option<String>,
+    },
 
     // Workspace commands
 
@@ -81,13 +225,18 @@ enum Commands {
         /// List all projects in the repository, including deleted projects.
         #[arg(short, long, default_value_t = false)]
         all: bool,
+
+        /// Show how many commits each branch is ahead of and behind trunk
+        #[arg(short, long, default_value_t = false)]
+        compare: bool,
     },
     /// Add a new empty project to the repository
     New {
         /// The name of the new project to create
         project_name: String,
 
-        /// When there is no .svn folder in current directory, specify the repo name to create the project in
+        /// When there is no .svn folder in current directory, specify the repo to create the project in:
+        /// a local repo name, a name registered under [repos] in the config, or a svn://, http(s):// URL
         #[arg(short, long)]
         repo: Option<String>,
     },
@@ -95,7 +244,8 @@ enum Commands {
     Checkout {
         /// The name of the project to check out
         project_name: String,
-        /// When there is no .svn folder in current directory, specify the repo name to check out the project from
+        /// When there is no .svn folder in current directory, specify the repo to check out the project from:
+        /// a local repo name, a name registered under [repos] in the config, or a svn://, http(s):// URL
         /// When there is a .svn folder, this parameter is ignored, and the current repo is used, it's same as switching projects
         #[arg(short, long)]
         repo: Option<String>,
@@ -122,8 +272,12 @@ enum Commands {
         project_name: Option<String>,
 
         /// The target branch name; if not specified, defaults to the current branch of that project
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "tag")]
         branch: Option<String>,
+
+        /// Switch to a tag of that project instead, tags are read-only
+        #[arg(short, long)]
+        tag: Option<String>,
     },
     
 
@@ -143,33 +297,108 @@ enum Commands {
     }
 }
 
+#[derive(Subcommand, Debug)]
+enum StashAction {
+    /// Save the local changes, including new files that are not ignored, and clean the workspace. Use -m to name it
+    Push,
+    /// List the stashes of the current project, newest first
+    List,
+    /// Show the files saved in a stash
+    Show {
+        /// Stash index from ws stash list, 0 is the newest
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+    /// Restore a stash and drop it; it is kept when some of its files changed in the meantime
+    Pop {
+        /// Stash index from ws stash list, 0 is the newest
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+    /// Delete a stash without restoring it
+    Drop {
+        /// Stash index from ws stash list, 0 is the newest
+        #[arg(default_value_t = 0)]
+        index: usize,
+    },
+}
+
 fn main() {
+    let code = run();
+    // 退出前 App 已经释放，光标等终端状态已经恢复
+    std::process::exit(code);
+}
+
+/// 执行命令，返回进程退出码
+fn run() -> i32 {
     let cli = match Cli::try_parse() {
         Ok(c) => c,
         Err(e) => {
             println!("{}", e);
-            return;
+            return if e.use_stderr() { 2 } else { 0 };
         }
     };
   
+    let policy = cli.prompt_policy();
+
     // Attempt to initialize the App
     let app_result = App::new();
 
     match app_result {
-        Ok(app) => { // App initialized successfully
+        Ok(mut app) => { // App initialized successfully
+            app.ui.set_policy(policy.with_defaults(&app.svn_ctx.get_config().prompt));
+            app.ui.set_output_format(cli.format);
+            // 输出格式确定后再恢复被中断的部分提交，提示不会混进 json 输出
+            if let Err(e) = recover_partial_commit(&app) {
+                return report_result(&app, Err(e));
+            }
             let command_result: AppResult<()> = match cli.command {
                 // Project
-                Commands::Log { all } => handle_log(&app, all),
-                Commands::Commit { message } => handle_commit(&app, &message),
+                Commands::Log { paths, all, author, since, until, grep, limit, graph } => {
+                    if graph {
+                        handle_log_graph(&app, limit)
+                    } else {
+                        handle_log(&app, all, &LogFilter { author, since, until, grep, limit, paths })
+                    }
+                },
+                Commands::Show { revision } => handle_show(&app, &revision),
+                Commands::Status {} => handle_status(&app),
+                Commands::Diff { paths, revision, branch, stat } => handle_diff(&app, revision.as_deref(), branch.as_deref(), stat, &paths),
+                Commands::Commit { paths, interactive } => {
+                    if paths.is_empty() && !interactive {
+                        handle_commit(&app, &cli.message)
+                    } else {
+                        handle_commit_selected(&app, &cli.message, &paths, interactive)
+                    }
+                },
                 Commands::Review { revision } => handle_review(&app, &revision),
-                Commands::Revert { revision } => handle_revert(&app, &revision),
-                Commands::Branch { name, new, delete, restore } => handle_branch(&app, name, new, delete, restore),
+                Commands::Revert { revision, paths, commit } => match commit {
+                    Some(commit) => handle_revert_commits(&app, &commit),
+                    None => handle_revert(&app, revision.as_deref().unwrap_or_default(), &paths),
+                },
+                Commands::Branch { name, new, delete, restore, revision, rename, compare } => match (rename.as_deref(), compare.as_deref()) {
+                    (Some([old_name, new_name]), _) => handle_branch_rename(&app, old_name, new_name),
+                    (_, Some([base, rest @ ..])) => handle_branch_compare(&app, base, rest.first().map(String::as_str)),
+                    _ => handle_branch(&app, name, new, delete, restore, revision.as_deref()),
+                },
+                Commands::Tag { name, revision, delete, all, .. } => handle_tag(&app, name, revision.as_deref(), delete, all),
                 Commands::Pull { source } => handle_pull(&app, source.as_deref()),
                 Commands::Push { target } => handle_push(&app, target.as_deref()),
+                Commands::Stash { action } => match action.unwrap_or(StashAction::Push) {
+                    StashAction::Push => handle_stash_push(&app, cli.message.as_deref()),
+                    StashAction::List => handle_stash_list(&app),
+                    StashAction::Show { index } => handle_stash_show(&app, index),
+                    StashAction::Pop { index } => handle_stash_pop(&app, index),
+                    StashAction::Drop { index } => handle_stash_drop(&app, index),
+                },
+                Commands::Pick { revisions, from } => handle_pick(&app, &revisions, from.as_deref()),
 
                 // Workspace
-                Commands::List { all } => handle_list(&app, all),
-                Commands::Switch { project_name, branch } => handle_switch(&app, project_name.as_deref(), branch),
+                Commands::List { all, compare } => handle_list(&app, all, compare),
+                Commands::Switch { project_name, branch, tag } => match tag {
+                    Some(tag) => handle_switch_tag(&app, project_name.as_deref(), &tag),
+                    None => handle_switch(&app, project_name.as_deref(), branch),
+                },
                 Commands::New { project_name, .. } => handle_new(&app, &project_name),
                 Commands::Delete { project_name, force } => handle_delete(&app, &project_name, force),
                 Commands::Restore { project_name } => handle_restore(&app, &project_name),
@@ -179,56 +408,69 @@ fn main() {
                 _ => Ok(()), // Placeholder for other commands
             };
 
-            if let Err(e) = command_result {
-                match e {
-                    AppError::OperationCancelled => app.ui.success("Operation cancelled by user."),
-                    _ => app.ui.error(&format!("{}", e)),
-                }
-            }
+            report_result(&app, command_result)
         },
         Err(e) => {
             match e {
                 AppError::SvnCommandFailed { .. } => { // Likely not an SVN working copy
                     match cli.command {
                         Commands::New { project_name, repo } => {
-                            let app = match App::default(repo.as_deref()) {
+                            let mut app = match App::default(repo.as_deref()) {
                                 Ok(a) => a,
                                 Err(e) => {
                                     eprintln!("Error initializing application: {}", e);
-                                    return;
+                                    return e.exit_code();
                                 }
                             };
-                            if let Err(e) = handle_new(&app, &project_name) {
-                                match e {
-                                    AppError::OperationCancelled => app.ui.success("Operation cancelled by user."),
-                                    _ => app.ui.error(&format!("{}", e)),
-                                }
-                            }
-                            
+                            app.ui.set_policy(policy.with_defaults(&app.svn_ctx.get_config().prompt));
+                            app.ui.set_output_format(cli.format);
+                            let result = handle_new(&app, &project_name);
+                            report_result(&app, result)
                         },
                         Commands::Checkout { project_name, repo } => {
-                            let app = match App::default(repo.as_deref()) {
+                            let mut app = match App::default(repo.as_deref()) {
                                 Ok(a) => a,
                                 Err(e) => {
                                     eprintln!("Error initializing application: {}", e);
-                                    return;
+                                    return e.exit_code();
                                 }
                             };
-                            if let Err(e) = handle_checkout(&app, &project_name) {
-                                match e {
-                                    AppError::OperationCancelled => app.ui.success("Operation cancelled by user."),
-                                    _ => app.ui.error(&format!("{}", e)),
-                                }
+                            app.ui.set_policy(policy.with_defaults(&app.svn_ctx.get_config().prompt));
+                            app.ui.set_output_format(cli.format);
+                            let result = handle_checkout(&app, &project_name);
+                            report_result(&app, result)
+                        },
+                        Commands::LinkFolder { project_name, origin_dir_path: vault_target_path } => {
+                            match handle_link_folder(&project_name, &vault_target_path) {
+                                Ok(_) => 0,
+                                Err(e) => e.exit_code(),
                             }
                         },
-                        Commands::LinkFolder { project_name, origin_dir_path: vault_target_path } => { let _ = handle_link_folder(&project_name, &vault_target_path); },
                         _ => {
                             eprintln!("Error: The current directory is not a valid SVN working copy. Please navigate to a valid SVN workspace or create a new project using the 'new' command.");
+                            e.exit_code()
                         }
                     }
                 },
-                _ => eprintln!("Error: {}", e),
+                _ => {
+                    eprintln!("Error: {}", e);
+                    e.exit_code()
+                }
+            }
+        }
+    }
+}
+
+/// 打印命令的执行结果，返回进程退出码
+fn report_result(app: &App, result: AppResult<()>) -> i32 {
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            match e {
+                AppError::OperationCancelled => app.ui.success("Operation cancelled by user."),
+                _ => app.ui.error(&format!("{}", e)),
             }
+            e.exit_code()
         }
     }
-}
\ No newline at end of file
+}
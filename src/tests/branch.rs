@@ -0,0 +1,93 @@
+//! ws branch --rename：服务器端移动分支，保留分支的历史
+//! ws branch --compare、ws list --compare：分支之间还没有合并的提交
+
+use crate::{
+    commands::{
+        models::LogFilter,
+        project::{build_log_entries, handle_branch, handle_branch_rename, handle_commit, handle_push},
+        workspace::handle_switch,
+        utils_branch::{add_ahead_behind, compare_branches, get_project_branches},
+        utils_graph::build_log_graph,
+    },
+    core::error::AppError,
+    tests::harness::TestEnv,
+    ui::models::{LogEntry, LogEntryKind},
+};
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn renamed_branch_keeps_its_history() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+
+    handle_branch_rename(&env.app(&[]), "feature", "parser").expect("ws branch --rename");
+    assert!(!env.url_exists("demo/branches/feature"));
+    assert!(env.url_exists("demo/branches/parser"));
+    // 工作副本跟着分支改名
+    let app = env.app(&[]);
+    assert_eq!(app.svn_ctx.get_current_branch_name().expect("branch name"), "parser");
+
+    let log = build_log_entries(&app, false, &LogFilter::default()).expect("ws log");
+    assert!(log.iter().any(|e| e.message == "Add b"), "{:?}", log.iter().map(|e| &e.message).collect::<Vec<_>>());
+    let creation = log.iter().find(|e| matches!(e.kind, LogEntryKind::Branch { .. })).expect("branch creation");
+    assert!(matches!(&creation.kind, LogEntryKind::Branch { source, .. } if source == "trunk"));
+    // 分支创建之前的 trunk 提交不显示
+    assert!(!log.iter().any(|e| e.message == "Add a"));
+
+    let graph = build_log_graph(&app, None).expect("ws log --graph");
+    let creation = graph.iter().find(|c| matches!(c.entry.kind, LogEntryKind::Branch { .. })).expect("branch creation in graph");
+    assert_eq!((creation.branch.as_str(), creation.from_branch.as_deref()), ("parser", Some("trunk")));
+
+    let err = handle_branch_rename(&env.app(&[]), "feature", "other").unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+    let err = handle_branch_rename(&env.app(&[]), "trunk", "main").unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn compare_lists_commits_not_merged_in_each_direction() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+    env.write_file("c.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add c".to_string())).expect("commit on feature");
+
+    handle_switch(&env.app(&[]), None, Some("trunk".to_string())).expect("ws switch trunk");
+    env.write_file("d.txt", "trunk\n");
+    handle_commit(&env.app(&[]), &Some("Add d".to_string())).expect("commit on trunk");
+
+    let comparison = compare_branches(&env.app(&[]), "trunk", "feature").expect("ws branch --compare");
+    let messages = |entries: &[LogEntry]| entries.iter().map(|e| e.message.clone()).collect::<Vec<_>>();
+    // 创建分支的提交不算在内
+    assert_eq!(messages(&comparison.ahead), vec!["Add c", "Add b"]);
+    assert_eq!(messages(&comparison.behind), vec!["Add d"]);
+
+    let app = env.app(&[]);
+    let mut branches = get_project_branches(&app, "demo").expect("branches");
+    add_ahead_behind(&app, "demo", &mut branches).expect("ws list --compare");
+    let feature = branches.iter().find(|b| b.branch_name == "feature").expect("feature");
+    assert_eq!((feature.ahead, feature.behind), (Some(2), Some(1)));
+    assert_eq!((branches[0].ahead, branches[0].behind), (None, None));
+
+    // 合并之后 trunk 不再落后
+    handle_switch(&env.app(&[]), None, Some("feature".to_string())).expect("ws switch feature");
+    handle_push(&env.app(&[]), Some("trunk")).expect("ws push trunk");
+    handle_commit(&env.app(&[]), &Some("Merge feature".to_string())).expect("commit merge");
+    let comparison = compare_branches(&env.app(&[]), "trunk", "feature").expect("ws branch --compare");
+    assert!(comparison.ahead.is_empty(), "{:?}", messages(&comparison.ahead));
+
+    let result = compare_branches(&env.app(&[]), "trunk", "missing");
+    assert!(matches!(result, Err(AppError::Validation(_))));
+}
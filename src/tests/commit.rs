@@ -0,0 +1,263 @@
+//! ws commit <paths> 和 ws commit --interactive：只提交选中的文件和修改块
+
+use std::rc::Rc;
+
+use crate::{
+    commands::{
+        project::{build_workspace_status, handle_commit, handle_commit_selected},
+        utils_commit::{commit_selected_paths, is_partial_commit_in_progress, restore_partial_files, write_partial_files},
+        utils_diff::{apply_hunks, parse_unified_diff},
+    },
+    core::error::AppError,
+    tests::{harness::TestEnv, scripted::{ScriptedExecutor, scripted_app}},
+    ui::{models::{DiffHunk, StatusKind}, policy::PromptPolicy},
+};
+
+const DIFF: &str = "\
+Index: a.txt
+===================================================================
+--- a.txt\t(revision 3)
++++ a.txt\t(working copy)
+@@ -1,3 +1,3 @@
+-one
++ONE
+ two
+ three
+@@ -8,2 +8,3 @@
+ eight
+ nine
++ten
+Property changes on: a.txt
+___________________________________________________________________
+Added: svn:eol-style
+## -0,0 +1 ##
++native
+";
+
+fn hunks() -> Vec<DiffHunk> {
+    parse_unified_diff(DIFF).remove(0).hunks()
+}
+
+#[test]
+fn hunks_are_split_from_the_file_diff() {
+    let hunks = hunks();
+    assert_eq!(hunks.len(), 2);
+    assert_eq!((hunks[0].old_start, hunks[0].old_len, hunks[0].new_start, hunks[0].new_len), (1, 3, 1, 3));
+    assert_eq!((hunks[1].old_start, hunks[1].old_len, hunks[1].new_start, hunks[1].new_len), (8, 2, 8, 3));
+    assert_eq!(hunks[0].lines, vec!["@@ -1,3 +1,3 @@", "-one", "+ONE", " two", " three"]);
+    // 属性的修改不算作修改块
+    assert_eq!(hunks[1].lines.last().map(String::as_str), Some("+ten"));
+}
+
+#[test]
+fn only_selected_hunks_are_applied() {
+    let base = b"one\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\n";
+    let working = b"ONE\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\nten";
+    let hunks = hunks();
+
+    assert_eq!(apply_hunks(base, working, &hunks, &[true, true]), working.to_vec());
+    assert_eq!(apply_hunks(base, working, &hunks, &[false, false]), base.to_vec());
+    // 选中的行保留工作副本中的换行符
+    assert_eq!(apply_hunks(base, working, &hunks, &[true, false]), b"ONE\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\n".to_vec());
+    assert_eq!(apply_hunks(base, working, &hunks, &[false, true]), b"one\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\nten".to_vec());
+
+    // 空文件中添加的内容
+    let added = DiffHunk { old_start: 0, old_len: 0, new_start: 1, new_len: 1, lines: vec![] };
+    assert_eq!(apply_hunks(b"", b"new\n", std::slice::from_ref(&added), &[true]), b"new\n".to_vec());
+}
+
+#[test]
+fn partial_files_are_restored_from_the_backup() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-partial-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let backup = dir.join("backup");
+    let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+    let (a, b) = (a.to_str().expect("utf-8 path"), b.to_str().expect("utf-8 path"));
+
+    // 提交期间工作文件是选中的内容，原内容在备份中
+    std::fs::write(a, "first\nlast\n").expect("write a");
+    std::fs::write(b, "one\ntwo\n").expect("write b");
+    write_partial_files(&backup, &[(a, b"first\n"), (b, b"one\n")]).expect("write partial files");
+    assert_eq!(std::fs::read_to_string(a).expect("read a"), "first\n");
+    assert!(backup.exists());
+
+    // 被中断后，之后又修改过的文件的当前内容另存到旁边
+    std::fs::write(b, "edited\n").expect("edit b");
+    let kept = restore_partial_files(&backup, true).expect("restore partial files");
+    assert_eq!(std::fs::read_to_string(a).expect("read a"), "first\nlast\n");
+    assert_eq!(std::fs::read_to_string(b).expect("read b"), "one\ntwo\n");
+    assert_eq!(kept, vec![dir.join("b.partial.txt").to_string_lossy().to_string()]);
+    assert_eq!(std::fs::read_to_string(&kept[0]).expect("read kept"), "edited\n");
+    assert!(!backup.exists());
+
+    // 没有清单时什么也不恢复
+    std::fs::create_dir_all(&backup).expect("create backup");
+    assert!(restore_partial_files(&backup, true).expect("restore empty backup").is_empty());
+    assert!(!backup.exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn partial_commit_is_only_recovered_when_its_owner_is_gone() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-partial-owner-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let backup = dir.join("backup");
+    let a = dir.join("a.txt");
+    let a = a.to_str().expect("utf-8 path");
+    std::fs::write(a, "first\nlast\n").expect("write a");
+
+    // 当前进程自己的备份不算作另一个进程正在提交
+    write_partial_files(&backup, &[(a, b"first\n")]).expect("write partial files");
+    assert!(!is_partial_commit_in_progress(&backup));
+
+    // 另一个仍在运行的进程正在提交，还没有回收的子进程在 Unix 上一定仍然存在
+    let mut child = std::process::Command::new(std::env::current_exe().expect("test executable"))
+        .args(["--exact", "no-such-test"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("spawn child");
+    std::fs::write(backup.join("owner"), child.id().to_string()).expect("write owner");
+    #[cfg(unix)]
+    assert!(is_partial_commit_in_progress(&backup));
+
+    // 进程结束后才恢复
+    child.wait().expect("wait child");
+    assert!(!is_partial_commit_in_progress(&backup));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn selected_file_in_added_directory_is_committed_non_recursively() {
+    let status = r#"<?xml version="1.0" encoding="UTF-8"?>
+<status><target path=".">
+<entry path="docs"><wc-status item="added" props="none" revision="-1"/></entry>
+<entry path="docs/new"><wc-status item="added" props="none" revision="-1"/></entry>
+<entry path="docs/new/c.txt"><wc-status item="added" props="none" revision="-1"/></entry>
+<entry path="docs/other.txt"><wc-status item="added" props="none" revision="-1"/></entry>
+</target></status>"#;
+    let commit = "svn commit --depth empty -m \"Add c\" docs docs/new docs/new/c.txt";
+    let svn = Rc::new(ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 4)
+        .on("svn status --xml", status)
+        .on(commit, "Committed revision 5."));
+    let app = scripted_app(&svn, &[]);
+
+    // 添加的上级目录只提交自身，同一目录下未选中的 docs/other.txt 不会被一起提交
+    commit_selected_paths(&app, "Add c", &["docs/new/c.txt"]).expect("commit selected paths");
+    assert_eq!(svn.calls().last().map(String::as_str), Some(commit));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn commit_paths_leaves_other_changes() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    env.write_file("docs/b.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    env.write_file("a.txt", "two\n");
+    env.write_file("docs/b.txt", "two\n");
+    env.write_file("docs/new/c.txt", "new\n");
+    handle_commit_selected(&env.app(&[]), &Some("Update docs".to_string()), &["docs".to_string()], false).expect("ws commit docs");
+
+    let status = build_workspace_status(&env.app(&[])).expect("ws status");
+    let paths: Vec<(String, StatusKind)> = status.entries.iter().map(|e| (e.path.replace('\\', "/"), e.kind)).collect();
+    assert_eq!(paths, vec![("a.txt".to_string(), StatusKind::Modified)]);
+    assert_eq!(env.log_messages("demo/trunk")[0], "Update docs");
+
+    let err = handle_commit_selected(&env.app(&[]), &Some("Nothing".to_string()), &["missing".to_string()], false).unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn commit_paths_records_moves() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    let content: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+    env.write_file("old/a.txt", &content);
+    env.write_file("other.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    // 只给出新路径，缺失的旧路径也作为改名一起提交
+    std::fs::create_dir_all(env.ws().join("new")).expect("create new dir");
+    std::fs::rename(env.ws().join("old/a.txt"), env.ws().join("new/a.txt")).expect("move a.txt");
+    env.write_file("other.txt", "two\n");
+    handle_commit_selected(&env.app(&[]), &Some("Move a".to_string()), &["new/a.txt".to_string()], false).expect("ws commit new/a.txt");
+
+    let log = env.svn(&["log", "-v", "-l", "1", "new/a.txt"]);
+    assert!(log.contains("/demo/trunk/new/a.txt (from /demo/trunk/old/a.txt"), "{}", log);
+    let status = build_workspace_status(&env.app(&[])).expect("ws status");
+    let paths: Vec<(String, StatusKind)> = status.entries.iter().map(|e| (e.path.replace('\\', "/"), e.kind)).collect();
+    assert_eq!(paths, vec![("other.txt".to_string(), StatusKind::Modified)]);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn commit_paths_records_directory_moves_with_added_and_dropped_files() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    let content: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+    env.write_file("old/a.txt", &content);
+    env.write_file("old/b.txt", &content.replace("line", "row"));
+    env.write_file("old/dropped.txt", "dropped\n");
+    env.write_file("other.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    // 移动整个目录，同时新增一个文件、去掉一个文件
+    std::fs::rename(env.ws().join("old"), env.ws().join("new")).expect("move dir");
+    std::fs::remove_file(env.ws().join("new/dropped.txt")).expect("remove dropped.txt");
+    env.write_file("new/c.txt", "new\n");
+    env.write_file("other.txt", "two\n");
+    handle_commit_selected(&env.app(&[]), &Some("Move old".to_string()), &["new".to_string()], false).expect("ws commit new");
+
+    let log = env.svn(&["log", "-v", "--xml", "-l", "1"]);
+    assert!(log.contains("copyfrom-path=\"/demo/trunk/old\""), "{}", log);
+    assert_eq!(env.svn(&["cat", "new/c.txt"]), "new");
+    assert_eq!(env.svn(&["list", &env.url("demo/trunk/new")]), "a.txt\nb.txt\nc.txt");
+    let status = build_workspace_status(&env.app(&[])).expect("ws status");
+    let paths: Vec<(String, StatusKind)> = status.entries.iter().map(|e| (e.path.replace('\\', "/"), e.kind)).collect();
+    assert_eq!(paths, vec![("other.txt".to_string(), StatusKind::Modified)]);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn interactive_commit_selects_files_and_hunks() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    let lines: Vec<String> = (1..=12).map(|i| format!("line {}\n", i)).collect();
+    env.write_file("a.txt", &lines.concat());
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+
+    let mut changed = lines.clone();
+    changed[0] = "first\n".to_string();
+    changed[11] = "last\n".to_string();
+    env.write_file("a.txt", &changed.concat());
+    env.write_file("b.txt", "experiment\n");
+
+    // 只勾选 a.txt 和它的第一个修改块
+    handle_commit_selected(&env.app(&["a.txt", "Hunk 1"]), &Some("First line".to_string()), &[], true).expect("ws commit -i");
+
+    let mut committed = lines.clone();
+    committed[0] = "first\n".to_string();
+    assert_eq!(env.svn(&["cat", "a.txt"]), committed.concat().trim_end());
+    assert_eq!(env.read_file("a.txt"), changed.concat());
+    let status = build_workspace_status(&env.app(&[])).expect("ws status");
+    assert!(status.entries.iter().any(|e| e.path == "a.txt" && e.kind == StatusKind::Modified));
+    assert!(status.entries.iter().any(|e| e.path == "b.txt" && e.kind == StatusKind::Added && e.auto));
+
+    // 非交互模式下不能勾选
+    let mut app = env.app(&[]);
+    app.ui.set_policy(PromptPolicy { non_interactive: true, ..Default::default() });
+    let err = handle_commit_selected(&app, &Some("x".to_string()), &[], true);
+    assert!(matches!(err, Err(AppError::PromptRequired { .. })));
+}
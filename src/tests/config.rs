@@ -0,0 +1,196 @@
+//! 配置文件的逐层合并、环境变量覆盖，以及配置在各处的使用
+
+use std::{collections::HashMap, env, fs, path::{Path, PathBuf}};
+
+use crate::{
+    commands::workspace::handle_new,
+    core::{config::{Config, WORKSPACE_CONFIG_FILE, find_workspace_config}, error::AppError, utils_default_repo::get_repo_path},
+    tests::harness::TestEnv,
+    ui::policy::{ConflictPolicy, DirtyPolicy, PromptPolicy},
+};
+
+/// 一个测试独占的临时目录，结束时删除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("ws-tool-config-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("create temp dir");
+        TempDir(path)
+    }
+
+    fn write(&self, rel_path: &str, content: &str) -> PathBuf {
+        let path = self.0.join(rel_path);
+        fs::create_dir_all(path.parent().expect("parent dir")).expect("create parent dir");
+        fs::write(&path, content).expect("write config");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[test]
+fn defaults_match_previous_behaviour() {
+    let config = Config::default();
+    assert_eq!(config.repo_root, None);
+    assert_eq!(config.store_root, None);
+    assert_eq!(config.default_repo, "repo");
+    assert_eq!(config.log_limit, 100);
+    assert_eq!(config.rename_similarity, 50);
+    assert!(config.messages.auto_commit_message().starts_with("Auto commit "));
+    assert_eq!(config.messages.delete_project_message("demo"), "Delete project demo");
+    assert_eq!(config.messages.restore_project_message("demo"), "Restore project demo");
+    assert_eq!(config.messages.restore_branch_message("dev"), "Restore branch dev");
+    assert!(!config.prompt.non_interactive);
+}
+
+#[test]
+fn later_layers_override_earlier_ones() {
+    let dir = TempDir::new("layers");
+    let system = dir.write("etc/config.toml", r#"
+default_repo = "shared"
+log_limit = 50
+repo_root = "/srv/svn"
+merge_tool = "meld {mine} {base} {theirs} -o {merged}"
+
+[messages]
+auto_commit = "wip {datetime}"
+delete_project = "Remove {project}"
+"#);
+    let user = dir.write("home/config.toml", r#"
+log_limit = 20
+store_root = "store"
+rename_similarity = 80
+
+[prompt]
+on_dirty = "branch:wip"
+conflicts = "theirs"
+"#);
+
+    let mut config = Config::default();
+    config.merge_file(&system).expect("merge system config");
+    config.merge_file(&user).expect("merge user config");
+
+    assert_eq!(config.default_repo, "shared");
+    assert_eq!(config.log_limit, 20);
+    assert_eq!(config.rename_similarity, 80);
+    assert_eq!(config.repo_root, Some(PathBuf::from("/srv/svn")));
+    assert_eq!(config.merge_tool.as_deref(), Some("meld {mine} {base} {theirs} -o {merged}"));
+    // 相对路径以配置文件所在目录为基准
+    assert_eq!(config.store_root, Some(dir.0.join("home").join("store")));
+    assert!(config.messages.auto_commit_message().starts_with("wip "));
+    assert_eq!(config.messages.delete_project_message("demo"), "Remove demo");
+    assert_eq!(config.messages.restore_project_message("demo"), "Restore project demo");
+    assert_eq!(config.prompt.on_dirty, Some(DirtyPolicy::Branch("wip".to_string())));
+    assert_eq!(config.prompt.conflicts, Some(ConflictPolicy::Theirs));
+}
+
+#[test]
+fn invalid_files_are_reported() {
+    let dir = TempDir::new("invalid");
+    let unknown = dir.write("unknown.toml", "log_limt = 10\n");
+    let bad_policy = dir.write("bad.toml", "[prompt]\non_dirty = \"shelve\"\n");
+
+    let err = Config::default().merge_file(&unknown).unwrap_err();
+    assert!(matches!(err, AppError::Config(ref msg) if msg.contains("log_limt")), "{}", err);
+    assert_eq!(err.exit_code(), 1);
+
+    let err = Config::default().merge_file(&bad_policy).unwrap_err();
+    assert!(matches!(err, AppError::Config(ref msg) if msg.contains("prompt.on_dirty")), "{}", err);
+}
+
+#[test]
+fn environment_overrides_files() {
+    let dir = TempDir::new("env");
+    let user = dir.write("config.toml", "default_repo = \"shared\"\nlog_limit = 20\n");
+
+    let mut config = Config::default();
+    config.merge_file(&user).expect("merge user config");
+    config.merge_env(env_of(&[
+        ("WS_TOOL_DEFAULT_REPO", "other"),
+        ("WS_TOOL_LOG_LIMIT", "5"),
+        ("WS_TOOL_RENAME_SIMILARITY", "0"),
+        ("WS_TOOL_STORE_ROOT", "/data/.ws_store"),
+        ("WS_TOOL_NON_INTERACTIVE", "yes"),
+        ("WS_TOOL_CONFLICTS", "mine"),
+        ("WS_TOOL_REPO_ROOT", ""),
+    ])).expect("merge env");
+
+    assert_eq!(config.default_repo, "other");
+    assert_eq!(config.log_limit, 5);
+    assert_eq!(config.rename_similarity, 0);
+    assert_eq!(config.store_root, Some(PathBuf::from("/data/.ws_store")));
+    assert!(config.prompt.non_interactive);
+    assert_eq!(config.prompt.conflicts, Some(ConflictPolicy::Mine));
+    // 空值视为未设置
+    assert_eq!(config.repo_root, None);
+
+    let err = Config::default().merge_env(env_of(&[("WS_TOOL_LOG_LIMIT", "many")])).unwrap_err();
+    assert!(matches!(err, AppError::Config(ref msg) if msg.contains("WS_TOOL_LOG_LIMIT")), "{}", err);
+    let err = Config::default().merge_env(env_of(&[("WS_TOOL_RENAME_SIMILARITY", "120")])).unwrap_err();
+    assert!(matches!(err, AppError::Config(ref msg) if msg.contains("WS_TOOL_RENAME_SIMILARITY")), "{}", err);
+}
+
+#[test]
+fn workspace_config_is_searched_above_the_working_directory() {
+    let dir = TempDir::new("workspace");
+    let ws = dir.0.join("projects").join("ws");
+    fs::create_dir_all(&ws).expect("create workspace");
+
+    assert_eq!(find_workspace_config(&ws), None);
+
+    // 工作目录本身是项目，其中的配置文件不会被使用
+    dir.write(&format!("projects/ws/{}", WORKSPACE_CONFIG_FILE), "log_limit = 1\n");
+    assert_eq!(find_workspace_config(&ws), None);
+
+    let outer = dir.write(WORKSPACE_CONFIG_FILE, "log_limit = 2\n");
+    assert_eq!(find_workspace_config(&ws), Some(outer));
+
+    let inner = dir.write(&format!("projects/{}", WORKSPACE_CONFIG_FILE), "log_limit = 3\n");
+    assert_eq!(find_workspace_config(&ws), Some(inner));
+}
+
+#[test]
+fn command_line_flags_take_precedence_over_prompt_defaults() {
+    let mut config = Config::default();
+    config.merge_env(env_of(&[("WS_TOOL_ON_DIRTY", "discard"), ("WS_TOOL_CONFLICTS", "theirs"), ("WS_TOOL_NON_INTERACTIVE", "1")])).expect("merge env");
+
+    let policy = PromptPolicy { on_dirty: Some(DirtyPolicy::Commit), ..Default::default() }.with_defaults(&config.prompt);
+    assert!(policy.non_interactive);
+    assert_eq!(policy.on_dirty, Some(DirtyPolicy::Commit));
+    assert_eq!(policy.conflicts, Some(ConflictPolicy::Theirs));
+
+    let policy = PromptPolicy::default().with_defaults(&Config::default().prompt);
+    assert!(!policy.non_interactive);
+    assert_eq!(policy.on_dirty, None);
+}
+
+#[test]
+fn repo_path_uses_repo_root_and_default_repo() {
+    let config = Config { repo_root: Some(PathBuf::from("/srv/svn")), default_repo: "shared".to_string(), ..Default::default() };
+
+    assert_eq!(get_repo_path(&config, None).expect("default repo path"), Path::new("/srv/svn/shared"));
+    assert_eq!(get_repo_path(&config, Some("other")).expect("named repo path"), Path::new("/srv/svn/other"));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn store_root_from_workspace_config_is_used_by_new() {
+    let env = TestEnv::new();
+    fs::write(env.root().join(WORKSPACE_CONFIG_FILE), "store_root = \"store\"\n").expect("write workspace config");
+
+    handle_new(&env.outside_app(&[]), "demo").expect("ws new");
+
+    let link = fs::read_link(env.ws()).expect("workspace should be a symlink");
+    assert_eq!(link, env.root().join("store").join("repo").join("demo"));
+}
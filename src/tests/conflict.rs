@@ -0,0 +1,302 @@
+//! 解决内容冲突：冲突标记、冲突文件、外部合并工具，以及只在冲突处选择一边
+//! 按 .ws-conflicts.toml 中的规则自动解决冲突
+
+use std::{path::PathBuf, rc::Rc};
+
+use crate::{
+    commands::{
+        models::{ConflictFiles, ConflictItem, ConflictKind, ConflictResolution},
+        project::handle_commit,
+        utils_commit::{get_conflicted_files, resolve_conflicts_with_rules},
+        utils_conflict::{conflict_shell_command, match_conflict_rule, merge_tool_command, parse_conflict_files, parse_conflict_regions, parse_conflict_rules, side_by_side_path},
+    },
+    core::error::AppError,
+    tests::{harness::TestEnv, scripted::{ScriptedExecutor, scripted_app}},
+    ui::models::ConflictRegion,
+};
+
+#[test]
+fn conflict_markers_are_split_into_regions() {
+    let content = "\
+keep
+<<<<<<< .mine
+mine 1
+mine 2
+||||||| .r1
+base
+=======
+theirs
+>>>>>>> .r2
+middle
+<<<<<<< .working
+=======
+added by them
+>>>>>>> .merge-right.r5
+<<<<<<< unfinished
+";
+    assert_eq!(parse_conflict_regions(content), vec![
+        ConflictRegion { line: 2, mine: vec!["mine 1".to_string(), "mine 2".to_string()], base: vec!["base".to_string()], theirs: vec!["theirs".to_string()] },
+        ConflictRegion { line: 11, mine: Vec::new(), base: Vec::new(), theirs: vec!["added by them".to_string()] },
+    ]);
+    assert!(parse_conflict_regions("no conflicts\n======= is not a marker here\n").is_empty());
+}
+
+#[test]
+fn conflict_files_are_read_from_svn_info() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<info>
+<entry kind="file" path="src/a.txt" revision="2">
+<conflict type="text" operation="update">
+<version side="source-left" kind="file" path-in-repos="trunk/src/a.txt" revision="1"/>
+<version side="source-right" kind="file" path-in-repos="trunk/src/a.txt" revision="2"/>
+<prev-base-file>src/a.txt.r1</prev-base-file>
+<prev-wc-file>src/a.txt.mine</prev-wc-file>
+<cur-base-file>src/a.txt.r2</cur-base-file>
+</conflict>
+</entry>
+</info>"#;
+    let files = parse_conflict_files(xml, "src/a.txt").unwrap().expect("text conflict");
+    assert_eq!((files.base, files.mine, files.theirs), (PathBuf::from("src/a.txt.r1"), PathBuf::from("src/a.txt.mine"), PathBuf::from("src/a.txt.r2")));
+
+    // 二进制文件没有 .mine，本地版本就是工作文件
+    let binary = xml.replace("<prev-wc-file>src/a.txt.mine</prev-wc-file>\n", "");
+    assert_eq!(parse_conflict_files(&binary, "src/a.txt").unwrap().expect("binary conflict").mine, PathBuf::from("src/a.txt"));
+
+    let property = r#"<info><entry kind="file" path="a.txt"><conflict type="property" operation="update"><prop-file>a.txt.prej</prop-file></conflict></entry></info>"#;
+    assert!(parse_conflict_files(property, "a.txt").unwrap().is_none());
+}
+
+#[test]
+fn merge_tool_placeholders_are_replaced_per_argument() {
+    let files = ConflictFiles {
+        base: PathBuf::from("my docs/a.txt.r1"),
+        mine: PathBuf::from("my docs/a.txt.mine"),
+        theirs: PathBuf::from("my docs/a.txt.r2"),
+    };
+    assert_eq!(merge_tool_command("meld {mine} {base} {theirs} --output={merged}", &files, "my docs/a.txt"), vec![
+        "meld", "my docs/a.txt.mine", "my docs/a.txt.r1", "my docs/a.txt.r2", "--output=my docs/a.txt",
+    ]);
+}
+
+#[test]
+fn both_versions_are_kept_side_by_side() {
+    assert_eq!(side_by_side_path("img/logo.png", "theirs"), "img/logo.theirs.png");
+    assert_eq!(side_by_side_path("Makefile", "theirs"), "Makefile.theirs");
+    assert_eq!(side_by_side_path("conf.d/.env", "theirs"), "conf.d/.env.theirs");
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn theirs_conflict_keeps_my_other_changes() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "a\nb\nc\nd\ne\nf\n");
+    handle_commit(&env.app(&[]), &Some("v1".to_string())).expect("commit v1");
+    let v1 = env.current_revision();
+    env.write_file("a.txt", "A\nb\nc\nd\ne\nf\n");
+    handle_commit(&env.app(&[]), &Some("v2".to_string())).expect("commit v2");
+
+    // 从旧版本开始修改：第一行与 v2 冲突，最后一行不冲突
+    env.svn(&["update", "-r", &v1]);
+    env.write_file("a.txt", "X\nb\nc\nd\ne\nF\n");
+    handle_commit(&env.app(&["Continue to commit", "Merge, Take Theirs"]), &Some("v3".to_string())).expect("commit with conflict");
+
+    assert_eq!(env.read_file("a.txt"), "A\nb\nc\nd\ne\nF\n");
+    assert_eq!(env.svn(&["status", "-q"]), "");
+}
+
+#[test]
+fn conflict_rules_match_like_gitignore() {
+    let rules = parse_conflict_rules(r#"
+[[rules]]
+pattern = "Cargo.lock"
+command = "cargo generate-lockfile"
+
+[[rules]]
+pattern = "dist/"
+resolve = "theirs"
+
+[[rules]]
+pattern = "*.lock"
+resolve = "mine"
+"#, "rules").expect("parse rules");
+
+    let resolution = |path: &str| match_conflict_rule(&rules, path).map(|rule| rule.resolution.clone());
+    assert_eq!(resolution("Cargo.lock"), Some(ConflictResolution::Command("cargo generate-lockfile".to_string())));
+    // 与 .gitignore 相同，没有 / 的规则匹配任意层级，目录规则匹配其中的文件
+    assert_eq!(resolution("crates/core/Cargo.lock"), Some(ConflictResolution::Command("cargo generate-lockfile".to_string())));
+    assert_eq!(resolution("dist\\app.js"), Some(ConflictResolution::Theirs));
+    assert_eq!(resolution("yarn.lock"), Some(ConflictResolution::Mine));
+    assert_eq!(resolution("src/main.rs"), None);
+
+    assert!(parse_conflict_rules("", "rules").expect("empty rules").is_empty());
+    for invalid in [
+        "[[rules]]\npattern = \"a\"\nresolve = \"both\"",
+        "[[rules]]\npattern = \"a\"",
+        "[[rules]]\npattern = \"a\"\nresolve = \"mine\"\ncommand = \"make\"",
+        "[[rules]]\npatern = \"a\"\nresolve = \"mine\"",
+    ] {
+        let err = parse_conflict_rules(invalid, "rules").err().expect(invalid);
+        assert!(matches!(err, AppError::Config(ref msg) if msg.starts_with("rules: ")), "{}", err);
+    }
+}
+
+#[test]
+fn conflict_rule_commands_do_not_splice_the_path() {
+    let command = conflict_shell_command("prettier --write {path} && git add {path}");
+    let script = command.last().expect("shell script");
+    assert!(!script.contains("{path}"), "{}", script);
+    assert_eq!(script.matches("WS_CONFLICT_PATH").count(), 2, "{}", script);
+}
+
+#[test]
+#[cfg(unix)]
+fn conflict_rule_commands_see_the_path_verbatim() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-conflict-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let path = dir.join("a;touch injected 'b'.txt").to_string_lossy().to_string();
+    let command = conflict_shell_command("echo regenerated > {path}");
+    let status = std::process::Command::new(&command[0]).args(&command[1..]).current_dir(&dir).env("WS_CONFLICT_PATH", &path).status().expect("run sh");
+    let written = std::fs::read_to_string(&path);
+    let injected = dir.join("injected").exists();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(status.success());
+    assert_eq!(written.expect("read regenerated file"), "regenerated\n");
+    assert!(!injected);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn conflict_rules_resolve_before_prompting() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file(".ws-conflicts.toml", "[[rules]]\npattern = \"*.lock\"\nresolve = \"theirs\"\n\n[[rules]]\npattern = \"gen.txt\"\ncommand = \"echo regenerated> {path}\"\n");
+    env.write_file("deps.lock", "v1\n");
+    env.write_file("gen.txt", "v1\n");
+    handle_commit(&env.app(&[]), &Some("v1".to_string())).expect("commit v1");
+    let v1 = env.current_revision();
+    env.write_file("deps.lock", "v2\n");
+    env.write_file("gen.txt", "v2\n");
+    handle_commit(&env.app(&[]), &Some("v2".to_string())).expect("commit v2");
+
+    env.svn(&["update", "-r", &v1]);
+    env.write_file("deps.lock", "mine\n");
+    env.write_file("gen.txt", "mine\n");
+    // 只回答 review 提示，冲突不需要回答
+    handle_commit(&env.app(&["Continue to commit"]), &Some("v3".to_string())).expect("commit with rules");
+
+    assert_eq!(env.read_file("deps.lock"), "v2\n");
+    assert_eq!(env.read_file("gen.txt").trim(), "regenerated");
+    assert_eq!(env.svn(&["status", "-q"]), "");
+}
+
+const CONFLICTED_STATUS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<status>
+<target path=".">
+<entry path="a.txt"><wc-status item="modified" props="none" revision="3"/></entry>
+<entry path="deps.lock"><wc-status item="conflicted" props="none" revision="3"/></entry>
+<entry path="conf"><wc-status item="normal" props="conflicted" revision="3"/></entry>
+<entry path="old"><wc-status item="missing" props="none" tree-conflicted="true"/></entry>
+<entry path="build"><wc-status item="obstructed" props="none"/></entry>
+</target>
+</status>"#;
+
+fn scripted_conflicts() -> ScriptedExecutor {
+    ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 3)
+        .on("svn status --xml", CONFLICTED_STATUS)
+}
+
+#[test]
+fn conflicted_files_are_read_from_svn_status() {
+    let svn = Rc::new(scripted_conflicts());
+    let app = scripted_app(&svn, &[]);
+
+    let conflicts: Vec<(String, &str)> = get_conflicted_files(&app).expect("conflicted files").into_iter().map(|item| {
+        let kind = match item.kind {
+            ConflictKind::Standard => "standard",
+            ConflictKind::TreeConflict => "tree",
+            ConflictKind::Obstructed => "obstructed",
+            ConflictKind::Incomplete => "incomplete",
+        };
+        (item.path, kind)
+    }).collect();
+    assert_eq!(conflicts, vec![
+        ("deps.lock".to_string(), "standard"),
+        ("conf".to_string(), "standard"),
+        ("old".to_string(), "tree"),
+        ("build".to_string(), "obstructed"),
+    ]);
+}
+
+#[test]
+fn conflicts_are_resolved_by_rules_then_prompts() {
+    let property_conflict = r#"<info><entry kind="dir" path="conf"><conflict type="property" operation="update"><prop-file>conf/dir_conflicts.prej</prop-file></conflict></entry></info>"#;
+    let svn = Rc::new(scripted_conflicts()
+        .on("svn resolve --accept theirs-full deps.lock", "")
+        .on("svn info --xml conf", property_conflict)
+        .on("svn resolve --accept mine-full conf", "")
+        .on("svn resolve --accept working old", "")
+        .on("svn revert -R old", "")
+        .on("svn update old", "")
+        .on("svn delete --keep-local --force build", "")
+        .on("svn add --parents --depth empty --force --force build", ""));
+    // 属性冲突保留本地版本，树冲突放弃本地修改，被占用的路径保留本地文件
+    let app = scripted_app(&svn, &["Keep My Version", "Discard My Version", "Keep My Version"]);
+    let rules = parse_conflict_rules("[[rules]]\npattern = \"*.lock\"\nresolve = \"theirs\"\n", "rules").expect("parse rules");
+
+    let conflicts = get_conflicted_files(&app).expect("conflicted files");
+    resolve_conflicts_with_rules(&app, conflicts, &rules).expect("resolve conflicts");
+
+    let calls = svn.calls();
+    let resolved = &calls[calls.iter().position(|c| c == "svn status --xml").expect("read status") + 1..];
+    assert_eq!(resolved, [
+        "svn resolve --accept theirs-full deps.lock",
+        "svn info --xml conf",
+        "svn resolve --accept mine-full conf",
+        "svn resolve --accept working old",
+        "svn revert -R old",
+        "svn update old",
+        "svn delete --keep-local --force build",
+        "svn add --parents --depth empty --force --force build",
+    ]);
+}
+
+#[test]
+fn failed_rule_commands_leave_the_conflict_for_the_prompt() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-rule-failure-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let file = |name: &str, content: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, content).expect("write conflict file");
+        path.to_string_lossy().to_string()
+    };
+    let markers = "<<<<<<< .mine\nmine\n=======\ntheirs\n>>>>>>> .r4\n";
+    let path = file("gen.txt", markers);
+    let (base, mine, theirs) = (file("gen.txt.r3", "base\n"), file("gen.txt.mine", "mine\n"), file("gen.txt.r4", "theirs\n"));
+    let info = format!(r#"<info><entry kind="file" path="{path}"><conflict type="text" operation="update"><prev-base-file>{base}</prev-base-file><prev-wc-file>{mine}</prev-wc-file><cur-base-file>{theirs}</cur-base-file></conflict></entry></info>"#);
+
+    let svn = Rc::new(ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 4)
+        .on(&format!("svn info --xml {}", path), &info)
+        .on(&format!("svn resolve --accept mine-full {}", path), ""));
+    let app = scripted_app(&svn, &["Keep My Version"]);
+    let rules = parse_conflict_rules("[[rules]]\npattern = \"gen.txt\"\ncommand = \"exit 1\"\n", "rules").expect("parse rules");
+
+    // 命令失败后冲突没有标记为已解决，工作文件恢复原样，之后照常询问
+    let conflicts = vec![ConflictItem { path: path.clone(), kind: ConflictKind::Standard }];
+    resolve_conflicts_with_rules(&app, conflicts, &rules).expect("resolve conflicts");
+    assert_eq!(std::fs::read_to_string(&path).expect("read conflicted file"), markers);
+    let calls = svn.calls();
+    let resolved = &calls[calls.iter().position(|c| c.starts_with("svn info --xml")).expect("read conflict files")..];
+    assert_eq!(resolved, [
+        format!("svn info --xml {}", path),
+        format!("svn info --xml {}", path),
+        format!("svn resolve --accept mine-full {}", path),
+    ]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,110 @@
+//! ws diff：工作副本、版本之间和分支之间的比较
+
+use std::fs;
+
+use crate::{
+    commands::{
+        project::{handle_branch, handle_commit},
+        utils_diff::{DiffTarget, build_diff, parse_unified_diff},
+    },
+    core::utils::parse_revision_range,
+    tests::harness::TestEnv,
+    ui::models::FileDiff,
+};
+
+fn find<'a>(diffs: &'a [FileDiff], path: &str) -> &'a FileDiff {
+    diffs.iter().find(|d| d.path == path).unwrap_or_else(|| panic!("no diff for {} in {:?}", path, diffs))
+}
+
+#[test]
+fn unified_diff_is_split_per_file() {
+    let text = "\
+Index: src\\a.txt
+===================================================================
+--- src/a.txt\t(revision 3)
++++ src/a.txt\t(working copy)
+@@ -1,2 +1,2 @@
+-old
++new
++++ looks like a header
+ same
+Index: b.txt
+===================================================================
+--- b.txt\t(revision 3)
++++ b.txt\t(working copy)
+@@ -1 +0,0 @@
+--- looks like a header
+";
+    let diffs = parse_unified_diff(text);
+    assert_eq!(diffs.len(), 2);
+
+    assert_eq!(diffs[0].path, "src/a.txt");
+    assert_eq!((diffs[0].added, diffs[0].removed), (2, 1));
+    assert_eq!(diffs[0].lines.len(), 9);
+
+    assert_eq!(diffs[1].path, "b.txt");
+    assert_eq!((diffs[1].added, diffs[1].removed), (0, 1));
+
+    assert!(parse_unified_diff("").is_empty());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn diff_includes_files_commit_would_add_or_delete() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file(".gitignore", "*.log\n");
+    env.write_file("a.txt", "one\ntwo\n");
+    env.write_file("gone.txt", "bye\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+    let first = env.current_revision();
+
+    assert!(build_diff(&env.app(&[]), &DiffTarget::WorkingCopy, &[]).expect("clean diff").is_empty());
+
+    env.write_file("a.txt", "one\n2\n");
+    env.write_file("new/b.txt", "b1\nb2\nb3\n");
+    env.write_file("debug.log", "log\n");
+    fs::remove_file(env.ws().join("gone.txt")).expect("remove gone.txt");
+
+    let diffs = build_diff(&env.app(&[]), &DiffTarget::WorkingCopy, &[]).expect("working copy diff");
+    assert_eq!((find(&diffs, "a.txt").added, find(&diffs, "a.txt").removed), (1, 1));
+    assert_eq!((find(&diffs, "new/b.txt").added, find(&diffs, "new/b.txt").removed), (3, 0));
+    assert_eq!((find(&diffs, "gone.txt").added, find(&diffs, "gone.txt").removed), (0, 1));
+    assert!(diffs.iter().all(|d| d.path != "debug.log"));
+
+    // 指定路径时只显示这些路径
+    let diffs = build_diff(&env.app(&[]), &DiffTarget::WorkingCopy, &["new".to_string()]).expect("diff of new/");
+    assert_eq!(diffs.iter().map(|d| d.path.as_str()).collect::<Vec<_>>(), vec!["new/b.txt"]);
+
+    handle_commit(&env.app(&[]), &Some("Update".to_string())).expect("ws commit");
+    let second = env.current_revision();
+
+    let (from, to) = parse_revision_range(&format!("{}:{}", first, second)).expect("revision range");
+    let target = DiffTarget::Revisions { from, to };
+    let diffs = build_diff(&env.app(&[]), &target, &[]).expect("revision diff");
+    for path in ["a.txt", "new/b.txt", "gone.txt"] {
+        find(&diffs, path);
+    }
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn diff_against_another_branch() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("commit on trunk");
+
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+
+    // 从 feature 到 trunk，b.txt 被删除
+    let diffs = build_diff(&env.app(&[]), &DiffTarget::Branch("trunk".to_string()), &[]).expect("diff against trunk");
+    assert_eq!(diffs.len(), 1);
+    assert_eq!((diffs[0].path.as_str(), diffs[0].removed), ("b.txt", 1));
+
+    assert!(build_diff(&env.app(&[]), &DiffTarget::Branch("missing".to_string()), &[]).is_err());
+}
@@ -0,0 +1,65 @@
+//! svn 错误码的分类和退出码
+
+use crate::core::error::{AppError, SvnErrorKind};
+
+fn failed(stderr: &str) -> AppError {
+    AppError::SvnCommandFailed {
+        command: "svn commit -m test".to_string(),
+        kind: SvnErrorKind::from_stderr(stderr),
+        _stdout: String::new(),
+        stderr: stderr.to_string(),
+    }
+}
+
+#[test]
+fn classifies_common_svn_errors() {
+    let cases = [
+        ("svn: E155011: File '/ws/a.txt' is out of date\nsvn: E160028: File '/trunk/a.txt' is out of date", SvnErrorKind::OutOfDate),
+        ("svn: E155004: Run 'svn cleanup' to remove locks (type 'svn help cleanup' for details)", SvnErrorKind::WorkingCopyLocked),
+        ("svn: E170001: Authorization failed", SvnErrorKind::AuthFailed),
+        ("svn: E155007: '/tmp' is not a working copy", SvnErrorKind::NotWorkingCopy),
+        ("svn: E155015: Aborting commit: '/ws/a.txt' remains in conflict", SvnErrorKind::Conflict),
+        ("svn: E195016: Cannot merge into mixed-revision working copy [3:5]; try updating first", SvnErrorKind::MergeNotReady),
+        ("svn: E999999: Something unexpected", SvnErrorKind::Other),
+        ("not an svn message", SvnErrorKind::Other),
+    ];
+    for (stderr, kind) in cases {
+        assert_eq!(SvnErrorKind::from_stderr(stderr), kind, "{}", stderr);
+    }
+}
+
+#[test]
+fn warning_codes_are_classified_before_generic_errors() {
+    // svn info 一个不存在的 URL
+    let stderr = "svn: warning: W170000: URL 'file:///repo/missing' non-existent in revision 3\n\nsvn: E200009: Could not display info for all targets because some targets don't exist";
+    assert_eq!(SvnErrorKind::from_stderr(stderr), SvnErrorKind::PathNotFound);
+}
+
+#[test]
+fn exit_codes_are_distinct() {
+    let kinds = [
+        SvnErrorKind::OutOfDate,
+        SvnErrorKind::WorkingCopyLocked,
+        SvnErrorKind::AuthFailed,
+        SvnErrorKind::PathNotFound,
+        SvnErrorKind::NotWorkingCopy,
+        SvnErrorKind::Conflict,
+        SvnErrorKind::MergeNotReady,
+        SvnErrorKind::Other,
+    ];
+    let mut codes: Vec<i32> = kinds.iter().map(|k| k.exit_code()).collect();
+    codes.extend([AppError::OperationCancelled.exit_code(), AppError::Validation(String::new()).exit_code()]);
+    let count = codes.len();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), count);
+    assert!(!codes.contains(&0));
+}
+
+#[test]
+fn display_includes_hint_and_stderr() {
+    let message = failed("svn: E155011: File '/ws/a.txt' is out of date").to_string();
+    assert!(message.contains("svn commit -m test"));
+    assert!(message.contains("ws pull"));
+    assert!(message.contains("E155011: File '/ws/a.txt' is out of date"));
+}
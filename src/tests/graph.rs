@@ -0,0 +1,143 @@
+//! ws log --graph 的分支图
+
+use std::rc::Rc;
+
+use crate::{
+    commands::{
+        project::{handle_branch, handle_commit, handle_push},
+        utils_graph::{build_log_graph, layout_graph},
+    },
+    tests::{harness::{TestEnv, log_entry}, scripted::{ScriptedExecutor, scripted_app}},
+    ui::models::{GraphCommit, LogEntryKind},
+};
+
+fn commit(revision: u64, branch: &str, from_branch: Option<&str>) -> GraphCommit {
+    GraphCommit {
+        entry: log_entry(revision, LogEntryKind::Commit),
+        branch: branch.to_string(),
+        from_branch: from_branch.map(str::to_string),
+    }
+}
+
+fn graph(commits: &[GraphCommit]) -> Vec<String> {
+    layout_graph(commits).into_iter().map(|line| match line.commit {
+        Some(index) => format!("{} r{}", line.graph, commits[index].entry.revision),
+        None => line.graph,
+    }).collect()
+}
+
+#[test]
+fn branches_start_from_their_source_and_merge_back() {
+    let commits = [
+        commit(15, "trunk", Some("feature")),
+        commit(14, "trunk", None),
+        commit(13, "feature", None),
+        commit(12, "feature", Some("trunk")),
+        commit(11, "trunk", None),
+    ];
+    assert_eq!(graph(&commits), vec![
+        "* r15",
+        "|-.",
+        "* | r14",
+        "| * r13",
+        "| * r12",
+        "+-'",
+        "* r11",
+    ]);
+}
+
+#[test]
+fn finished_lanes_are_reused_and_edges_cross_other_lanes() {
+    let commits = [
+        commit(20, "trunk", Some("b")),
+        commit(19, "c", Some("trunk")),
+        commit(18, "b", Some("trunk")),
+        commit(17, "trunk", Some("a")),
+        commit(16, "a", Some("trunk")),
+        commit(15, "trunk", None),
+    ];
+    assert_eq!(graph(&commits), vec![
+        "* r20",
+        "|-.",
+        "| | * r19",
+        "+-+-'",
+        "| * r18",
+        "+-'",
+        "* r17",
+        "|-.",
+        "| * r16",
+        "+-'",
+        "* r15",
+    ]);
+
+    // 来源分支不在显示范围内时不画边
+    let commits = [commit(5, "feature", Some("trunk")), commit(4, "feature", Some("missing"))];
+    assert_eq!(graph(&commits), vec!["* r5", "* r4"]);
+}
+
+#[test]
+fn merged_branches_keep_their_lane_after_deletion() {
+    let trunk_log = r#"<log>
+<logentry revision="12"><author>alice</author><date>2025-01-12T00:00:00Z</date><msg>Merge feature</msg>
+<logentry revision="9" reverse-merge="false"><paths><path action="M" kind="file">/demo/branches/feature/a.txt</path></paths><msg>Work</msg></logentry>
+</logentry>
+<logentry revision="6"><author>alice</author><date>2025-01-06T00:00:00Z</date><paths><path action="A" kind="file">/demo/trunk/a.txt</path></paths><msg>Add a</msg></logentry>
+</log>"#;
+    let feature_log = r#"<log>
+<logentry revision="9"><author>alice</author><date>2025-01-09T00:00:00Z</date><paths><path action="M" kind="file">/demo/branches/feature/a.txt</path></paths><msg>Work</msg></logentry>
+<logentry revision="7"><author>alice</author><date>2025-01-07T00:00:00Z</date><paths>
+<path action="A" kind="dir" copyfrom-path="/demo/trunk" copyfrom-rev="6">/demo/branches/feature</path>
+</paths><msg>[WS-BRANCH] Create feature</msg></logentry>
+</log>"#;
+    // feature 在 r11 合并之后删除，HEAD 上只有 trunk
+    let svn = Rc::new(ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 12)
+        .on("svn list file:///srv/svn/repo/demo/branches", "")
+        .on("svn log -v -g --xml --stop-on-copy file:///srv/svn/repo/demo/trunk", trunk_log)
+        .on("svn log -v -g --xml --stop-on-copy -r 9:0 file:///srv/svn/repo/demo/branches/feature@9", feature_log));
+
+    let commits = build_log_graph(&scripted_app(&svn, &[]), None).expect("ws log --graph");
+    assert_eq!(graph(&commits), vec![
+        "* r12",
+        "|-.",
+        "| * r9",
+        "| * r7",
+        "+-'",
+        "* r6",
+    ]);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn graph_reads_all_branches_of_the_project() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+    handle_push(&env.app(&[]), Some("trunk")).expect("ws push trunk");
+    handle_commit(&env.app(&[]), &Some("Merge feature".to_string())).expect("commit merge");
+
+    let commits = build_log_graph(&env.app(&[]), None).expect("ws log --graph");
+    let revisions: Vec<u64> = commits.iter().map(|c| c.entry.revision).collect();
+    assert!(revisions.windows(2).all(|w| w[0] > w[1]), "{:?}", revisions);
+
+    let creation = commits.iter().find(|c| matches!(c.entry.kind, LogEntryKind::Branch { .. })).expect("branch creation");
+    assert_eq!((creation.branch.as_str(), creation.from_branch.as_deref()), ("feature", Some("trunk")));
+    let merge = commits.iter().find(|c| matches!(c.entry.kind, LogEntryKind::Merge { .. })).expect("merge");
+    assert_eq!((merge.branch.as_str(), merge.from_branch.as_deref()), ("trunk", Some("feature")));
+    assert!(merge.entry.is_current);
+
+    let limited = build_log_graph(&env.app(&[]), Some(2)).expect("ws log --graph -n 2");
+    assert_eq!(limited.len(), 2);
+
+    // 删除合并过的分支后，它的提交和合并的边仍然保留
+    handle_branch(&env.app(&[]), Some("feature".to_string()), false, true, false, None).expect("ws branch -d feature");
+    let commits = build_log_graph(&env.app(&[]), None).expect("ws log --graph after delete");
+    assert!(commits.iter().any(|c| c.branch == "feature" && c.entry.message == "Add b"));
+    let merge = commits.iter().find(|c| matches!(c.entry.kind, LogEntryKind::Merge { .. })).expect("merge");
+    assert_eq!(merge.from_branch.as_deref(), Some("feature"));
+}
@@ -0,0 +1,261 @@
+//! 测试环境：临时仓库、临时 $HOME（.ws_store 所在位置）和一个空的工作目录
+//!
+//! 指令依赖进程的当前目录和环境变量，所以同一时间只允许一个 TestEnv 存在
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    rc::Rc,
+    sync::{Mutex, MutexGuard, atomic::{AtomicUsize, Ordering}},
+    time::Duration,
+};
+
+use crate::{
+    commands::{utils_file::get_lock_file_path, workspace::handle_new},
+    core::{app::App, config::Config, context::SvnContext, svn::svn_svnmucc, svn_executor::{CommandExecutor, SvnExecutor}, svn_repo::svnadmin_create},
+    ui::{display::AppUI, models::{LogEntry, LogEntryKind}},
+};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+static ENV_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TestEnv {
+    root: PathBuf,
+    repo_path: PathBuf,
+    repo_url: String,
+    ws: PathBuf,
+    old_cwd: PathBuf,
+    /// 测试期间修改的环境变量及其原值
+    old_vars: Vec<(&'static str, Option<OsString>)>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    /// 创建测试环境；没有安装 svn 命令行工具时直接失败，不会静默跳过
+    /// - 端到端测试都标记为 #[ignore]，用 cargo test -- --ignored 运行
+    pub fn new() -> Self {
+        let guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let missing: Vec<&str> = ["svn", "svnadmin", "svnmucc"].into_iter().filter(|tool| !is_tool_available(tool)).collect();
+        assert!(missing.is_empty(), "end-to-end tests need {} on PATH", missing.join(", "));
+
+        let id = ENV_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let root = env::temp_dir().join(format!("ws-tool-test-{}-{}", std::process::id(), id));
+        if root.exists() {
+            fs::remove_dir_all(&root).expect("remove stale test root");
+        }
+        fs::create_dir_all(&root).expect("create test root");
+        let root = fs::canonicalize(&root).expect("canonicalize test root");
+
+        let repo_path = root.join("repo");
+        let repo_url = format!("file://{}", repo_path.display());
+        svnadmin_create(&CommandExecutor, repo_path.to_str().expect("utf-8 repo path")).expect("svnadmin create");
+        svn_svnmucc(&CommandExecutor, &["mkdir", &format!("{}/.ws_empty", repo_url), "-m", "Add default .ws_empty folder"]).expect("create .ws_empty");
+
+        let ws = root.join("ws");
+        fs::create_dir_all(&ws).expect("create workspace");
+
+        let old_cwd = env::current_dir().expect("current dir");
+        // 用户配置和 WS_TOOL_* 也指向临时目录或清空，避免读到本机的配置
+        let vars: [(&str, Option<&Path>); 5] = [
+            ("HOME", Some(&root)),
+            ("PWD", Some(&ws)),
+            ("XDG_CONFIG_HOME", None),
+            ("WS_TOOL_STORE_ROOT", None),
+            ("WS_TOOL_REPO_ROOT", None),
+        ];
+        let old_vars = vars.iter().map(|(key, _)| (*key, env::var_os(key))).collect();
+
+        // SAFETY: 所有修改环境变量的测试都持有 ENV_LOCK
+        unsafe {
+            for (key, value) in vars {
+                match value {
+                    Some(value) => env::set_var(key, value),
+                    None => env::remove_var(key),
+                }
+            }
+        }
+        env::set_current_dir(&ws).expect("enter workspace");
+
+        TestEnv { root, repo_path, repo_url, ws, old_cwd, old_vars, _guard: guard }
+    }
+
+    /// 临时根目录，也是 $HOME 和工作目录的上级目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 工作目录，检出项目后是指向 .ws_store 的软链接
+    pub fn ws(&self) -> &Path {
+        &self.ws
+    }
+
+    pub fn repo_url(&self) -> &str {
+        &self.repo_url
+    }
+
+    /// .ws_store/{repo_name}
+    pub fn vault_root(&self) -> PathBuf {
+        self.root.join(".ws_store").join("repo")
+    }
+
+    /// 工作目录中还没有项目时使用的 App，相当于 App::default
+    pub fn outside_app(&self, answers: &[&str]) -> App {
+        let svn: Rc<dyn SvnExecutor> = Rc::new(CommandExecutor);
+        let config = Config::load().expect("load config");
+        let svn_ctx = SvnContext::for_repo(svn.clone(), &self.repo_url, &self.repo_path, config);
+        App::from_parts(AppUI::with_answers(answers), svn, svn_ctx)
+    }
+
+    /// 工作目录中还没有项目时，通过 --repo 指定仓库的 App
+    pub fn outside_app_with_repo(&self, repo: &str, answers: &[&str]) -> App {
+        let mut app = App::default_with_executor(Rc::new(CommandExecutor), Some(repo)).expect("init app with repo");
+        app.ui = AppUI::with_answers(answers);
+        app
+    }
+
+    /// 用 svnserve 在本机提供临时仓库，返回 svn:// 仓库 URL
+    pub fn serve(&self) -> SvnServe {
+        assert!(is_tool_available("svnserve"), "end-to-end tests need svnserve on PATH");
+
+        fs::write(self.repo_path.join("conf").join("svnserve.conf"), "[general]\nanon-access = write\n").expect("write svnserve.conf");
+        let port = TcpListener::bind("127.0.0.1:0").expect("bind free port").local_addr().expect("local addr").port();
+        let child = Command::new("svnserve")
+            .args(["-d", "--foreground", "--listen-host", "127.0.0.1", "--listen-port", &port.to_string(), "-r"])
+            .arg(&self.root)
+            .spawn()
+            .expect("start svnserve");
+        let server = SvnServe { child, url: format!("svn://127.0.0.1:{}/repo", port) };
+
+        for _ in 0..50 {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return server;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("svnserve did not start on port {}", port);
+    }
+
+    /// 在工作目录中重新初始化 App，每条指令执行前都应重新获取
+    pub fn app(&self, answers: &[&str]) -> App {
+        env::set_current_dir(&self.ws).expect("enter workspace");
+        let mut app = App::with_executor(Rc::new(CommandExecutor)).expect("init app in workspace");
+        app.ui = AppUI::with_answers(answers);
+        app
+    }
+
+    /// 在空的工作目录中创建项目并检出
+    pub fn new_project(&self, project_name: &str) {
+        handle_new(&self.outside_app(&[]), project_name).expect("ws new");
+    }
+
+    /// 读取项目的 lock 计数，lock 文件不存在时为 0
+    pub fn lock_value(&self, project_name: &str) -> u64 {
+        let lock_file = get_lock_file_path(&self.vault_root(), project_name).expect("lock file path");
+        fs::read_to_string(lock_file).ok().and_then(|s| s.trim().parse().ok()).unwrap_or(0)
+    }
+
+    pub fn write_file(&self, rel_path: &str, content: &str) {
+        let path = self.ws.join(rel_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent dir");
+        }
+        fs::write(path, content).expect("write file");
+    }
+
+    pub fn read_file(&self, rel_path: &str) -> String {
+        fs::read_to_string(self.ws.join(rel_path)).expect("read file")
+    }
+
+    /// 直接执行 svn 命令，返回 stdout
+    pub fn svn(&self, args: &[&str]) -> String {
+        env::set_current_dir(&self.ws).expect("enter workspace");
+        let output = CommandExecutor.execute("svn", args).expect("svn command");
+        String::from_utf8_lossy(&output).trim().to_string()
+    }
+
+    /// 仓库中某个路径下所有提交信息，从新到旧
+    pub fn log_messages(&self, rel_url: &str) -> Vec<String> {
+        let xml = self.svn(&["log", "--xml", &self.url(rel_url)]);
+        let doc = roxmltree::Document::parse(&xml).expect("parse svn log xml");
+        doc.descendants()
+            .filter(|n| n.has_tag_name("msg"))
+            .map(|n| n.text().unwrap_or("").to_string())
+            .collect()
+    }
+
+    /// 仓库中某个路径是否存在
+    pub fn url_exists(&self, rel_url: &str) -> bool {
+        CommandExecutor.execute("svn", &["info", &self.url(rel_url)]).is_ok()
+    }
+
+    /// 仓库中某个路径的 URL，rel_url 为空时是仓库根目录
+    pub fn url(&self, rel_url: &str) -> String {
+        if rel_url.is_empty() {
+            self.repo_url.clone()
+        } else {
+            format!("{}/{}", self.repo_url, rel_url)
+        }
+    }
+
+    pub fn current_revision(&self) -> String {
+        self.svn(&["info", "--show-item", "last-changed-revision"])
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.old_cwd);
+        // SAFETY: 仍然持有 ENV_LOCK
+        unsafe {
+            for (key, value) in &self.old_vars {
+                match value {
+                    Some(value) => env::set_var(key, value),
+                    None => env::remove_var(key),
+                }
+            }
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// 测试结束时停止的 svnserve 进程
+pub struct SvnServe {
+    child: Child,
+    url: String,
+}
+
+impl SvnServe {
+    /// 仓库的 svn:// URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for SvnServe {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn is_tool_available(tool: &str) -> bool {
+    Command::new(tool).arg("--version").output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// 用于显示和过滤测试的日志条目，提交信息为 r{revision}
+pub fn log_entry(revision: u64, kind: LogEntryKind) -> LogEntry {
+    LogEntry {
+        revision,
+        date: "2025-01-02T03:04:05.000000Z".to_string(),
+        author: "alice".to_string(),
+        message: format!("r{}", revision),
+        kind,
+        is_current: false,
+        tags: Vec::new(),
+    }
+}
@@ -0,0 +1,235 @@
+//! ws log 的过滤条件和 ws show
+
+use std::rc::Rc;
+
+use chrono::{Local, TimeZone};
+
+use crate::{
+    commands::{
+        models::LogFilter,
+        project::{build_log_entries, build_revision_detail, handle_branch, handle_commit, handle_push, handle_revert},
+        utils_log::{LogMatcher, normalize_log_paths, parse_log_entry},
+    },
+    core::{error::AppError, utils::{Revision, parse_date_arg}},
+    tests::{harness::{TestEnv, log_entry}, scripted::{ScriptedExecutor, scripted_app}},
+    ui::models::{LogEntry, LogEntryKind},
+};
+
+fn entry(date: &str, author: &str, message: &str) -> LogEntry {
+    LogEntry { date: date.to_string(), author: author.to_string(), message: message.to_string(), ..log_entry(1, LogEntryKind::Commit) }
+}
+
+#[test]
+fn dates_can_be_relative_or_absolute() {
+    // 避开夏令时切换的日期
+    let now = Local.with_ymd_and_hms(2025, 6, 15, 12, 0, 0).unwrap();
+    assert_eq!(parse_date_arg("2w", now).unwrap(), Local.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap());
+    assert_eq!(parse_date_arg("3d", now).unwrap(), Local.with_ymd_and_hms(2025, 6, 12, 12, 0, 0).unwrap());
+    assert_eq!(parse_date_arg("12h", now).unwrap(), Local.with_ymd_and_hms(2025, 6, 15, 0, 0, 0).unwrap());
+    assert_eq!(parse_date_arg("2025-03-01", now).unwrap(), Local.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap());
+    assert_eq!(parse_date_arg("{2025-03-01 18:30}", now).unwrap(), Local.with_ymd_and_hms(2025, 3, 1, 18, 30, 0).unwrap());
+
+    for invalid in ["", "w", "-2w", "2x", "yesterday", "2025-02-30"] {
+        assert!(matches!(parse_date_arg(invalid, now), Err(AppError::Validation(_))), "{:?} should not parse", invalid);
+    }
+}
+
+#[test]
+fn matcher_checks_author_message_and_dates() {
+    let now = Local::now();
+    let filter = LogFilter {
+        author: Some("Alice".to_string()),
+        grep: Some("^fix".to_string()),
+        since: Some("2025-03-01".to_string()),
+        until: Some("2025-04-01".to_string()),
+        ..Default::default()
+    };
+    let matcher = LogMatcher::new(&filter, now).unwrap();
+    assert!(matcher.filters_entries());
+
+    let date = "2025-03-10T08:00:00.000000Z";
+    assert!(matcher.matches(&entry(date, "alice.smith", "fix crash")));
+    assert!(!matcher.matches(&entry(date, "bob", "fix crash")));
+    assert!(!matcher.matches(&entry(date, "alice", "add feature, fix crash")));
+    assert!(!matcher.matches(&entry("2025-02-10T08:00:00.000000Z", "alice", "fix crash")));
+    assert!(!matcher.matches(&entry("2025-04-10T08:00:00.000000Z", "alice", "fix crash")));
+
+    // 只有起始时间和路径时可以直接交给 svn log --limit
+    let filter = LogFilter { since: Some("2w".to_string()), paths: vec!["src/".to_string()], ..Default::default() };
+    let matcher = LogMatcher::new(&filter, now).unwrap();
+    assert!(!matcher.filters_entries());
+    let query = matcher.query(&filter, Some(10));
+    assert_eq!(query.limit, Some(10));
+    assert_eq!(query.paths, vec!["src".to_string()]);
+    assert!(query.since.is_some());
+
+    let filter = LogFilter { grep: Some("(".to_string()), ..Default::default() };
+    assert!(matches!(LogMatcher::new(&filter, now), Err(AppError::Validation(_))));
+}
+
+#[test]
+fn log_paths_are_relative_to_the_project_root() {
+    let paths = ["./src/".to_string(), "docs\\guide".to_string(), ".".to_string()];
+    assert_eq!(normalize_log_paths(&paths), vec!["src".to_string(), "docs/guide".to_string()]);
+}
+
+#[test]
+fn log_entries_are_classified_by_ws_conventions() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<log>
+<logentry revision="9"><author>alice</author><date>2026-01-09T00:00:00Z</date><msg>Fix typo</msg></logentry>
+<logentry revision="8"><author>alice</author><date>2026-01-08T00:00:00Z</date><msg>Merge feature</msg>
+<logentry revision="6" reverse-merge="false"><paths><path action="M" kind="file">/demo/branches/feature/a.txt</path></paths><msg>work</msg></logentry>
+</logentry>
+<logentry revision="7"><author>alice</author><date>2026-01-07T00:00:00Z</date><msg>[WS-PICK] r6 from feature
+
+r6 work</msg>
+<logentry revision="6" reverse-merge="false"><paths><path action="M" kind="file">/demo/branches/feature/a.txt</path></paths><msg>work</msg></logentry>
+</logentry>
+<logentry revision="6"><author>alice</author><date>2026-01-06T00:00:00Z</date><msg>[WS-ROLLBACK] 2 path(s) from r2
+
+docs, notes
+src</msg></logentry>
+<logentry revision="5"><author>alice</author><date>2026-01-05T00:00:00Z</date><msg>[WS-UNDO] r3,r4
+
+r3 one
+r4 two</msg></logentry>
+<logentry revision="4"><author>alice</author><date>2026-01-04T00:00:00Z</date><msg>[WS-ROLLBACK] tags/rollback-4</msg></logentry>
+<logentry revision="3"><author>alice</author><date>2026-01-03T00:00:00Z</date><msg>[WS-BRANCH] Create feature</msg>
+<paths><path action="A" kind="dir" copyfrom-path="/demo/trunk" copyfrom-rev="2">/demo/branches/feature</path></paths></logentry>
+<logentry revision="2"><author>alice</author><date>2026-01-02T00:00:00Z</date><msg>[WS-INIT-GITIGNORE] Init</msg></logentry>
+<logentry revision="1"><author>alice</author><date>2026-01-01T00:00:00Z</date><msg>[WS-INIT] demo</msg></logentry>
+</log>"#;
+    let tag_log = r#"<log><logentry revision="4"><paths><path action="A" kind="dir" copyfrom-path="/demo/trunk" copyfrom-rev="2">/demo/tags/rollback-4</path></paths><msg>tag</msg></logentry></log>"#;
+    let svn = Rc::new(ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 9)
+        .on("svn log -v --xml --stop-on-copy --limit 1 file:///srv/svn/repo/demo/tags/rollback-4", tag_log));
+    let app = scripted_app(&svn, &[]);
+
+    let doc = roxmltree::Document::parse(xml).expect("parse log");
+    let entries: Vec<(u64, LogEntryKind, bool)> = doc.root_element().children().filter(|n| n.has_tag_name("logentry"))
+        .filter_map(|entry| parse_log_entry(&app, entry, "trunk", Some(&Revision::Number(9))).expect("classify entry"))
+        .map(|entry| (entry.revision, entry.kind, entry.is_current))
+        .collect();
+    assert_eq!(entries, vec![
+        (9, LogEntryKind::Commit, true),
+        (8, LogEntryKind::Merge { source: "feature".to_string() }, false),
+        (7, LogEntryKind::Pick { source: "feature".to_string(), revisions: vec![6] }, false),
+        (6, LogEntryKind::Rollback { source_revision: Some(2), paths: vec!["docs, notes".to_string(), "src".to_string()] }, false),
+        (5, LogEntryKind::Undo { revisions: vec![3, 4] }, false),
+        (4, LogEntryKind::Rollback { source_revision: Some(2), paths: Vec::new() }, false),
+        (3, LogEntryKind::Branch { source: "trunk".to_string(), source_revision: 2 }, false),
+        (2, LogEntryKind::Init { project: "demo".to_string() }, false),
+    ]);
+}
+
+/// 工作副本在 feature 上，feature 在 r5 由 old 改名而来，old 最后的版本是 r4
+fn scripted_renamed_branch(since_revision: u64) -> ScriptedExecutor {
+    let branches = "file:///srv/svn/repo/demo/branches";
+    let empty_log = r#"<?xml version="1.0" encoding="UTF-8"?><log></log>"#;
+    let renamed = r#"<log><logentry revision="5"><paths>
+<path action="A" kind="dir" copyfrom-path="/demo/branches/old" copyfrom-rev="4">/demo/branches/feature</path>
+<path action="D" kind="dir">/demo/branches/old</path>
+</paths><msg>[WS-BRANCH] Rename old to feature</msg></logentry></log>"#;
+    let created = r#"<log><logentry revision="3"><paths>
+<path action="A" kind="dir" copyfrom-path="/demo/trunk" copyfrom-rev="2">/demo/branches/old</path>
+</paths><msg>[WS-BRANCH] Create old</msg></logentry></log>"#;
+    ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "branches/feature", 9)
+        .on(&format!("svn log -v -g --xml --stop-on-copy --limit 100 -r HEAD:{{2026-01-05T00:00:00}} {}/feature", branches), empty_log)
+        .on(&format!("svn log -v --xml --stop-on-copy --limit 1 -r 1:HEAD {}/feature", branches), renamed)
+        .on(&format!("svn log -v --xml --stop-on-copy --limit 1 -r 1:4 {}/old@4", branches), created)
+        .on("svn info --show-item revision -r {2026-01-05T00:00:00} file:///srv/svn/repo", &since_revision.to_string())
+        .on(&format!("svn log -v -g --xml --stop-on-copy --limit 100 -r 4:{{2026-01-05T00:00:00}} {}/old@4", branches), empty_log)
+}
+
+#[test]
+fn log_since_skips_branch_names_renamed_before_the_date() {
+    let filter = LogFilter { since: Some("2026-01-05".to_string()), ..Default::default() };
+    let old_log = "svn log -v -g --xml --stop-on-copy --limit 100 -r 4:{2026-01-05T00:00:00} file:///srv/svn/repo/demo/branches/old@4";
+
+    // 改名在 --since 之后，还要读取旧名称上的日志
+    let svn = Rc::new(scripted_renamed_branch(3));
+    build_log_entries(&scripted_app(&svn, &[]), false, &filter).expect("log since before rename");
+    assert!(svn.calls().iter().any(|c| c == old_log));
+
+    // 改名在 --since 之前，旧名称上没有要显示的日志
+    let svn = Rc::new(scripted_renamed_branch(6));
+    build_log_entries(&scripted_app(&svn, &[]), false, &filter).expect("log since after rename");
+    assert!(!svn.calls().iter().any(|c| c == old_log));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn log_filters_compose_with_ws_kinds() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("src/a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("fix: src a".to_string())).expect("ws commit");
+    env.write_file("docs/b.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("fix: docs b".to_string())).expect("ws commit");
+    env.write_file("src/a.txt", "two\n");
+    handle_commit(&env.app(&[]), &Some("feature: src a".to_string())).expect("ws commit");
+
+    let messages = |filter: LogFilter| -> Vec<String> {
+        build_log_entries(&env.app(&[]), false, &filter).expect("ws log").into_iter().map(|e| e.message).collect()
+    };
+
+    let filter = LogFilter { grep: Some("fix".to_string()), since: Some("1d".to_string()), paths: vec!["src/".to_string()], ..Default::default() };
+    assert_eq!(messages(filter), vec!["fix: src a".to_string()]);
+
+    let filter = LogFilter { limit: Some(2), ..Default::default() };
+    assert_eq!(messages(filter), vec!["feature: src a".to_string(), "fix: docs b".to_string()]);
+
+    assert!(messages(LogFilter { author: Some("nobody-here".to_string()), ..Default::default() }).is_empty());
+    assert!(messages(LogFilter { until: Some("1w".to_string()), ..Default::default() }).is_empty());
+
+    // 过滤在识别种类之后进行
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    let entries = build_log_entries(&env.app(&[]), false, &LogFilter { grep: Some("^\\[WS-BRANCH\\]".to_string()), ..Default::default() }).expect("ws log");
+    assert_eq!(entries.len(), 1);
+    assert!(matches!(entries[0].kind, LogEntryKind::Branch { .. }));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn show_decodes_ws_kinds_and_changed_paths() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a\n\nWith a longer description".to_string())).expect("ws commit");
+
+    let detail = build_revision_detail(&env.app(&[]), "HEAD").expect("ws show HEAD");
+    assert_eq!(detail.entry.kind, LogEntryKind::Commit);
+    assert_eq!(detail.entry.message, "Add a\n\nWith a longer description");
+    assert!(detail.entry.is_current);
+    assert!(detail.paths.iter().any(|p| p.action == "A" && p.path == "/demo/trunk/a.txt"), "{:?}", detail.paths);
+    assert_eq!(detail.diff.len(), 1);
+    assert_eq!((detail.diff[0].path.as_str(), detail.diff[0].added), ("a.txt", 1));
+
+    // 合并提交显示来源分支和被合并的版本
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+    let feature_rev = env.current_revision().parse::<u64>().expect("revision number");
+
+    let detail = build_revision_detail(&env.app(&[]), "HEAD~1").expect("ws show branch creation");
+    assert!(matches!(detail.entry.kind, LogEntryKind::Branch { ref source, .. } if source == "trunk"));
+    assert!(detail.paths.iter().any(|p| p.copy_from.as_deref() == Some("/demo/trunk")), "{:?}", detail.paths);
+
+    handle_push(&env.app(&[]), Some("trunk")).expect("ws push trunk");
+    handle_commit(&env.app(&[]), &Some("Merge feature".to_string())).expect("commit merge");
+    let detail = build_revision_detail(&env.app(&[]), "HEAD").expect("ws show merge");
+    assert_eq!(detail.entry.kind, LogEntryKind::Merge { source: "feature".to_string() });
+    assert!(detail.merged_revisions.contains(&feature_rev), "{:?}", detail.merged_revisions);
+
+    // 还原提交显示还原到的版本
+    let first = build_log_entries(&env.app(&[]), false, &LogFilter::default()).expect("ws log").last().expect("first entry").revision;
+    handle_revert(&env.app(&[]), &format!("r{}", first), &[]).expect("ws revert");
+    let detail = build_revision_detail(&env.app(&[]), "HEAD").expect("ws show rollback");
+    assert_eq!(detail.entry.kind, LogEntryKind::Rollback { source_revision: Some(first), paths: Vec::new() });
+
+    assert!(build_revision_detail(&env.app(&[]), "feature@HEAD").is_ok());
+}
@@ -0,0 +1,28 @@
+//! ### 端到端测试
+//!
+//! 在临时的 file:// 仓库上完整地执行各个指令，
+//! 用于发现 [WS-*] 提交信息约定和 lock 计数的回归
+//!
+//! 端到端测试标记为 #[ignore]，用 cargo test -- --include-ignored 运行，缺少 svn 时失败
+//! 不依赖 svn 的部分（如交互策略）直接测试，需要 svn 输出的部分使用 ScriptedExecutor 预设输出
+
+mod branch;
+mod commit;
+mod config;
+mod conflict;
+mod diff;
+mod errors;
+mod graph;
+mod harness;
+mod log;
+mod output;
+mod pick;
+mod policy;
+mod remote;
+mod rename;
+mod revert;
+mod revision;
+mod scripted;
+mod stash;
+mod tag;
+mod workflow;
@@ -0,0 +1,114 @@
+//! --format json/jsonl 输出的记录结构
+
+use serde_json::json;
+
+use crate::{commands::models::{BranchInfo, TagInfo}, tests::harness::log_entry, ui::models::{ChangedPath, FileDiff, LogEntryKind, ProjectInfo, RevisionDetail}};
+
+#[test]
+fn log_entry_is_flat_record_with_kind() {
+    let value = serde_json::to_value(log_entry(7, LogEntryKind::Commit)).unwrap();
+    assert_eq!(value, json!({
+        "revision": 7,
+        "date": "2025-01-02T03:04:05.000000Z",
+        "author": "alice",
+        "message": "r7",
+        "kind": "commit",
+        "is_current": false,
+    }));
+}
+
+#[test]
+fn log_entry_kinds_carry_sources() {
+    let merge = serde_json::to_value(log_entry(8, LogEntryKind::Merge { source: "feature".to_string() })).unwrap();
+    assert_eq!(merge["kind"], "merge");
+    assert_eq!(merge["source"], "feature");
+
+    let branch = serde_json::to_value(log_entry(9, LogEntryKind::Branch { source: "trunk".to_string(), source_revision: 5 })).unwrap();
+    assert_eq!(branch["kind"], "branch");
+    assert_eq!(branch["source"], "trunk");
+    assert_eq!(branch["source_revision"], 5);
+
+    let rollback = serde_json::to_value(log_entry(10, LogEntryKind::Rollback { source_revision: Some(3), paths: Vec::new() })).unwrap();
+    assert_eq!(rollback["kind"], "rollback");
+    assert_eq!(rollback["source_revision"], 3);
+
+    let init = serde_json::to_value(log_entry(2, LogEntryKind::Init { project: "demo".to_string() })).unwrap();
+    assert_eq!(init["kind"], "init");
+    assert_eq!(init["project"], "demo");
+}
+
+#[test]
+fn project_info_lists_branches() {
+    let active = ProjectInfo {
+        name: "demo".to_string(),
+        is_deleted: false,
+        is_current: true,
+        branches: Some(vec![
+            BranchInfo { branch_name: "trunk".to_string(), is_current_branch: true, ahead: None, behind: None },
+            BranchInfo { branch_name: "feature".to_string(), is_current_branch: false, ahead: Some(2), behind: Some(0) },
+        ]),
+        tags: Some(vec![TagInfo {
+            name: "v1".to_string(),
+            source: Some("trunk".to_string()),
+            source_revision: Some(3),
+            revision: 4,
+            date: "2025-01-02T03:04:05.000000Z".to_string(),
+            is_rollback: false,
+            is_current: false,
+        }]),
+    };
+    assert_eq!(serde_json::to_value(active).unwrap(), json!({
+        "name": "demo",
+        "is_deleted": false,
+        "is_current": true,
+        "branches": [
+            { "name": "trunk", "is_current": true },
+            { "name": "feature", "is_current": false, "ahead": 2, "behind": 0 },
+        ],
+        "tags": [{
+            "name": "v1",
+            "source": "trunk",
+            "source_revision": 3,
+            "revision": 4,
+            "date": "2025-01-02T03:04:05.000000Z",
+            "is_rollback": false,
+            "is_current": false,
+        }],
+    }));
+
+    let deleted = ProjectInfo { name: "old".to_string(), is_deleted: true, is_current: false, branches: None, tags: None };
+    assert_eq!(serde_json::to_value(deleted).unwrap(), json!({ "name": "old", "is_deleted": true, "is_current": false }));
+}
+
+#[test]
+fn revision_detail_extends_log_entry() {
+    let mut diff = FileDiff::new("a.txt");
+    for line in ["Index: a.txt", "@@ -1 +1 @@", "-old", "+new"] {
+        diff.push_line(line);
+    }
+    let detail = RevisionDetail {
+        entry: log_entry(12, LogEntryKind::Merge { source: "feature".to_string() }),
+        merged_revisions: vec![10, 11],
+        paths: vec![ChangedPath {
+            action: "A".to_string(),
+            path: "/demo/trunk/b.txt".to_string(),
+            copy_from: Some("/demo/branches/feature/b.txt".to_string()),
+            copy_from_revision: Some(11),
+        }],
+        diff: vec![diff],
+    };
+
+    let value = serde_json::to_value(detail).unwrap();
+    assert_eq!(value["revision"], 12);
+    assert_eq!(value["kind"], "merge");
+    assert_eq!(value["source"], "feature");
+    assert_eq!(value["merged_revisions"], json!([10, 11]));
+    assert_eq!(value["paths"][0], json!({
+        "action": "A",
+        "path": "/demo/trunk/b.txt",
+        "copy_from": "/demo/branches/feature/b.txt",
+        "copy_from_revision": 11,
+    }));
+    assert_eq!(value["diff"][0]["path"], "a.txt");
+    assert_eq!((value["diff"][0]["added"].clone(), value["diff"][0]["removed"].clone()), (json!(1), json!(1)));
+}
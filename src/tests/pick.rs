@@ -0,0 +1,58 @@
+//! ws pick：只把其他分支上选中的版本合并到当前分支
+
+use crate::{
+    commands::{
+        project::{build_log_entries, handle_branch, handle_commit, handle_pick},
+        utils_pick::{PickedRevision, parse_pick_message, pick_message},
+        workspace::handle_switch,
+    },
+    core::error::AppError,
+    tests::harness::TestEnv,
+    ui::models::LogEntryKind,
+};
+
+#[test]
+fn pick_message_records_the_source_revisions() {
+    let picked = [
+        PickedRevision { revision: 12, message: "Fix crash\n\nDetails".to_string() },
+        PickedRevision { revision: 15, message: "Fix typo".to_string() },
+    ];
+    let message = pick_message("trunk", &picked);
+    assert_eq!(message, "[WS-PICK] r12,r15 from trunk\n\nr12 Fix crash\nr15 Fix typo");
+    assert_eq!(parse_pick_message(&message), Some(("trunk".to_string(), vec![12, 15])));
+
+    assert_eq!(parse_pick_message("[WS-PICK] r7 from release-1.0"), Some(("release-1.0".to_string(), vec![7])));
+    assert_eq!(parse_pick_message("[WS-PICK] latest from trunk"), None);
+    assert_eq!(parse_pick_message("Pick r7 from trunk"), None);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn picked_revisions_are_committed_and_shown_in_the_log() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    handle_branch(&env.app(&[]), Some("release".to_string()), true, false, false, None).expect("ws branch release");
+
+    handle_switch(&env.app(&[]), None, Some("trunk".to_string())).expect("ws switch trunk");
+    env.write_file("a.txt", "fixed\n");
+    handle_commit(&env.app(&[]), &Some("Fix a".to_string())).expect("commit fix");
+    let fix = env.current_revision().trim().to_string();
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit feature");
+
+    handle_switch(&env.app(&[]), None, Some("release".to_string())).expect("ws switch release");
+    let err = handle_pick(&env.app(&[]), "HEAD", None).unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+
+    // 来源分支由版本更改的路径确定
+    handle_pick(&env.app(&[]), &fix, None).expect("ws pick");
+    assert_eq!(env.read_file("a.txt"), "fixed\n");
+    assert!(!env.ws().join("b.txt").exists());
+
+    let log = build_log_entries(&env.app(&[]), false, &Default::default()).expect("ws log");
+    let fix: u64 = fix.parse().unwrap();
+    assert_eq!(log[0].kind, LogEntryKind::Pick { source: "trunk".to_string(), revisions: vec![fix] });
+}
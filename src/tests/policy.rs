@@ -0,0 +1,131 @@
+//! 交互策略：参数解析，以及 AppUI 在打开终端控件之前如何使用策略
+
+use crate::{
+    core::error::AppError,
+    ui::{display::AppUI, policy::{ConflictPolicy, DirtyPolicy, PromptChoice, PromptKind, PromptPolicy}},
+};
+
+const DIRTY_CHOICES: [(PromptChoice, &str); 5] = [
+    (PromptChoice::Commit, "Commit changes and Continue in current branch"),
+    (PromptChoice::Branch, "Save changes to a new branch (Create a new branch and commit changes there)"),
+    (PromptChoice::Stash, "Stash changes and Continue (Restore them later with ws stash pop)"),
+    (PromptChoice::Discard, "Discard changes and Continue (Delete all changes!)"),
+    (PromptChoice::Other, "Cancel operation"),
+];
+
+fn ui_with(policy: PromptPolicy) -> AppUI {
+    let mut ui = AppUI::new();
+    ui.set_policy(policy);
+    ui
+}
+
+fn non_interactive() -> PromptPolicy {
+    PromptPolicy { non_interactive: true, ..Default::default() }
+}
+
+#[test]
+fn parse_on_dirty() {
+    assert_eq!(DirtyPolicy::parse("commit"), Ok(DirtyPolicy::Commit));
+    assert_eq!(DirtyPolicy::parse("discard"), Ok(DirtyPolicy::Discard));
+    assert_eq!(DirtyPolicy::parse("abort"), Ok(DirtyPolicy::Abort));
+    assert_eq!(DirtyPolicy::parse("branch:wip"), Ok(DirtyPolicy::Branch("wip".to_string())));
+    assert!(DirtyPolicy::parse("branch:").is_err());
+    assert_eq!(DirtyPolicy::parse("stash"), Ok(DirtyPolicy::Stash));
+    assert!(DirtyPolicy::parse("shelve").is_err());
+}
+
+#[test]
+fn parse_conflicts() {
+    assert_eq!(ConflictPolicy::parse("mine"), Ok(ConflictPolicy::Mine));
+    assert_eq!(ConflictPolicy::parse("theirs"), Ok(ConflictPolicy::Theirs));
+    assert_eq!(ConflictPolicy::parse("abort"), Ok(ConflictPolicy::Abort));
+    assert!(ConflictPolicy::parse("both").is_err());
+}
+
+#[test]
+fn on_dirty_selects_matching_option() {
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Discard), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES.to_vec()).unwrap(), 3);
+
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Stash), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES.to_vec()).unwrap(), 2);
+
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Branch("wip".to_string())), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES.to_vec()).unwrap(), 1);
+    assert_eq!(ui.input(PromptKind::BranchName, "branch").unwrap(), "wip");
+    // 分支名来自策略，不再重试
+    assert!(!ui.selector_yes_or_no(PromptKind::Retry, "retry").unwrap());
+
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Abort), ..non_interactive() });
+    assert!(matches!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES.to_vec()), Err(AppError::OperationCancelled)));
+}
+
+#[test]
+fn policies_do_not_depend_on_labels() {
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Commit), ..non_interactive() });
+    let choices = vec![(PromptChoice::Other, "Commit nothing, cancel"), (PromptChoice::Commit, "Keep going")];
+    assert_eq!(ui.selector(PromptKind::DirtyWorkspace, "dirty", choices).unwrap(), 1);
+}
+
+#[test]
+fn on_dirty_commit_does_not_apply_in_review_state() {
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Commit), ..non_interactive() });
+    assert!(matches!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES[1..].to_vec()), Err(AppError::Validation(_))));
+}
+
+#[test]
+fn outdated_commit_is_answered_by_commit_branch_or_yes() {
+    let choices = vec![
+        (PromptChoice::Commit, "Continue to commit (may get conflicts!)"),
+        (PromptChoice::Branch, "Create a new branch and commit there (no conflicts)"),
+        (PromptChoice::Other, "No, cancel operation"),
+    ];
+
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Branch("wip".to_string())), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::OutdatedCommit, "outdated", choices.clone()).unwrap(), 1);
+
+    // stash 和 discard 不适用，按 --yes 继续提交
+    for on_dirty in [None, Some(DirtyPolicy::Stash), Some(DirtyPolicy::Discard), Some(DirtyPolicy::Commit)] {
+        let ui = ui_with(PromptPolicy { on_dirty, ..non_interactive() });
+        assert_eq!(ui.selector(PromptKind::OutdatedCommit, "outdated", choices.clone()).unwrap(), 0);
+    }
+
+    let ui = ui_with(PromptPolicy { on_dirty: Some(DirtyPolicy::Abort), ..non_interactive() });
+    assert!(matches!(ui.selector(PromptKind::OutdatedCommit, "outdated", choices), Err(AppError::OperationCancelled)));
+}
+
+#[test]
+fn conflicts_select_mine_or_theirs() {
+    let choices = vec![(PromptChoice::KeepMine, "Keep My Version (Keep Local Changes)"), (PromptChoice::TakeTheirs, "Discard My Version (Delete Local Changes)")];
+
+    let ui = ui_with(PromptPolicy { conflicts: Some(ConflictPolicy::Mine), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::Conflict, "conflict", choices.clone()).unwrap(), 0);
+
+    let ui = ui_with(PromptPolicy { conflicts: Some(ConflictPolicy::Theirs), ..non_interactive() });
+    assert_eq!(ui.selector(PromptKind::Conflict, "conflict", choices.clone()).unwrap(), 1);
+
+    let ui = ui_with(PromptPolicy { conflicts: Some(ConflictPolicy::Abort), ..non_interactive() });
+    assert!(matches!(ui.selector(PromptKind::Conflict, "conflict", choices), Err(AppError::OperationCancelled)));
+}
+
+#[test]
+fn message_answers_commit_message_input() {
+    let ui = ui_with(PromptPolicy { message: Some("from flag".to_string()), ..non_interactive() });
+    assert_eq!(ui.input_commit_message().unwrap(), "from flag");
+}
+
+#[test]
+fn non_interactive_confirms_with_yes() {
+    let ui = ui_with(non_interactive());
+    assert!(ui.selector_yes_or_no(PromptKind::Confirm, "Switch to the project now?").unwrap());
+}
+
+#[test]
+fn non_interactive_fails_without_policy() {
+    let ui = ui_with(non_interactive());
+    assert!(matches!(ui.selector(PromptKind::DirtyWorkspace, "dirty", DIRTY_CHOICES.to_vec()), Err(AppError::PromptRequired { .. })));
+    assert!(matches!(ui.selector(PromptKind::Conflict, "conflict", vec![(PromptChoice::KeepMine, "Keep My Version")]), Err(AppError::PromptRequired { .. })));
+    assert!(matches!(ui.input_commit_message(), Err(AppError::PromptRequired { .. })));
+    assert!(matches!(ui.input(PromptKind::BranchName, "branch"), Err(AppError::PromptRequired { .. })));
+    assert!(matches!(ui.selector_yes_or_no(PromptKind::Retry, "retry"), Err(AppError::PromptRequired { .. })));
+}
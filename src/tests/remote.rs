@@ -0,0 +1,127 @@
+//! 通过 svn://、http(s):// 登记的远程仓库
+
+use std::{fs, path::PathBuf};
+
+use crate::{
+    commands::{project::handle_commit, workspace::{handle_delete, handle_new}},
+    core::{
+        config::Config,
+        error::AppError,
+        svn_repo::{svnadmin_create, svnadmin_filter_into},
+        utils_default_repo::{RepoLocation, get_repo_name_for_url, repo_location_from_url, resolve_repo},
+    },
+    tests::{harness::TestEnv, scripted::ScriptedExecutor},
+};
+
+fn config_with_repos(repos: &[(&str, &str)]) -> Config {
+    Config {
+        repo_root: Some(PathBuf::from("/srv/svn")),
+        repos: repos.iter().map(|(name, url)| (name.to_string(), url.to_string())).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn repo_location_depends_on_scheme() {
+    assert_eq!(repo_location_from_url("file:///srv/svn/repo"), RepoLocation::Local(PathBuf::from("/srv/svn/repo")));
+    assert_eq!(repo_location_from_url("svn://server/team/"), RepoLocation::Remote("svn://server/team".to_string()));
+    assert_eq!(repo_location_from_url("https://server/svn/team"), RepoLocation::Remote("https://server/svn/team".to_string()));
+}
+
+#[test]
+fn remote_repo_names() {
+    let config = config_with_repos(&[("shared", "svn://server/team")]);
+    assert_eq!(get_repo_name_for_url(&config, "svn://server/team/"), "shared");
+    assert_eq!(get_repo_name_for_url(&config, "https://server/svn/my%20repo"), "my repo");
+    // svnserve -r 直接指向仓库时，URL 中没有路径
+    assert_eq!(get_repo_name_for_url(&config, "svn://user@server:3690"), "server");
+}
+
+#[test]
+fn repo_argument_accepts_names_registered_names_and_urls() {
+    let config = config_with_repos(&[("shared", "svn://server/team"), ("mirror", "file:///data/mirror")]);
+
+    let target = resolve_repo(&config, None).expect("default repo");
+    assert_eq!(target.name, "repo");
+    assert_eq!(target.location, RepoLocation::Local(PathBuf::from("/srv/svn/repo")));
+
+    let target = resolve_repo(&config, Some("shared")).expect("registered remote repo");
+    assert_eq!(target.name, "shared");
+    assert_eq!(target.location, RepoLocation::Remote("svn://server/team".to_string()));
+    assert_eq!(target.local_path(), None);
+
+    let target = resolve_repo(&config, Some("mirror")).expect("registered local repo");
+    assert_eq!(target.local_path(), Some(PathBuf::from("/data/mirror").as_path()));
+
+    let target = resolve_repo(&config, Some("http://server/svn/other")).expect("remote repo by url");
+    assert_eq!(target.name, "other");
+    assert_eq!(target.location, RepoLocation::Remote("http://server/svn/other".to_string()));
+
+    // default_repo 也可以是登记的名字
+    let config = Config { default_repo: "shared".to_string(), ..config };
+    assert_eq!(resolve_repo(&config, None).expect("default remote repo").name, "shared");
+}
+
+#[test]
+fn repos_must_be_registered_by_url() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-remote-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create temp dir");
+    let path = dir.join("config.toml");
+
+    fs::write(&path, "[repos]\nshared = \"svn://server/team\"\n").expect("write config");
+    let mut config = Config::default();
+    config.merge_file(&path).expect("merge config");
+    assert_eq!(config.repos.get("shared").map(String::as_str), Some("svn://server/team"));
+
+    fs::write(&path, "[repos]\nshared = \"server/team\"\n").expect("write config");
+    let err = Config::default().merge_file(&path).unwrap_err();
+    let _ = fs::remove_dir_all(&dir);
+    assert!(matches!(err, AppError::Config(ref msg) if msg.contains("repos.shared")), "{}", err);
+}
+
+#[test]
+fn svnadmin_refuses_remote_repositories() {
+    // 没有预设任何命令，启动进程会 panic
+    let svn = ScriptedExecutor::new();
+    let err = svnadmin_create(&svn, "svn://server/team").unwrap_err();
+    assert!(matches!(err, AppError::RemoteRepository { ref operation, .. } if operation == "svnadmin create"), "{}", err);
+
+    let err = svnadmin_filter_into(&svn, "https://server/svn/team", &["exclude", "demo"], "/srv/svn/repo_gc").unwrap_err();
+    assert!(matches!(err, AppError::RemoteRepository { ref url, .. } if url == "https://server/svn/team"), "{}", err);
+    assert!(svn.calls().is_empty());
+}
+
+#[test]
+fn filtering_a_repository_runs_one_pipeline() {
+    let pipeline = "svnadmin dump /srv/svn/repo --quiet | svndumpfilter exclude demo | svnadmin load /srv/svn/repo_gc --quiet --ignore-uuid";
+    let svn = ScriptedExecutor::new().on(pipeline, "");
+    svnadmin_filter_into(&svn, "/srv/svn/repo", &["exclude", "demo"], "/srv/svn/repo_gc").expect("filter repository");
+    assert_eq!(svn.calls(), vec![pipeline]);
+
+    let svn = ScriptedExecutor::new().fail(pipeline, "svndumpfilter: E200004: Invalid copy source path");
+    assert!(matches!(svnadmin_filter_into(&svn, "/srv/svn/repo", &["exclude", "demo"], "/srv/svn/repo_gc"), Err(AppError::SvnCommandFailed { .. })));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn project_on_svnserve_repository() {
+    let env = TestEnv::new();
+    let server = env.serve();
+
+    handle_new(&env.outside_app_with_repo(server.url(), &[]), "demo").expect("ws new on svn://");
+    assert_eq!(fs::read_link(env.ws()).expect("workspace symlink"), env.vault_root().join("demo"));
+    assert_eq!(env.svn(&["info", "--show-item", "repos-root-url"]), server.url());
+
+    env.write_file("a.txt", "hello\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    assert!(env.log_messages("demo").iter().any(|m| m == "Add a"));
+
+    handle_new(&env.app(&["No"]), "other").expect("ws new other");
+    let err = handle_delete(&env.app(&[]), "demo", true).unwrap_err();
+    assert!(matches!(err, AppError::RemoteRepository { ref operation, .. } if operation == "delete --force"), "{}", err);
+    assert!(env.url_exists("demo"));
+
+    // 不带 --force 的删除不需要访问仓库文件
+    handle_delete(&env.app(&[]), "demo", false).expect("soft delete on svn://");
+    assert!(!env.url_exists("demo"));
+}
@@ -0,0 +1,102 @@
+//! 提交时把缺失的文件和新文件识别为改名
+
+use std::fs;
+
+use crate::{
+    commands::{
+        project::handle_commit,
+        utils_rename::{group_directory_moves, pair_renames, similarity},
+    },
+    tests::harness::TestEnv,
+    ui::models::DetectedRename,
+};
+
+fn file(path: &str, content: &str) -> (String, Vec<u8>) {
+    (path.to_string(), content.as_bytes().to_vec())
+}
+
+fn rename(from: &str, to: &str, similarity: u8) -> DetectedRename {
+    DetectedRename { from: from.to_string(), to: to.to_string(), similarity, is_dir: false }
+}
+
+#[test]
+fn similarity_counts_equal_lines() {
+    assert_eq!(similarity(b"a\nb\nc\n", b"a\nb\nc\n"), 100);
+    assert_eq!(similarity(b"", b""), 100);
+    // 只有换行符不同时不算完全相同
+    assert_eq!(similarity(b"a\r\nb\r\n", b"a\nb\n"), 99);
+    assert_eq!(similarity(b"a\nb\nc\nd", b"a\nb\nc\nx"), 75);
+    assert_eq!(similarity(b"a\nb", b"x\ny"), 0);
+    // 最后的换行符不算作一行
+    assert_eq!(similarity(b"a\n", b"b\n"), 0);
+}
+
+#[test]
+fn renames_pair_the_most_similar_files() {
+    let missing = [
+        file("old/a.txt", "1\n2\n3\n4\n"),
+        file("b.txt", "same\n"),
+        file("gone.txt", "nothing alike\n"),
+    ];
+    let created = [
+        file("new/a.txt", "1\n2\n3\n5\n"),
+        file("copy/b.txt", "same\n"),
+        file("c.txt", "same\n"),
+        file("fresh.txt", "brand new\n"),
+    ];
+
+    // 内容相同时文件名相同的优先
+    assert_eq!(pair_renames(&missing, &created, 50), vec![
+        rename("b.txt", "copy/b.txt", 100),
+        rename("old/a.txt", "new/a.txt", 75),
+    ]);
+    assert_eq!(pair_renames(&missing, &created, 90), vec![rename("b.txt", "copy/b.txt", 100)]);
+}
+
+#[test]
+fn whole_directories_are_moved_together() {
+    let renames = vec![
+        rename("old/a.txt", "new/a.txt", 100),
+        rename("old/sub/b.txt", "new/sub/b.txt", 80),
+        rename("lib/c.txt", "src/c.txt", 100),
+        rename("docs/d.txt", "manual/d.txt", 100),
+        rename("docs/e.txt", "other/e.txt", 100),
+    ];
+    let missing_dirs = ["old/sub".to_string(), "old".to_string(), "docs".to_string(), "lib".to_string()];
+
+    let moves = group_directory_moves(renames, &missing_dirs, |dir| dir != "src");
+    assert_eq!(moves, vec![
+        DetectedRename { from: "old".to_string(), to: "new".to_string(), similarity: 90, is_dir: true },
+        // 目标目录已经受控时按文件移动
+        rename("lib/c.txt", "src/c.txt", 100),
+        // 目录中的文件移到了不同的目录
+        rename("docs/d.txt", "manual/d.txt", 100),
+        rename("docs/e.txt", "other/e.txt", 100),
+    ]);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn commit_records_renames_as_moves() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    env.write_file("a.txt", &content);
+    env.write_file("old/b.txt", "b\n");
+    env.write_file("old/c.txt", "c\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    // 改名并修改一行，移动整个目录
+    fs::remove_file(env.ws().join("a.txt")).expect("remove a.txt");
+    env.write_file("renamed.txt", &content.replace("line 10", "line ten"));
+    fs::rename(env.ws().join("old"), env.ws().join("new")).expect("move dir");
+    handle_commit(&env.app(&[]), &Some("Rename".to_string())).expect("ws commit renames");
+
+    let log = env.svn(&["log", "-v", "--xml", "-l", "1"]);
+    assert!(log.contains("copyfrom-path=\"/demo/trunk/a.txt\""), "{}", log);
+    assert!(log.contains("copyfrom-path=\"/demo/trunk/old\""), "{}", log);
+    assert_eq!(env.read_file("renamed.txt"), content.replace("line 10", "line ten"));
+    assert_eq!(env.read_file("new/b.txt"), "b\n");
+    assert_eq!(env.svn(&["status", "-q"]), "");
+}
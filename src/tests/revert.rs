@@ -0,0 +1,151 @@
+//! ws revert -r <rev> <paths>：只还原部分文件和目录
+//! ws revert --commit：只撤销某些提交
+
+use std::rc::Rc;
+
+use crate::{
+    commands::{
+        project::{build_log_entries, handle_commit, handle_revert, handle_revert_commits},
+        utils_revert::{normalize_revert_paths, parse_rollback_paths_message, parse_undo_message, restore_paths, rollback_paths_message, undo_message},
+    },
+    core::error::{AppError, SvnErrorKind},
+    tests::{harness::TestEnv, scripted::{ScriptedExecutor, scripted_app}},
+    ui::models::LogEntryKind,
+};
+
+fn paths(items: &[&str]) -> Vec<String> {
+    items.iter().map(|p| p.to_string()).collect()
+}
+
+#[test]
+fn revert_paths_are_relative_to_the_project() {
+    assert_eq!(normalize_revert_paths(&paths(&["./src/", "src", "config\\app.toml"])).unwrap(), paths(&["src", "config/app.toml"]));
+    // 包括项目根目录时还原整个项目
+    assert_eq!(normalize_revert_paths(&paths(&["src", "."])).unwrap(), Vec::<String>::new());
+
+    for outside in ["../other", "/etc/passwd", "C:/Windows"] {
+        let err = normalize_revert_paths(&paths(&[outside])).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+    }
+}
+
+#[test]
+fn rollback_message_records_paths_and_revision() {
+    let message = rollback_paths_message(&paths(&["src/foo.rs", "config"]), 42);
+    assert_eq!(message, "[WS-ROLLBACK] 2 path(s) from r42\n\nsrc/foo.rs\nconfig");
+    assert_eq!(parse_rollback_paths_message(&message), Some((paths(&["src/foo.rs", "config"]), 42)));
+
+    // 路径中可以有逗号和空格
+    let message = rollback_paths_message(&paths(&["a, b.txt", "notes from r1"]), 7);
+    assert_eq!(parse_rollback_paths_message(&message), Some((paths(&["a, b.txt", "notes from r1"]), 7)));
+
+    // 还原整个项目的提交信息记录的是锚点标签
+    assert_eq!(parse_rollback_paths_message("[WS-ROLLBACK] tags/rollback-20250302-120000"), None);
+}
+
+#[test]
+fn undo_message_records_the_undone_revisions() {
+    let message = undo_message(&[(42, "Break b\n\nDetails".to_string()), (40, "Break a".to_string())]);
+    assert_eq!(message, "[WS-UNDO] r42,r40\n\nr42 Break b\nr40 Break a");
+    assert_eq!(parse_undo_message(&message), Some(vec![42, 40]));
+    assert_eq!(parse_undo_message("[WS-UNDO] everything"), None);
+}
+
+#[test]
+fn only_missing_paths_are_reported_as_not_existing() {
+    let branch = "file:///srv/svn/repo/demo/trunk";
+    let svn = Rc::new(ScriptedExecutor::new()
+        .workspace("file:///srv/svn/repo", "demo", "trunk", 9)
+        .fail(&format!("svn info --show-item kind {}/gone@3", branch), "svn: warning: W170000: URL 'file:///srv/svn/repo/demo/trunk/gone' non-existent in revision 3")
+        .fail(&format!("svn info --show-item kind {}/locked@3", branch), "svn: E155004: Working copy '/ws' locked."));
+    let app = scripted_app(&svn, &[]);
+
+    let err = restore_paths(&app, branch, &paths(&["gone"]), 3).unwrap_err();
+    assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("does not exist in r3")), "{}", err);
+
+    let err = restore_paths(&app, branch, &paths(&["locked"]), 3).unwrap_err();
+    assert!(matches!(err, AppError::SvnCommandFailed { kind: SvnErrorKind::WorkingCopyLocked, .. }), "{}", err);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn revert_restores_only_the_given_paths() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "a1\n");
+    env.write_file("b.txt", "b1\n");
+    env.write_file("conf/c.txt", "c1\n");
+    handle_commit(&env.app(&[]), &Some("v1".to_string())).expect("commit v1");
+    let v1 = env.current_revision();
+
+    env.write_file("a.txt", "a2\n");
+    env.write_file("b.txt", "b2\n");
+    std::fs::remove_dir_all(env.ws().join("conf")).expect("remove conf");
+    handle_commit(&env.app(&[]), &Some("v2".to_string())).expect("commit v2");
+
+    // 已经删除的目录也会恢复
+    handle_revert(&env.app(&[]), &v1, &paths(&["a.txt", "conf"])).expect("ws revert a.txt conf");
+    assert_eq!(env.read_file("a.txt"), "a1\n");
+    assert_eq!(env.read_file("b.txt"), "b2\n");
+    assert_eq!(env.read_file("conf/c.txt"), "c1\n");
+    assert_eq!(env.svn(&["status", "-q"]), "");
+
+    let v1: u64 = v1.parse().expect("numeric revision");
+    let log = build_log_entries(&env.app(&[]), false, &Default::default()).expect("ws log");
+    assert_eq!(log[0].kind, LogEntryKind::Rollback { source_revision: Some(v1), paths: paths(&["a.txt", "conf"]) });
+
+    let err = handle_revert(&env.app(&[]), &format!("r{}", v1), &paths(&["missing.txt"])).unwrap_err();
+    assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn failed_revert_removes_the_paths_it_copied_back() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "a1\n");
+    env.write_file("conf/c.txt", "c1\n");
+    handle_commit(&env.app(&[]), &Some("v1".to_string())).expect("commit v1");
+    let v1 = env.current_revision();
+
+    std::fs::remove_file(env.ws().join("a.txt")).expect("remove a.txt");
+    std::fs::remove_dir_all(env.ws().join("conf")).expect("remove conf");
+    env.write_file(".gitignore", "a.txt\n");
+    handle_commit(&env.app(&[]), &Some("v2".to_string())).expect("commit v2");
+
+    // 被忽略的本地文件挡住了 a.txt，此时 conf 已经复制回来
+    env.write_file("a.txt", "local\n");
+    assert!(handle_revert(&env.app(&[]), &v1, &paths(&["conf", "a.txt"])).is_err());
+    assert!(!env.ws().join("conf").exists());
+    assert_eq!(env.read_file("a.txt"), "local\n");
+    assert_eq!(env.svn(&["status", "-q"]), "");
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn revert_commit_undoes_only_that_commit() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "a1\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("commit a");
+    env.write_file("b.txt", "bad\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit b");
+    let bad = env.current_revision();
+    env.write_file("a.txt", "a2\n");
+    handle_commit(&env.app(&[]), &Some("Change a".to_string())).expect("commit later change");
+
+    handle_revert_commits(&env.app(&[]), &format!("r{}", bad)).expect("ws revert --commit");
+    assert!(!env.ws().join("b.txt").exists());
+    // 之后的提交保留
+    assert_eq!(env.read_file("a.txt"), "a2\n");
+
+    let bad: u64 = bad.parse().expect("numeric revision");
+    let log = build_log_entries(&env.app(&[]), false, &Default::default()).expect("ws log");
+    assert_eq!(log[0].kind, LogEntryKind::Undo { revisions: vec![bad] });
+
+    // 不存在的版本
+    assert!(handle_revert_commits(&env.app(&[]), "r999").is_err());
+}
@@ -0,0 +1,106 @@
+//! 版本表达式：HEAD~N、PREV、日期和 分支@版本
+
+use crate::{
+    commands::{
+        project::{handle_branch, handle_commit, handle_review},
+        utils_diff::{DiffTarget, build_diff},
+        utils_revision::resolve_revision,
+    },
+    core::{
+        error::AppError,
+        utils::{RevisionBase, RevisionExpr, parse_revision_expr, parse_revision_range},
+    },
+    tests::harness::TestEnv,
+};
+
+fn expr(branch: Option<&str>, base: RevisionBase, offset: u64) -> RevisionExpr {
+    RevisionExpr { branch: branch.map(str::to_string), base, offset }
+}
+
+#[test]
+fn parses_revision_expressions() {
+    assert_eq!(parse_revision_expr("HEAD").unwrap(), expr(None, RevisionBase::Head, 0));
+    assert_eq!(parse_revision_expr("r100").unwrap(), expr(None, RevisionBase::Number(100), 0));
+    assert_eq!(parse_revision_expr(" 100 ").unwrap(), expr(None, RevisionBase::Number(100), 0));
+    assert_eq!(parse_revision_expr("head~3").unwrap(), expr(None, RevisionBase::Head, 3));
+    assert_eq!(parse_revision_expr("HEAD~~2").unwrap(), expr(None, RevisionBase::Head, 3));
+    assert_eq!(parse_revision_expr("PREV").unwrap(), expr(None, RevisionBase::Prev, 0));
+    assert_eq!(parse_revision_expr("{2025-03-01}").unwrap(), expr(None, RevisionBase::Date("2025-03-01".to_string()), 0));
+    assert_eq!(parse_revision_expr("{2025-03-01 12:30}").unwrap(), expr(None, RevisionBase::Date("2025-03-01T12:30".to_string()), 0));
+    assert_eq!(parse_revision_expr("feature@r120").unwrap(), expr(Some("feature"), RevisionBase::Number(120), 0));
+    assert_eq!(parse_revision_expr("trunk@{2025-03-01T08:00:00}~1").unwrap(), expr(Some("trunk"), RevisionBase::Date("2025-03-01T08:00:00".to_string()), 1));
+
+    for invalid in ["", "feature", "@r1", "HEAD~x", "HEAD~-1", "{2025-13-01}", "{yesterday}", "r12a"] {
+        assert!(matches!(parse_revision_expr(invalid), Err(AppError::RevisionParse(_))), "{:?} should not parse", invalid);
+    }
+
+    // 显示为可以再次解析的形式
+    for input in ["HEAD~3", "feature@r120", "PREV", "trunk@{2025-03-01T12:30}~2"] {
+        assert_eq!(parse_revision_expr(input).unwrap().to_string(), input);
+    }
+}
+
+#[test]
+fn parses_revision_ranges() {
+    let (from, to) = parse_revision_range("r10:HEAD~1").unwrap();
+    assert_eq!(from, expr(None, RevisionBase::Number(10), 0));
+    assert_eq!(to, Some(expr(None, RevisionBase::Head, 1)));
+
+    // 日期中的 : 不是分隔符
+    let (from, to) = parse_revision_range("{2025-03-01 12:30}:feature@HEAD").unwrap();
+    assert_eq!(from, expr(None, RevisionBase::Date("2025-03-01T12:30".to_string()), 0));
+    assert_eq!(to, Some(expr(Some("feature"), RevisionBase::Head, 0)));
+
+    let (from, to) = parse_revision_range("feature@r120").unwrap();
+    assert_eq!(from, expr(Some("feature"), RevisionBase::Number(120), 0));
+    assert_eq!(to, None);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn offsets_follow_the_branch_history() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    let mut revisions = Vec::new();
+    for (i, project) in ["demo", "other", "demo", "other", "demo"].iter().enumerate() {
+        if *project != "demo" {
+            env.svn(&["mkdir", "--parents", &format!("{}/other/trunk/dir{}", env.repo_url(), i), "-m", "Change other project"]);
+            continue;
+        }
+        env.write_file("a.txt", &format!("{}\n", i));
+        handle_commit(&env.app(&[]), &Some(format!("Commit {}", i))).expect("ws commit");
+        revisions.push(env.current_revision().parse::<u64>().expect("revision number"));
+    }
+
+    let resolve = |input: &str| resolve_revision(&env.app(&[]), &parse_revision_expr(input).expect("parse")).map(|r| r.revision);
+    // 其他项目的提交不计入
+    assert_eq!(resolve("HEAD").unwrap(), revisions[2]);
+    assert_eq!(resolve("HEAD~1").unwrap(), revisions[1]);
+    assert_eq!(resolve("HEAD~2").unwrap(), revisions[0]);
+    assert_eq!(resolve("PREV").unwrap(), revisions[1]);
+    assert_eq!(resolve(&format!("r{}~1", revisions[2] - 1)).unwrap(), revisions[0]);
+    assert!(matches!(resolve("HEAD~100"), Err(AppError::RevisionParse(_))));
+    assert!(matches!(resolve("{2000-01-01}"), Err(AppError::RevisionParse(_))));
+
+    // 分支创建后，分支自己的历史延续到 trunk 的历史
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, Some("HEAD~1")).expect("branch from HEAD~1");
+    assert_eq!(env.read_file("a.txt"), "2\n");
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+
+    assert_eq!(resolve("trunk@HEAD").unwrap(), revisions[2]);
+    assert_eq!(resolve("HEAD~2").unwrap(), revisions[1]);
+    assert!(matches!(resolve("trunk@PREV"), Err(AppError::RevisionParse(_))));
+    assert!(matches!(resolve("missing@HEAD"), Err(AppError::Validation(_))));
+
+    // ws diff -r trunk@HEAD 比较 trunk 与工作副本
+    let (from, to) = parse_revision_range("trunk@HEAD").expect("parse");
+    let diffs = build_diff(&env.app(&[]), &DiffTarget::Revisions { from, to }, &[]).expect("diff against trunk@HEAD");
+    assert!(diffs.iter().any(|d| d.path == "a.txt") && diffs.iter().any(|d| d.path == "b.txt"), "{:?}", diffs);
+
+    // review 另一个分支的版本时切换到该分支
+    handle_review(&env.app(&[]), &format!("trunk@r{}", revisions[0])).expect("review trunk@r");
+    assert!(env.svn(&["info", "--show-item", "url"]).ends_with("/demo/trunk"));
+    assert_eq!(env.read_file("a.txt"), "0\n");
+}
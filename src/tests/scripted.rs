@@ -0,0 +1,82 @@
+//! 返回预设输出的执行器，不启动任何进程，用于不依赖 svn 的测试
+
+use std::{cell::RefCell, collections::{HashMap, VecDeque}, rc::Rc};
+
+use crate::{
+    core::{app::App, config::Config, context::get_svn_context, error::{AppError, AppResult, SvnErrorKind}, svn_executor::{SvnExecutor, format_command}},
+    ui::display::AppUI,
+};
+
+/// 按命令行（format_command 的格式）返回预设的 stdout，并记录执行过的命令
+/// - 同一命令预设多次时按顺序返回，最后一个重复使用
+/// - 管道按 "a | b | c" 的形式匹配
+/// - 没有预设的命令直接 panic
+#[derive(Debug, Default)]
+pub struct ScriptedExecutor {
+    outputs: RefCell<HashMap<String, VecDeque<Result<String, String>>>>,
+    calls: RefCell<Vec<String>>,
+}
+
+impl ScriptedExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 预设命令的 stdout
+    pub fn on(self, command: &str, stdout: &str) -> Self {
+        self.outputs.borrow_mut().entry(command.to_string()).or_default().push_back(Ok(stdout.to_string()));
+        self
+    }
+
+    /// 预设命令失败时的 stderr，错误种类按 stderr 分类
+    pub fn fail(self, command: &str, stderr: &str) -> Self {
+        self.outputs.borrow_mut().entry(command.to_string()).or_default().push_back(Err(stderr.to_string()));
+        self
+    }
+
+    /// 预设工作副本位于 {repo_url}/{project}/{branch_path}，版本为 revision
+    pub fn workspace(self, repo_url: &str, project: &str, branch_path: &str, revision: u64) -> Self {
+        self.on("svn info --show-item repos-root-url", repo_url)
+            .on("svn info --show-item relative-url", &format!("^/{}/{}", project, branch_path))
+            .on("svn info --show-item url", &format!("{}/{}/{}", repo_url, project, branch_path))
+            .on("svn info --show-item last-changed-revision", &revision.to_string())
+            .on("svn info -r HEAD --show-item last-changed-revision", &revision.to_string())
+    }
+
+    /// 按顺序返回执行过的命令
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    fn run(&self, command: String) -> AppResult<Vec<u8>> {
+        self.calls.borrow_mut().push(command.clone());
+        let mut outputs = self.outputs.borrow_mut();
+        let queue = outputs.get_mut(&command)
+            .unwrap_or_else(|| panic!("unexpected command: {}", command));
+        let output = if queue.len() > 1 { queue.pop_front() } else { queue.front().cloned() }
+            .expect("scripted output");
+        output.map(String::into_bytes).map_err(|stderr| AppError::SvnCommandFailed {
+            command,
+            kind: SvnErrorKind::from_stderr(&stderr),
+            _stdout: String::new(),
+            stderr,
+        })
+    }
+}
+
+impl SvnExecutor for ScriptedExecutor {
+    fn execute(&self, program: &str, args: &[&str]) -> AppResult<Vec<u8>> {
+        self.run(format_command(program, args))
+    }
+
+    fn execute_piped(&self, stages: &[(&str, &[&str])]) -> AppResult<()> {
+        let command = stages.iter().map(|(program, args)| format_command(program, args)).collect::<Vec<_>>().join(" | ");
+        self.run(command).map(|_| ())
+    }
+}
+
+/// 由预设的 svn info 输出初始化 App，见 ScriptedExecutor::workspace
+pub fn scripted_app(svn: &Rc<ScriptedExecutor>, answers: &[&str]) -> App {
+    let svn_ctx = get_svn_context(svn.clone(), Config::default()).expect("scripted svn context");
+    App::from_parts(AppUI::with_answers(answers), svn.clone(), svn_ctx)
+}
@@ -0,0 +1,181 @@
+//! ws stash：保存更改、恢复更改，以及 ws switch 回到原分支时自动恢复
+
+use crate::{
+    commands::{
+        models::StashInfo,
+        project::{handle_branch, handle_commit, handle_stash_drop, handle_stash_pop, handle_stash_push},
+        utils_stash::{directory_matches_base, list_stashes, parse_proplist},
+        workspace::handle_switch,
+    },
+    tests::harness::TestEnv,
+    ui::models::{StatusEntry, StatusKind},
+};
+
+#[test]
+fn stash_info_round_trips_without_its_index() {
+    let stash = StashInfo {
+        index: 3,
+        message: "WIP on trunk@r5".to_string(),
+        branch: "trunk".to_string(),
+        revision: 5,
+        date: "2025-03-01T12:00:00+08:00".to_string(),
+        auto_pop: true,
+        entries: vec![StatusEntry::new("src/a.txt", StatusKind::Modified, false), StatusEntry::new("new.txt", StatusKind::Added, true)],
+        properties: Vec::new(),
+        directories: vec!["docs".to_string()],
+    };
+
+    let json = serde_json::to_string(&stash).unwrap();
+    let read: StashInfo = serde_json::from_str(&json).unwrap();
+    // 位置由读取列表时决定
+    assert_eq!(read.index, 0);
+    assert_eq!(read.entries, stash.entries);
+    assert_eq!((read.branch.as_str(), read.revision, read.auto_pop), ("trunk", 5, true));
+    assert_eq!(read.directories, stash.directories);
+
+    // 旧的 stash 中没有属性和目录
+    let old = json.replace(r#","properties":[],"directories":["docs"]"#, "");
+    assert!(!old.contains("directories"));
+    assert!(serde_json::from_str::<StashInfo>(&old).unwrap().directories.is_empty());
+}
+
+#[test]
+fn stashed_properties_are_read_as_text() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<properties>
+<target path=".">
+<property name="svn:ignore">target
+*.log
+</property>
+<property name="svn:externals">-r21 https://server/svn/lib lib</property>
+</target>
+</properties>"#;
+    let properties = parse_proplist(xml, ".").expect("parse proplist");
+    assert_eq!(properties.get("svn:ignore").map(String::as_str), Some("target\n*.log\n"));
+    assert_eq!(properties.get("svn:externals").map(String::as_str), Some("-r21 https://server/svn/lib lib"));
+
+    let binary = r#"<properties><target path="logo.png"><property name="thumb" encoding="base64">AAEC</property></target></properties>"#;
+    assert!(parse_proplist(binary, "logo.png").is_err());
+}
+
+#[test]
+fn deleted_directory_is_compared_with_the_stashed_base() {
+    let dir = std::env::temp_dir().join(format!("ws-tool-stash-base-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let (current, base) = (dir.join("current"), dir.join("base"));
+    for root in [&current, &base] {
+        std::fs::create_dir_all(root.join("sub")).expect("create dirs");
+        std::fs::write(root.join("sub/a.txt"), "one\n").expect("write a");
+    }
+    // 保存之后被删除的文件不影响
+    std::fs::write(base.join("gone.txt"), "gone\n").expect("write gone");
+    std::fs::create_dir_all(current.join("empty")).expect("create empty dir");
+    assert!(directory_matches_base(&current, &base).expect("compare"));
+
+    // 保存之后修改或加入的文件
+    std::fs::write(current.join("sub/a.txt"), "two\n").expect("edit a");
+    assert!(!directory_matches_base(&current, &base).expect("compare"));
+    std::fs::write(current.join("sub/a.txt"), "one\n").expect("restore a");
+    std::fs::write(current.join("sub/new.txt"), "new\n").expect("write new");
+    assert!(!directory_matches_base(&current, &base).expect("compare"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn stash_keeps_deleted_directory_with_files_added_since() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("lib/a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add lib".to_string())).expect("ws commit");
+
+    std::fs::remove_dir_all(env.ws().join("lib")).expect("remove lib");
+    handle_stash_push(&env.app(&[]), None).expect("ws stash");
+    assert_eq!(env.read_file("lib/a.txt"), "one\n");
+
+    env.write_file("lib/b.txt", "new\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("ws commit b");
+
+    // lib/b.txt 不在 stash 中，目录保留，stash 也保留
+    handle_stash_pop(&env.app(&[]), 0).expect("ws stash pop");
+    assert_eq!(env.read_file("lib/b.txt"), "new\n");
+    assert_eq!(env.svn(&["status"]), "");
+    assert_eq!(list_stashes(&env.app(&[]), "demo").expect("ws stash list").len(), 1);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn stash_saves_and_restores_local_changes() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    env.write_file("gone.txt", "gone\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    env.write_file("a.txt", "two\n");
+    env.write_file("dir/new.txt", "new\n");
+    std::fs::remove_file(env.ws().join("gone.txt")).expect("remove gone.txt");
+    handle_stash_push(&env.app(&[]), Some("wip")).expect("ws stash");
+
+    assert_eq!(env.read_file("a.txt"), "one\n");
+    assert_eq!(env.read_file("gone.txt"), "gone\n");
+    assert!(!env.ws().join("dir").exists());
+    assert_eq!(env.svn(&["status"]), "");
+
+    let stashes = list_stashes(&env.app(&[]), "demo").expect("ws stash list");
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].1.message, "wip");
+
+    handle_stash_pop(&env.app(&[]), 0).expect("ws stash pop");
+    assert_eq!(env.read_file("a.txt"), "two\n");
+    assert_eq!(env.read_file("dir/new.txt"), "new\n");
+    assert!(!env.ws().join("gone.txt").exists());
+    assert!(list_stashes(&env.app(&[]), "demo").expect("ws stash list").is_empty());
+
+    assert!(handle_stash_drop(&env.app(&[]), 0).is_err());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn switching_back_restores_changes_stashed_on_the_way_out() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("ws commit");
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+
+    env.write_file("a.txt", "feature wip\n");
+    handle_switch(&env.app(&["Stash changes"]), None, Some("trunk".to_string())).expect("switch to trunk");
+    assert_eq!(env.read_file("a.txt"), "one\n");
+
+    handle_switch(&env.app(&[]), None, Some("feature".to_string())).expect("switch back to feature");
+    assert_eq!(env.read_file("a.txt"), "feature wip\n");
+    assert!(list_stashes(&env.app(&[]), "demo").expect("ws stash list").is_empty());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn stash_keeps_property_changes_and_added_directories() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("lib/a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add lib".to_string())).expect("ws commit");
+
+    env.svn(&["propset", "svn:externals", "-r1 ^/demo/trunk/lib vendored", "lib"]);
+    env.svn(&["propset", "team:owner", "alice", "lib/a.txt"]);
+    env.svn(&["mkdir", "empty"]);
+    handle_stash_push(&env.app(&[]), None).expect("ws stash");
+    assert_eq!(env.svn(&["status"]), "");
+    assert!(!env.ws().join("empty").exists());
+
+    handle_stash_pop(&env.app(&[]), 0).expect("ws stash pop");
+    assert_eq!(env.svn(&["propget", "svn:externals", "lib"]).trim(), "-r1 ^/demo/trunk/lib vendored");
+    assert_eq!(env.svn(&["propget", "team:owner", "lib/a.txt"]).trim(), "alice");
+    assert!(env.svn(&["status", "empty"]).starts_with('A'));
+    assert!(list_stashes(&env.app(&[]), "demo").expect("ws stash list").is_empty());
+}
@@ -0,0 +1,247 @@
+//! 从 new 开始，依次执行 commit、branch、push、pull、revert、switch、delete、restore 等指令
+
+use std::fs;
+
+use crate::{
+    commands::{
+        models::LogFilter,
+        project::{build_log_entries, build_workspace_status, handle_branch, handle_commit, handle_pull, handle_push, handle_revert},
+        utils_file::{ChangeLockType, change_lock_file, get_lock_file_path},
+        workspace::{handle_delete, handle_new, handle_restore, handle_switch, handle_uncheckout},
+    },
+    tests::harness::TestEnv,
+    ui::{models::{LogEntry, LogEntryKind, StatusEntry, StatusKind}, policy::{DirtyPolicy, PromptPolicy}},
+};
+
+fn log_entries_of(env: &TestEnv) -> Vec<LogEntry> {
+    build_log_entries(&env.app(&[]), false, &LogFilter::default()).expect("build log entries")
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn new_project_links_workspace_and_counts_lock() {
+    let env = TestEnv::new();
+
+    env.new_project("demo");
+
+    let link = fs::read_link(env.ws()).expect("workspace should be a symlink");
+    assert_eq!(link, env.vault_root().join("demo"));
+    assert_eq!(env.lock_value("demo"), 1);
+
+    let messages = env.log_messages("demo");
+    assert!(messages.iter().any(|m| m == "[WS-INIT] demo"));
+    assert!(messages.iter().any(|m| m == "[WS-INIT] Add default .gitignore file"));
+    assert!(messages.iter().any(|m| m.starts_with("[WS-INIT-GITIGNORE]")));
+
+    // [WS-INIT] 被隐藏，[WS-INIT-GITIGNORE] 显示为 Init
+    let entries = log_entries_of(&env);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, LogEntryKind::Init { project: "demo".to_string() });
+    assert!(entries[0].is_current);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn commit_shows_message_in_log() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("src/main.rs", "fn main() {}\n");
+    handle_commit(&env.app(&[]), &Some("Add main".to_string())).expect("ws commit");
+
+    assert!(env.svn(&["status"]).is_empty());
+    let entries = log_entries_of(&env);
+    assert_eq!(entries[0].kind, LogEntryKind::Commit);
+    assert_eq!(entries[0].message, "Add main");
+    assert!(!entries[0].author.is_empty());
+    assert!(chrono::DateTime::parse_from_rfc3339(&entries[0].date).is_ok());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn status_shows_what_commit_will_do() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file(".gitignore", "*.log\n");
+    env.write_file("keep.txt", "v1\n");
+    env.write_file("gone.txt", "v1\n");
+    handle_commit(&env.app(&[]), &Some("Add files".to_string())).expect("ws commit");
+
+    let status = build_workspace_status(&env.app(&[])).expect("clean status");
+    assert_eq!(status.project, "demo");
+    assert_eq!(status.branch, "trunk");
+    assert!(!status.is_review);
+    assert!(status.entries.is_empty(), "{:?}", status.entries);
+
+    env.write_file("keep.txt", "v2\n");
+    env.write_file("new/a.txt", "a\n");
+    env.write_file("debug.log", "log\n");
+    fs::remove_file(env.ws().join("gone.txt")).expect("remove gone.txt");
+
+    let entries = build_workspace_status(&env.app(&[])).expect("dirty status").entries;
+    for expected in [
+        StatusEntry::new("keep.txt", StatusKind::Modified, false),
+        StatusEntry::new("gone.txt", StatusKind::Deleted, true),
+        StatusEntry::new("new/a.txt", StatusKind::Added, true),
+        StatusEntry::new("debug.log", StatusKind::Ignored, false),
+    ] {
+        assert!(entries.contains(&expected), "missing {:?} in {:?}", expected, entries);
+    }
+
+    // 提交后只剩被忽略的文件
+    handle_commit(&env.app(&[]), &Some("Update".to_string())).expect("ws commit");
+    let entries = build_workspace_status(&env.app(&[])).expect("status after commit").entries;
+    assert_eq!(entries, vec![StatusEntry::new("debug.log", StatusKind::Ignored, false)]);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn branch_push_and_pull_round_trip() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "one\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("commit on trunk");
+
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    assert!(env.url_exists("demo/branches/feature"));
+    assert!(env.log_messages("demo/branches/feature").iter().any(|m| m == "[WS-BRANCH] Create feature"));
+    assert!(log_entries_of(&env).iter().any(|e| matches!(&e.kind, LogEntryKind::Branch { source, .. } if source == "trunk")));
+
+    env.write_file("b.txt", "feature\n");
+    handle_commit(&env.app(&[]), &Some("Add b".to_string())).expect("commit on feature");
+
+    // push 会切换到 trunk 并合并，合并结果由下一次 commit 提交
+    handle_push(&env.app(&[]), Some("trunk")).expect("ws push trunk");
+    assert!(env.svn(&["info", "--show-item", "url"]).ends_with("/demo/trunk"));
+    assert_eq!(env.read_file("b.txt"), "feature\n");
+    handle_commit(&env.app(&[]), &Some("Merge feature".to_string())).expect("commit merge");
+
+    let entries = log_entries_of(&env);
+    assert!(entries.iter().any(|e| e.kind == LogEntryKind::Merge { source: "feature".to_string() }));
+
+    env.write_file("c.txt", "trunk\n");
+    handle_commit(&env.app(&[]), &Some("Add c".to_string())).expect("commit on trunk");
+
+    handle_switch(&env.app(&[]), None, Some("feature".to_string())).expect("switch to feature");
+    assert!(!env.ws().join("c.txt").exists());
+
+    handle_pull(&env.app(&[]), Some("trunk")).expect("ws pull trunk");
+    assert_eq!(env.read_file("c.txt"), "trunk\n");
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn revert_creates_anchor_and_rollback_commit() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "v1\n");
+    handle_commit(&env.app(&[]), &Some("v1".to_string())).expect("commit v1");
+    let v1 = env.current_revision();
+
+    env.write_file("a.txt", "v2\n");
+    handle_commit(&env.app(&[]), &Some("v2".to_string())).expect("commit v2");
+
+    handle_revert(&env.app(&[]), &v1, &[]).expect("ws revert");
+    assert_eq!(env.read_file("a.txt"), "v1\n");
+
+    let tag_messages = env.log_messages("demo/tags");
+    assert!(tag_messages.iter().any(|m| m.starts_with("[WS-REVERT] Anchor for revert: rollback-")));
+    let trunk_messages = env.log_messages("demo/trunk");
+    assert!(trunk_messages[0].starts_with("[WS-ROLLBACK] tags/rollback-"));
+
+    let v1: u64 = v1.parse().expect("numeric revision");
+    let entries = log_entries_of(&env);
+    assert_eq!(entries[0].kind, LogEntryKind::Rollback { source_revision: Some(v1), paths: Vec::new() });
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn dirty_workspace_is_committed_before_switching_branch() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("wip.txt", "wip\n");
+
+    handle_switch(&env.app(&["Commit changes", "Save wip"]), None, Some("trunk".to_string())).expect("switch to trunk");
+
+    assert!(env.log_messages("demo/branches/feature").iter().any(|m| m == "Save wip"));
+    assert!(!env.ws().join("wip.txt").exists());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn non_interactive_discard_policy_drops_changes_before_switching() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    env.write_file("a.txt", "committed\n");
+    handle_commit(&env.app(&[]), &Some("Add a".to_string())).expect("commit a");
+    handle_branch(&env.app(&[]), Some("feature".to_string()), true, false, false, None).expect("ws branch feature");
+    env.write_file("a.txt", "local edit\n");
+
+    let mut app = env.app(&[]);
+    app.ui.set_policy(PromptPolicy { non_interactive: true, on_dirty: Some(DirtyPolicy::Discard), ..Default::default() });
+    handle_switch(&app, None, Some("trunk".to_string())).expect("switch to trunk");
+
+    assert_eq!(env.read_file("a.txt"), "committed\n");
+    assert!(env.svn(&["status"]).is_empty());
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn switch_moves_lock_between_projects() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    handle_new(&env.app(&["No"]), "other").expect("ws new other");
+    assert_eq!(env.lock_value("demo"), 1);
+    assert_eq!(env.lock_value("other"), 0);
+
+    handle_switch(&env.app(&[]), Some("other"), None).expect("switch to other");
+    assert_eq!(env.lock_value("demo"), 0);
+    assert_eq!(env.lock_value("other"), 1);
+    assert_eq!(fs::read_link(env.ws()).expect("workspace symlink"), env.vault_root().join("other"));
+    assert_eq!(env.svn(&["info", "--show-item", "url"]), format!("{}/other/trunk", env.repo_url()));
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn delete_and_restore_project() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+    handle_new(&env.app(&["No"]), "other").expect("ws new other");
+
+    // 当前项目不能删除
+    assert!(handle_delete(&env.app(&[]), "demo", false).is_err());
+
+    // 其他工作区还在使用时不能删除
+    let lock_file = get_lock_file_path(&env.vault_root(), "other").expect("lock file path");
+    change_lock_file(&lock_file, ChangeLockType::Add).expect("simulate another workspace");
+    handle_delete(&env.app(&[]), "other", false).expect("ws delete other in use");
+    assert!(env.url_exists("other"));
+    change_lock_file(&lock_file, ChangeLockType::Sub).expect("release other workspace");
+
+    handle_delete(&env.app(&[]), "other", false).expect("ws delete other");
+    assert!(!env.url_exists("other"));
+    assert!(env.log_messages("").iter().any(|m| m == "Delete project other"));
+
+    handle_restore(&env.app(&["No"]), "other").expect("ws restore other");
+    assert!(env.url_exists("other/trunk"));
+    assert!(env.log_messages("").iter().any(|m| m == "Restore project other"));
+    assert_eq!(env.lock_value("demo"), 1);
+}
+
+#[test]
+#[ignore = "end-to-end, needs svn"]
+fn uncheckout_releases_lock() {
+    let env = TestEnv::new();
+    env.new_project("demo");
+
+    handle_uncheckout(&env.app(&[])).expect("ws uncheckout");
+    assert_eq!(env.lock_value("demo"), 0);
+    assert!(fs::symlink_metadata(env.ws()).is_err());
+}
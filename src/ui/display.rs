@@ -1,40 +1,80 @@
-use std::{cell::RefCell, io};
+use std::{cell::RefCell, collections::VecDeque, io};
 
-use chrono::Local;
 // use colored::Colorize;
 use comfy_table::{Cell, ContentArrangement, Table, presets};
 use crossterm::{cursor, execute, style::{self, SetForegroundColor, Stylize}, terminal};
-use dialoguer::{Select, theme};
+use dialoguer::{MultiSelect, Select, theme};
 use unicode_width::UnicodeWidthStr;
 
-use crate::{core::{error::{AppError, AppResult}, utils::CursorGuard}, ui::models::{LogEntry, ProjectInfo, SpinnerInfo, TableWidth}};
+use serde::Serialize;
+
+use crate::{commands::{models::{ConflictResolution, StashInfo, TagInfo}, utils::{format_full_time, format_relative_time}, utils_conflict::{CONFLICT_RULES_FILE, ConflictRule}, utils_graph::layout_graph}, core::{config::MessageTemplates, error::{AppError, AppResult}, utils::CursorGuard}, ui::{models::{BranchComparison, ConflictRegion, DetectedRename, DiffHunk, FileDiff, GraphCommit, LogEntry, LogEntryKind, OutputFormat, ProjectInfo, RevisionDetail, SpinnerInfo, StatusEntry, StatusKind, TableWidth, WorkspaceStatus}, policy::{PromptChoice, PromptKind, PromptPolicy}}};
 
 pub struct AppUI {
     spinner: RefCell<Option<SpinnerInfo>>,
     dialoguer_color_theme: theme::ColorfulTheme,
     _cursor_guard: CursorGuard,
+    /// 交互策略，在打开终端控件之前查找
+    policy: PromptPolicy,
+    /// 查询结果的输出格式，非 Text 时提示信息输出到 stderr
+    output_format: OutputFormat,
+    /// 提交信息模板，来自配置
+    messages: MessageTemplates,
+    /// 预设的交互答案，存在时不再打开终端控件
+    /// - selector: 选中第一个以答案开头的选项
+    /// - input: 答案即输入内容
+    scripted_answers: Option<RefCell<VecDeque<String>>>,
 }
 
 
 impl AppUI {
     pub fn new() -> Self {
-        let mut color_theme = dialoguer::theme::ColorfulTheme::default();
-        color_theme.success_prefix = dialoguer::console::style(String::from("[ OK ]")).green().bold().bright();
-        color_theme.error_prefix = dialoguer::console::style(String::from("[ERR!]")).red().bright().bold();
-        color_theme.prompt_prefix = dialoguer::console::style(String::from("[INFO]")).blue().bright().bold();
-        color_theme.active_item_style = dialoguer::console::Style::new().for_stderr().green();
-        color_theme.success_suffix = dialoguer::console::style(String::new());
-        color_theme.prompt_style = dialoguer::console::Style::new().for_stderr();
-        color_theme.prompt_suffix = dialoguer::console::style(String::new()).for_stderr().black().bright();
-        color_theme.active_item_prefix = dialoguer::console::style(">".to_string()).for_stderr().green();
+        let color_theme = dialoguer::theme::ColorfulTheme {
+            success_prefix: dialoguer::console::style(String::from("[ OK ]")).green().bold().bright(),
+            error_prefix: dialoguer::console::style(String::from("[ERR!]")).red().bright().bold(),
+            prompt_prefix: dialoguer::console::style(String::from("[INFO]")).blue().bright().bold(),
+            active_item_style: dialoguer::console::Style::new().for_stderr().green(),
+            success_suffix: dialoguer::console::style(String::new()),
+            prompt_style: dialoguer::console::Style::new().for_stderr(),
+            prompt_suffix: dialoguer::console::style(String::new()).for_stderr().black().bright(),
+            active_item_prefix: dialoguer::console::style(">".to_string()).for_stderr().green(),
+            ..Default::default()
+        };
 
         AppUI {
             spinner: RefCell::new(None),
             dialoguer_color_theme: color_theme,
             _cursor_guard: CursorGuard::new(),
+            policy: PromptPolicy::default(),
+            output_format: OutputFormat::default(),
+            messages: MessageTemplates::default(),
+            scripted_answers: None,
         }
     }
 
+    /// 设置交互策略
+    pub fn set_policy(&mut self, policy: PromptPolicy) {
+        self.policy = policy;
+    }
+
+    /// 设置查询结果的输出格式
+    pub fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_format = output_format;
+    }
+
+    /// 设置提交信息模板
+    pub fn set_messages(&mut self, messages: MessageTemplates) {
+        self.messages = messages;
+    }
+
+    /// 使用预设答案代替终端交互，用于测试
+    #[cfg(test)]
+    pub fn with_answers(answers: &[&str]) -> Self {
+        let mut ui = Self::new();
+        ui.scripted_answers = Some(RefCell::new(answers.iter().map(|a| a.to_string()).collect()));
+        ui
+    }
+
     /// 打印普通信息
     pub fn info(&self, msg: &str) {
         self.print_safe(format!("{} {}", "[INFO]".blue().bold(), msg));
@@ -82,6 +122,13 @@ impl AppUI {
 
     /// list 显示
     pub fn show_project_list(&self, active_projects: Vec<ProjectInfo>, deleted_projects: Option<Vec<ProjectInfo>>) {
+        if self.output_format != OutputFormat::Text {
+            let mut projects = active_projects;
+            projects.extend(deleted_projects.unwrap_or_default());
+            self.print_records(&projects);
+            return;
+        }
+
         let mut table = self.create_clean_table();
 
         let hander_cell1 = Cell::new("  PROJECT NAME").fg(comfy_table::Color::DarkGrey);
@@ -135,83 +182,488 @@ impl AppUI {
         self.print_safe(format!("{}", table));
     }
 
+    /// 显示工作区状态
+    /// - 冲突、待提交的更改、switched 和被 .gitignore 忽略的条目分组显示
+    pub fn show_status(&self, status: &WorkspaceStatus) {
+        if self.output_format != OutputFormat::Text {
+            self.print_record(status);
+            return;
+        }
+        self.finish_step();
+
+        println!("{} {}  {} {}  {} {}",
+            "Project".dark_grey(), status.project.clone().yellow().bold(),
+            "Branch".dark_grey(), status.branch.clone().green().bold(),
+            "Revision".dark_grey(), format!("r{}", status.revision).yellow());
+        if status.is_review {
+            self.warn(&format!("Review mode: the workspace is at r{}, the latest revision is r{}", status.revision, status.latest_revision));
+        }
+
+        let group = |kinds: &[StatusKind]| -> Vec<&StatusEntry> {
+            status.entries.iter().filter(|e| kinds.contains(&e.kind)).collect()
+        };
+        let conflicted = group(&[StatusKind::Conflicted]);
+        let changes = group(&[StatusKind::Added, StatusKind::Modified, StatusKind::Deleted]);
+        let switched = group(&[StatusKind::Switched]);
+        let ignored = group(&[StatusKind::Ignored]);
+
+        if conflicted.is_empty() && changes.is_empty() {
+            println!("Nothing to commit, working copy clean");
+        }
+        for (title, entries) in [
+            ("Conflicts (resolve them before committing):", conflicted),
+            ("Changes to be committed:", changes),
+            ("Switched:", switched),
+            ("Ignored by .gitignore:", ignored),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            println!("{}", title.bold());
+            for entry in entries {
+                println!("  {}", self.format_status_entry(entry));
+            }
+        }
+    }
+
+    /// 显示 diff，stat 为 true 时只显示每个文件增删的行数
+    pub fn show_diff(&self, diffs: &[FileDiff], stat: bool) {
+        if self.output_format != OutputFormat::Text {
+            self.print_records(diffs);
+            return;
+        }
+        self.finish_step();
+
+        if diffs.is_empty() {
+            println!("No differences");
+            return;
+        }
+
+        if stat {
+            let path_width = diffs.iter().map(|d| d.path.chars().count()).max().unwrap_or(0);
+            let count_width = diffs.iter().map(|d| (d.added + d.removed).to_string().len()).max().unwrap_or(1);
+            for diff in diffs {
+                // 和 git 一样，最多显示 50 个 +/-
+                let total = diff.added + diff.removed;
+                let scale = |n: usize| if total > 50 { (n * 50).div_ceil(total) } else { n };
+                println!(" {:<path_width$} | {:>count_width$} {}{}",
+                    diff.path, total,
+                    "+".repeat(scale(diff.added)).green(),
+                    "-".repeat(scale(diff.removed)).red());
+            }
+            let added: usize = diffs.iter().map(|d| d.added).sum();
+            let removed: usize = diffs.iter().map(|d| d.removed).sum();
+            println!(" {} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)", diffs.len(), added, removed);
+            return;
+        }
+
+        for diff in diffs {
+            self.print_file_diff(diff);
+        }
+    }
+
+    /// 显示一个版本的详细信息：作者、时间、完整的提交信息、更改的路径和 diff
+    pub fn show_revision(&self, detail: &RevisionDetail) {
+        if self.output_format != OutputFormat::Text {
+            self.print_record(detail);
+            return;
+        }
+        self.finish_step();
+
+        let entry = &detail.entry;
+        let current = if entry.is_current { " (current)".green().to_string() } else { String::new() };
+        println!("{} {}{}", "Revision".dark_grey(), format!("r{}", entry.revision).yellow().bold(), current);
+        println!("{}   {}", "Author".dark_grey(), entry.author.as_str().cyan());
+        println!("{}     {}", "Date".dark_grey(), format_full_time(&entry.date));
+        if entry.kind != LogEntryKind::Commit {
+            let mut kind = self.format_log_message(entry);
+            // 挑选的版本已经显示在种类中
+            if !detail.merged_revisions.is_empty() && !matches!(entry.kind, LogEntryKind::Pick { .. }) {
+                let revisions: Vec<String> = detail.merged_revisions.iter().map(|r| format!("r{}", r)).collect();
+                kind = format!("{} ({})", kind, revisions.join(", "));
+            }
+            println!("{}     {}", "Kind".dark_grey(), kind);
+        }
+
+        println!();
+        for line in entry.message.lines() {
+            println!("    {}", line);
+        }
+        println!();
+
+        if !detail.paths.is_empty() {
+            println!("{}", "Changed paths:".bold());
+            for path in &detail.paths {
+                let action = match path.action.as_str() {
+                    "A" => "A".green(),
+                    "D" => "D".red(),
+                    "R" => "R".cyan(),
+                    other => other.yellow(),
+                };
+                let source = match (&path.copy_from, path.copy_from_revision) {
+                    (Some(from), Some(rev)) => format!(" (from {}@r{})", from, rev).dark_grey().to_string(),
+                    _ => String::new(),
+                };
+                println!("  {}  {}{}", action, path.path, source);
+            }
+            println!();
+        }
+
+        for diff in &detail.diff {
+            self.print_file_diff(diff);
+        }
+    }
+
     /// log 显示
     pub fn show_log(&self, log_entries: Vec<LogEntry>) {
-        let mut table = self.create_clean_table();
+        if self.output_format != OutputFormat::Text {
+            self.print_records(&log_entries);
+            return;
+        }
 
-        let hander_cell1 = Cell::new("  REV").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
-        let hander_cell2 = Cell::new("DATE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
-        let hander_cell3 = Cell::new("MESSAGE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        self.print_safe(format!("{}", self.create_log_table(&log_entries)));
+    }
 
+    /// 显示 ws log --graph 的分支图
+    pub fn show_log_graph(&self, commits: Vec<GraphCommit>) {
+        if self.output_format != OutputFormat::Text {
+            self.print_records(&commits);
+            return;
+        }
+
+        let lines = layout_graph(&commits);
+        let width = lines.iter().map(|line| line.graph.len()).max().unwrap_or(0);
+        let mut output = Vec::new();
+        for line in lines {
+            let graph = format!("{:<width$}", line.graph, width = width);
+            let Some(index) = line.commit else {
+                output.push(graph.dark_grey().to_string());
+                continue;
+            };
+
+            let commit = &commits[index];
+            let log = &commit.entry;
+            let revision = if log.is_current {
+                format!("> r{}", log.revision).green().bold()
+            } else {
+                format!("  r{}", log.revision).yellow()
+            };
+            // 提交信息只显示第一行
+            let message = match log.kind {
+                LogEntryKind::Commit => log.message.lines().next().unwrap_or("").to_string().yellow().to_string(),
+                _ => self.format_log_message(log),
+            };
+            output.push(format!("{} {}  {}  {}  {}  {}",
+                graph.replace('*', &"*".bold().to_string()),
+                revision,
+                format!("[{}]", commit.branch).green(),
+                message,
+                format_relative_time(&log.date).dark_grey(),
+                log.author.clone().dark_cyan(),
+            ));
+        }
+        self.print_safe(output.join("\n"));
+    }
+
+    /// 显示 ws branch --compare 的结果
+    pub fn show_branch_comparison(&self, comparison: &BranchComparison) {
+        if self.output_format != OutputFormat::Text {
+            self.print_record(comparison);
+            return;
+        }
+
+        let (base, branch) = (comparison.base.as_str(), comparison.branch.as_str());
+        let mut output = vec![format!("{} is {} and {} {}",
+            branch.yellow().bold(),
+            format!("{} ahead", comparison.ahead.len()).green().bold(),
+            format!("{} behind", comparison.behind.len()).red().bold(),
+            base.yellow().bold(),
+        )];
+        for (entries, from, to) in [(&comparison.ahead, branch, base), (&comparison.behind, base, branch)] {
+            output.push(String::new());
+            if entries.is_empty() {
+                output.push(format!("Nothing on {} to merge into {}", from, to).dark_grey().to_string());
+            } else {
+                output.push(format!("{} commit(s) on {} to merge into {}:", entries.len(), from.yellow(), to.yellow()));
+                output.push(format!("{}", self.create_log_table(entries)));
+            }
+        }
+        self.print_safe(output.join("\n"));
+    }
+
+    /// 显示标签列表
+    pub fn show_tags(&self, tags: Vec<TagInfo>) {
+        if self.output_format != OutputFormat::Text {
+            self.print_records(&tags);
+            return;
+        }
+        if tags.is_empty() {
+            self.info("No tags yet, create one with ws tag <name>");
+            return;
+        }
+
+        let mut table = self.create_clean_table();
+
+        let hander_cell1 = Cell::new("  TAG").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell2 = Cell::new("SOURCE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell3 = Cell::new("CREATED").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
         table.set_header([hander_cell1, hander_cell2, hander_cell3]);
 
         for column in table.column_iter_mut() {
             column.set_padding((0, 3));
         }
 
-        for log in log_entries {
-            // 1. 版本号
-            let mut c_rev = if log.is_rollback {
-                Cell::new(log.revision).add_attribute(comfy_table::Attribute::Italic).fg(comfy_table::Color::DarkYellow)
+        for tag in tags {
+            let c_name = if tag.is_current {
+                Cell::new(format!("> {}", tag.name)).fg(comfy_table::Color::Green).add_attribute(comfy_table::Attribute::Bold)
+            } else if tag.is_rollback {
+                Cell::new(format!("  {}", tag.name)).fg(comfy_table::Color::DarkYellow).add_attribute(comfy_table::Attribute::Italic)
             } else {
-                Cell::new(log.revision).fg(comfy_table::Color::Yellow)
+                Cell::new(format!("  {}", tag.name)).fg(comfy_table::Color::Magenta)
             };
 
-            c_rev = if log.is_current {
-                c_rev.add_attribute(comfy_table::Attribute::Bold).fg(comfy_table::Color::Green)
-            } else {
-                c_rev
+            let source = match (&tag.source, tag.source_revision) {
+                (Some(branch), Some(revision)) => format!("{}@r{}", branch, revision),
+                (None, Some(revision)) => format!("r{}", revision),
+                _ => "-".to_string(),
             };
+            let c_source = Cell::new(source).fg(comfy_table::Color::Green);
+            let c_date = Cell::new(format!("{} (r{})", format_relative_time(&tag.date), tag.revision)).fg(comfy_table::Color::DarkGrey);
 
-            // 时间 灰色
-            let c_date = Cell::new(log.date).fg(comfy_table::Color::DarkGrey);
+            table.add_row([c_name, c_source, c_date]);
+        }
 
-            // 消息 (最后一列，is_last_col = true)
-            let c_msg = if log.is_rollback {
-                Cell::new(log.message).add_attribute(comfy_table::Attribute::Italic)
-            }
-            else {
-                Cell::new(log.message).fg(comfy_table::Color::Yellow)
-            };
+        self.print_safe(format!("{}", table));
+    }
 
-            table.add_row([c_rev, c_date, c_msg]);
+    /// 显示 stash 列表
+    pub fn show_stashes(&self, stashes: Vec<StashInfo>) {
+        if self.output_format != OutputFormat::Text {
+            self.print_records(&stashes);
+            return;
+        }
+        if stashes.is_empty() {
+            self.info("No stashes, save local changes with ws stash");
+            return;
+        }
+
+        let mut table = self.create_clean_table();
+
+        let hander_cell1 = Cell::new("  STASH").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell2 = Cell::new("BRANCH").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell3 = Cell::new("MESSAGE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell4 = Cell::new("CREATED").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        table.set_header([hander_cell1, hander_cell2, hander_cell3, hander_cell4]);
+
+        for column in table.column_iter_mut() {
+            column.set_padding((0, 3));
+        }
+
+        for stash in stashes {
+            let c_index = Cell::new(format!("  {}", stash.index)).fg(comfy_table::Color::Yellow);
+            let c_branch = Cell::new(format!("{}@r{}", stash.branch, stash.revision)).fg(comfy_table::Color::Green);
+            let c_message = Cell::new(format!("{} ({} files)", stash.message, stash.entries.len())).fg(comfy_table::Color::Yellow);
+            let c_date = Cell::new(format_relative_time(&stash.date)).fg(comfy_table::Color::DarkGrey);
+            table.add_row([c_index, c_branch, c_message, c_date]);
         }
 
         self.print_safe(format!("{}", table));
     }
 
+    /// 显示一个 stash 保存的文件
+    pub fn show_stash(&self, stash: &StashInfo) {
+        if self.output_format != OutputFormat::Text {
+            self.print_record(stash);
+            return;
+        }
+        self.finish_step();
+
+        println!("{} {}  {} {}  {} {}",
+            "Stash".dark_grey(), stash.index.to_string().yellow().bold(),
+            "Branch".dark_grey(), stash.branch.clone().green().bold(),
+            "Revision".dark_grey(), format!("r{}", stash.revision).yellow());
+        println!("{}    {}", "Date".dark_grey(), format_full_time(&stash.date));
+        println!();
+        println!("    {}", stash.message);
+        println!();
+        for entry in &stash.entries {
+            println!("  {}", self.format_status_entry(entry));
+        }
+        for directory in &stash.directories {
+            println!("  {}  {}", "A".green(), format!("{}/", directory).green());
+        }
+        for properties in &stash.properties {
+            println!("  {}  {}{}", "M".yellow(), properties.path.clone().yellow(), " (properties)".dark_grey());
+        }
+    }
+
     /// 选择 yes/no
-    pub fn selector_yes_or_no(&self, prompt: &str) -> AppResult<bool> {
-        let items = vec!["Yes", "No"];
-        let result = if let Some(pb_info) = &self.spinner.borrow().as_ref() {
-            let message = pb_info.get_current_message();
-            pb_info.pb.suspend(|| {
-                self.get_selector_result(prompt, items, Some(&message))
-            })?
-        } else {
-            self.get_selector_result(prompt, items, None)?
-        };
+    pub fn selector_yes_or_no(&self, kind: PromptKind, prompt: &str) -> AppResult<bool> {
+        let result = self.selector(kind, prompt, vec![(PromptChoice::Yes, "Yes"), (PromptChoice::No, "No")])?;
         Ok(result == 0)
     }
 
     /// 选择器，返回选中项的索引
-    pub fn selector(&self, prompt: &str, items: Vec<&str>) -> AppResult<usize> {
+    /// - 每一项为 (选项的含义, 显示的文字)，策略按含义选择选项
+    /// - 先按 kind 查找交互策略，非交互模式下缺少策略时报错
+    pub fn selector(&self, kind: PromptKind, prompt: &str, choices: Vec<(PromptChoice, &str)>) -> AppResult<usize> {
+        if let Some(answer) = self.policy.select_answer(kind)? {
+            let index = choices.iter().position(|(choice, _)| *choice == answer).ok_or(
+                AppError::Validation(format!("The given policy does not apply to prompt: {}", prompt))
+            )?;
+            self.info(&format!("{} {}", prompt, choices[index].1));
+            return Ok(index);
+        }
+        let items: Vec<&str> = choices.into_iter().map(|(_, label)| label).collect();
+        self.policy.ensure_interactive(kind, prompt)?;
+
+        if let Some(answer) = self.next_scripted_answer(prompt)? {
+            return items.iter().position(|item| item.starts_with(answer.as_str())).ok_or(
+                AppError::Validation(format!("Scripted answer '{}' does not match any option of: {}", answer, prompt))
+            );
+        }
+
         if let Some(pb_info) = &self.spinner.borrow().as_ref() {
             let message = pb_info.get_current_message();
-            return pb_info.pb.suspend(|| {
+            pb_info.pb.suspend(|| {
                 self.get_selector_result(prompt, items, Some(&message))
-            });
+            })
         } else {
             self.get_selector_result(prompt, items, None)
         }
     }
 
+    /// 多选，返回选中项的下标，defaults 为默认选中的项
+    /// - 预设答案用逗号分隔，每一段匹配一个选项的前缀
+    pub fn multi_selector(&self, kind: PromptKind, prompt: &str, items: &[String], defaults: &[bool]) -> AppResult<Vec<usize>> {
+        self.policy.ensure_interactive(kind, prompt)?;
+
+        if let Some(answer) = self.next_scripted_answer(prompt)? {
+            let mut selected = Vec::new();
+            for part in answer.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                let index = items.iter().position(|item| item.starts_with(part)).ok_or(
+                    AppError::Validation(format!("Scripted answer '{}' does not match any option of: {}", part, prompt))
+                )?;
+                selected.push(index);
+            }
+            selected.sort_unstable();
+            selected.dedup();
+            return Ok(selected);
+        }
+
+        let select = || -> AppResult<Vec<usize>> {
+            println!("{} {}", "[INFO]".blue().bold(), prompt);
+            MultiSelect::with_theme(&self.dialoguer_color_theme)
+                .items(items)
+                .defaults(defaults)
+                .interact()
+                .map_err(|e| AppError::Validation(e.to_string()))
+        };
+        if let Some(pb_info) = &self.spinner.borrow().as_ref() {
+            pb_info.pb.suspend(select)
+        } else {
+            select()
+        }
+    }
+
+    /// 显示提交前识别出的改名
+    pub fn show_renames(&self, renames: &[DetectedRename]) {
+        self.info(&format!("Detected {} rename(s), recorded as svn move:", renames.len()));
+        let width = renames.iter().map(|r| r.from.width() + usize::from(r.is_dir)).max().unwrap_or(0);
+        let lines: Vec<String> = renames.iter().map(|rename| {
+            let suffix = if rename.is_dir { "/" } else { "" };
+            let from = format!("{:<width$}", format!("{}{}", rename.from, suffix), width = width);
+            format!("    {}  →  {}  {}", from.red(), format!("{}{}", rename.to, suffix).green(), format!("({}%)", rename.similarity).dark_grey())
+        }).collect();
+        self.print_safe(lines.join("\n"));
+    }
+
+    /// 显示一个文件的修改块，带上编号
+    pub fn show_hunks(&self, path: &str, hunks: &[DiffHunk]) {
+        let mut output = vec![path.bold().to_string()];
+        for (index, hunk) in hunks.iter().enumerate() {
+            for (i, line) in hunk.lines.iter().enumerate() {
+                if i == 0 {
+                    output.push(format!("{} {}", format!("[{}]", index + 1).bold(), line.as_str().cyan()));
+                } else if line.starts_with('+') {
+                    output.push(line.as_str().green().to_string());
+                } else if line.starts_with('-') {
+                    output.push(line.as_str().red().to_string());
+                } else {
+                    output.push(line.clone());
+                }
+            }
+        }
+        self.print_safe(output.join("\n"));
+    }
+
+    /// 显示文件中的冲突：本地版本、共同祖先和仓库版本
+    pub fn show_conflict_regions(&self, path: &str, regions: &[ConflictRegion]) {
+        let mut output = vec![format!("{} {}", path.bold(), format!("({} conflict(s))", regions.len()).dark_grey())];
+        for (index, region) in regions.iter().enumerate() {
+            output.push(format!("{} {}", format!("[{}]", index + 1).bold(), format!("line {}", region.line).cyan()));
+            for (label, lines) in [("mine  ", &region.mine), ("base  ", &region.base), ("theirs", &region.theirs)] {
+                if lines.is_empty() {
+                    // 冲突标记中没有共同祖先时不显示
+                    if label.trim() != "base" {
+                        output.push(format!("{} │", label).dark_grey().to_string());
+                    }
+                    continue;
+                }
+                for line in lines.iter() {
+                    let line = format!("{} │ {}", label, line);
+                    output.push(match label.trim() {
+                        "mine" => line.green().to_string(),
+                        "base" => line.dark_grey().to_string(),
+                        _ => line.red().to_string(),
+                    });
+                }
+            }
+        }
+        self.print_safe(output.join("\n"));
+    }
+
+    /// 显示按冲突规则自动解决的文件
+    /// - 每一行是文件、解决方式和匹配的规则，规则失败、改为询问的文件也列出
+    pub fn show_auto_resolved(&self, resolved: &[(String, &ConflictRule)], failed: &[(String, &ConflictRule)]) {
+        self.info(&format!("Auto-resolved {} conflict(s) by {}:", resolved.len(), CONFLICT_RULES_FILE));
+        let width = resolved.iter().chain(failed).map(|(path, _)| path.width()).max().unwrap_or(0);
+        let line = |path: &str, how: String, rule: &ConflictRule| {
+            format!("    {}  {}  {}", format!("{:<width$}", path.replace('\\', "/"), width = width).yellow(), how, format!("({})", rule.pattern).dark_grey())
+        };
+        let mut lines: Vec<String> = resolved.iter().map(|(path, rule)| {
+            let how = match &rule.resolution {
+                ConflictResolution::Mine => "kept my version".to_string(),
+                ConflictResolution::Theirs => "took their version".to_string(),
+                ConflictResolution::Command(command) => format!("regenerated with `{}`", command),
+            };
+            line(path, how, rule)
+        }).collect();
+        lines.extend(failed.iter().map(|(path, rule)| {
+            let how = match &rule.resolution {
+                ConflictResolution::Command(command) => format!("`{}` failed, resolved by prompt", command),
+                _ => "rule failed, resolved by prompt".to_string(),
+            };
+            line(path, how.red().to_string(), rule)
+        }));
+        self.print_safe(lines.join("\n"));
+    }
+
+    /// 暂停 spinner 执行 f，用于运行编辑器等占用终端的程序
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        if let Some(pb_info) = &self.spinner.borrow().as_ref() {
+            pb_info.pb.suspend(f)
+        } else {
+            f()
+        }
+    }
+
     /// 输入提交信息，如果为空则生成自动信息，不会返回空字符串
     pub fn input_commit_message(&self) -> AppResult<String> {
-        match self.input("Input commit message (Leave empty for auto message):") {
+        match self.input(PromptKind::CommitMessage, "Input commit message (Leave empty for auto message):") {
             Ok(msg) if !msg.trim().is_empty() => Ok(msg),
             Ok(_) => { // Empty message
-                let msg = format!("Auto commit {}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+                let msg = self.messages.auto_commit_message();
                 self.info(&format!("Using auto commit message: {}", msg));
                 Ok(msg)
             },
@@ -220,17 +672,38 @@ impl AppUI {
     }
 
     /// 开启一个 Input，返回输入结果，可以为空 String
-    pub fn input(&self, prompt: &str) -> AppResult<String> {
+    /// - 先按 kind 查找交互策略，非交互模式下缺少策略时报错
+    pub fn input(&self, kind: PromptKind, prompt: &str) -> AppResult<String> {
+        if let Some(answer) = self.policy.input_answer(kind) {
+            return Ok(answer);
+        }
+        self.policy.ensure_interactive(kind, prompt)?;
+
+        if let Some(answer) = self.next_scripted_answer(prompt)? {
+            return Ok(answer);
+        }
+
         if let Some(pb_info) = &self.spinner.borrow().as_ref() {
             let message = pb_info.get_current_message();
-            return pb_info.pb.suspend(|| {
+            pb_info.pb.suspend(|| {
                 self.get_input_result(prompt, Some(&message))
-            });
+            })
         } else {
             self.get_input_result(prompt, None)
         }
     }
 
+    /// 取出下一个预设答案；没有预设答案时返回 None，预设答案用完时报错
+    fn next_scripted_answer(&self, prompt: &str) -> AppResult<Option<String>> {
+        match &self.scripted_answers {
+            Some(answers) => match answers.borrow_mut().pop_front() {
+                Some(answer) => Ok(Some(answer)),
+                None => Err(AppError::Validation(format!("No scripted answer left for prompt: {}", prompt))),
+            },
+            None => Ok(None),
+        }
+    }
+
     /// 开启一个 Input
     fn get_input_result(&self, prompt: &str, message: Option<&str>) -> AppResult<String> {
         let mut stderr_io = io::stderr();
@@ -291,7 +764,7 @@ impl AppUI {
         }
 
         if let Ok(index) = &result {
-            println!("{} {}", "[ OK ]".green().bold(), format!("Choose: {}", items[*index]));
+            println!("{} Choose: {}", "[ OK ]".green().bold(), items[*index]);
         }
         execute!(stderr_io, cursor::Hide).ok();
 
@@ -316,13 +789,163 @@ impl AppUI {
     }
 
     fn print_safe(&self, msg: String) {
+        // 输出 JSON 时 stdout 只留给记录
+        let print = || if self.output_format == OutputFormat::Text { println!("{}", msg) } else { eprintln!("{}", msg) };
         if let Some(pb_info) = &self.spinner.borrow().as_ref() {
-            pb_info.pb.suspend(|| println!("{}", msg));
+            pb_info.pb.suspend(print);
         } else {
-            println!("{}", msg);
+            print();
+        }
+    }
+
+    /// 按输出格式打印单条记录：Json 格式化输出，Jsonl 输出一行
+    fn print_record<T: Serialize>(&self, record: &T) {
+        self.finish_step();
+        let result = match self.output_format {
+            OutputFormat::Jsonl => serde_json::to_string(record),
+            _ => serde_json::to_string_pretty(record),
+        };
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(e) => self.error(&format!("Failed to serialize output: {}", e)),
         }
     }
 
+    /// 按输出格式打印记录：Json 输出一个数组，Jsonl 每行一条
+    fn print_records<T: Serialize>(&self, records: &[T]) {
+        self.finish_step();
+        let result = match self.output_format {
+            OutputFormat::Jsonl => records.iter().map(serde_json::to_string).collect::<Result<Vec<_>, _>>().map(|lines| lines.join("\n")),
+            _ => serde_json::to_string_pretty(records),
+        };
+        match result {
+            Ok(output) if !output.is_empty() => println!("{}", output),
+            Ok(_) => {},
+            Err(e) => self.error(&format!("Failed to serialize output: {}", e)),
+        }
+    }
+
+    /// 带颜色地打印一个文件的 diff
+    fn print_file_diff(&self, diff: &FileDiff) {
+        let mut in_hunk = false;
+        for line in &diff.lines {
+            if line.starts_with("@@") {
+                in_hunk = true;
+                println!("{}", line.as_str().cyan());
+            } else if !in_hunk {
+                println!("{}", line.as_str().bold());
+            } else if line.starts_with('+') {
+                println!("{}", line.as_str().green());
+            } else if line.starts_with('-') {
+                println!("{}", line.as_str().red());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+
+    /// 状态条目的显示文本，ws 自动处理的条目会标注
+    fn format_status_entry(&self, entry: &StatusEntry) -> String {
+        let (mark, path) = match entry.kind {
+            StatusKind::Added => ("A".green(), entry.path.clone().green()),
+            StatusKind::Modified => ("M".yellow(), entry.path.clone().yellow()),
+            StatusKind::Deleted => ("D".red(), entry.path.clone().red()),
+            StatusKind::Conflicted => ("C".red().bold(), entry.path.clone().red().bold()),
+            StatusKind::Switched => ("S".cyan(), entry.path.clone().cyan()),
+            StatusKind::Ignored => ("I".dark_grey(), entry.path.clone().dark_grey()),
+        };
+        let note = match (entry.kind, entry.auto) {
+            (StatusKind::Added, true) => " (new, will be added)".dark_grey().to_string(),
+            (StatusKind::Deleted, true) => " (missing, will be deleted)".dark_grey().to_string(),
+            _ => String::new(),
+        };
+        format!("{}  {}{}", mark, path, note)
+    }
+
+    /// 日志消息的显示文本
+    fn format_log_message(&self, log: &LogEntry) -> String {
+        match &log.kind {
+            LogEntryKind::Commit => log.message.clone().yellow().to_string(),
+            LogEntryKind::Init { project } => format!("Init {}", project.clone().yellow().bold()),
+            LogEntryKind::Branch { source, source_revision } => {
+                format!("{} {}", "⎇ Branch created from".dark_green(), format!("{}@r{}", source, source_revision).green().bold())
+            }
+            LogEntryKind::Merge { source } => format!("{} {}", "⇄ Merged from".dark_cyan(), source.clone().cyan().bold()),
+            LogEntryKind::Pick { source, revisions } => {
+                let revisions: Vec<String> = revisions.iter().map(|r| format!("r{}", r)).collect();
+                format!("{} {} {} {}", "⇄ Picked".dark_cyan(), revisions.join(", ").cyan().bold(), "from".dark_cyan(), source.clone().cyan().bold())
+            }
+            LogEntryKind::Undo { revisions } => {
+                let revisions: Vec<String> = revisions.iter().map(|r| format!("r{}", r)).collect();
+                format!("{} {}", "↶ Undid".dark_yellow(), revisions.join(", ").yellow().bold())
+            }
+            LogEntryKind::Rollback { source_revision, paths } => {
+                let source = source_revision.map(|r| format!("r{}", r)).unwrap_or("Unknown Rev".to_string());
+                if paths.is_empty() {
+                    format!("{} {}", "↩ Reverted from".dark_yellow(), source.yellow().bold())
+                } else {
+                    format!("{} {} {} {}", "↩ Reverted".dark_yellow(), paths.join(", ").yellow(), "from".dark_yellow(), source.yellow().bold())
+                }
+            }
+        }
+    }
+
+    /// 日志表格，ws log 和 ws branch --compare 共用
+    fn create_log_table(&self, log_entries: &[LogEntry]) -> Table {
+        let mut table = self.create_clean_table();
+
+        let hander_cell1 = Cell::new("  REV").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell2 = Cell::new("DATE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell3 = Cell::new("AUTHOR").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+        let hander_cell4 = Cell::new("MESSAGE").fg(comfy_table::Color::DarkGrey).add_attribute(comfy_table::Attribute::Bold);
+
+        table.set_header([hander_cell1, hander_cell2, hander_cell3, hander_cell4]);
+
+        for column in table.column_iter_mut() {
+            column.set_padding((0, 3));
+        }
+
+        for log in log_entries {
+            let is_rollback = matches!(log.kind, LogEntryKind::Rollback { .. });
+            let revision = if log.is_current { format!("> r{}", log.revision) } else { format!("  r{}", log.revision) };
+
+            // 1. 版本号
+            let mut c_rev = if is_rollback {
+                Cell::new(revision).add_attribute(comfy_table::Attribute::Italic).fg(comfy_table::Color::DarkYellow)
+            } else {
+                Cell::new(revision).fg(comfy_table::Color::Yellow)
+            };
+
+            c_rev = if log.is_current {
+                c_rev.add_attribute(comfy_table::Attribute::Bold).fg(comfy_table::Color::Green)
+            } else {
+                c_rev
+            };
+
+            // 时间 灰色
+            let c_date = Cell::new(format_relative_time(&log.date)).fg(comfy_table::Color::DarkGrey);
+
+            // 作者 青色
+            let c_author = Cell::new(&log.author).fg(comfy_table::Color::DarkCyan);
+
+            // 消息 (最后一列，is_last_col = true)，前面是指向该版本的标签
+            let mut message = self.format_log_message(log);
+            if !log.tags.is_empty() {
+                message = format!("{} {}", format!("(tag: {})", log.tags.join(", ")).magenta().bold(), message);
+            }
+            let c_msg = if is_rollback {
+                Cell::new(message).add_attribute(comfy_table::Attribute::Italic)
+            }
+            else {
+                Cell::new(message).fg(comfy_table::Color::Yellow)
+            };
+
+            table.add_row([c_rev, c_date, c_author, c_msg]);
+        }
+
+        table
+    }
+
     /// 创建一个无边框且动态宽度的表格
     fn create_clean_table(&self) -> Table {
         let mut table = Table::new();
@@ -337,11 +960,18 @@ impl AppUI {
         let mut max_branch_name_width = 0;
         let project_name_width = project.name.width();
         if !project.is_deleted {
-            let branchs = project.branches.as_ref().unwrap();
-            let branch_count = branchs.len();
-            for (i, branch) in branchs.iter().enumerate() {
+            // 分支之后是标签
+            let mut rows: Vec<(String, bool, bool)> = project.branches.as_ref().unwrap().iter()
+                .map(|b| match (b.ahead, b.behind) {
+                    (Some(ahead), Some(behind)) => (format!("{} ↑{} ↓{}", b.branch_name, ahead, behind), b.is_current_branch, false),
+                    _ => (b.branch_name.clone(), b.is_current_branch, false),
+                })
+                .collect();
+            rows.extend(project.tags.iter().flatten().map(|t| (format!("{} (tag)", t.name), t.is_current, true)));
+            let row_count = rows.len();
+            for (i, (name, is_current, is_tag)) in rows.into_iter().enumerate() {
                 let is_first_row = i == 0;
-                let is_last_branch = i == branch_count - 1;
+                let is_last_branch = i == row_count - 1;
 
                 // Branch name cell
                 let prefix = if is_first_row {
@@ -352,20 +982,14 @@ impl AppUI {
                     "  ├─ " // 中间的分支
                 };
 
-                let branch_name_display = if branch.is_current_branch {
-                    if is_first_row {
-                        let branch_name = format!("* {}", branch.branch_name);
-                        max_branch_name_width = std::cmp::max(max_branch_name_width, branch_name.width());
-                        Cell::new(branch_name).fg(comfy_table::Color::Yellow).add_attribute(comfy_table::Attribute::Bold)
-                    } else {
-                        let branch_name = format!("{}* {}", prefix, branch.branch_name);
-                        max_branch_name_width = std::cmp::max(max_branch_name_width, branch_name.width());
-                        Cell::new(branch_name).fg(comfy_table::Color::Yellow).add_attribute(comfy_table::Attribute::Bold)
-                    }
+                let branch_name_display = if is_current {
+                    let branch_name = format!("{}* {}", prefix, name);
+                    max_branch_name_width = std::cmp::max(max_branch_name_width, branch_name.width());
+                    Cell::new(branch_name).fg(comfy_table::Color::Yellow).add_attribute(comfy_table::Attribute::Bold)
                 } else {
-                    let branch_name = format!("{}{}", prefix, branch.branch_name);
+                    let branch_name = format!("{}{}", prefix, name);
                     max_branch_name_width = std::cmp::max(max_branch_name_width, branch_name.width());
-                    Cell::new(branch_name)
+                    if is_tag { Cell::new(branch_name).fg(comfy_table::Color::DarkMagenta) } else { Cell::new(branch_name) }
                 };
 
                 if is_first_row {
@@ -2,4 +2,5 @@
 
 pub mod display;
 pub mod models;
+pub mod policy;
 
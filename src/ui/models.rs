@@ -6,26 +6,261 @@ use std::time;
 
 use crossterm::style::Stylize;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 
-use crate::commands::models::BranchInfo;
+use crate::commands::models::{BranchInfo, TagInfo};
 
 /// 表格宽度类型 (项目名称宽度, 分支名称宽度)
 pub type TableWidth = (usize, usize);
 
+/// 输出格式，variant 的注释会显示在 --help 中
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Colored tables
+    #[default]
+    Text,
+    /// A single JSON document
+    Json,
+    /// JSON Lines, one record per line
+    Jsonl,
+}
 
+#[derive(Serialize)]
 pub struct ProjectInfo {
     pub name: String,
     pub is_deleted: bool,
     pub is_current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub branches: Option<Vec<BranchInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<TagInfo>>,
+}
+
+/// 日志条目的种类，由提交信息的 [WS-*] 前缀和合并记录识别
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LogEntryKind {
+    /// 普通提交
+    Commit,
+    /// [WS-INIT-GITIGNORE]，项目最初的提交
+    Init { project: String },
+    /// [WS-BRANCH]，分支从 source@source_revision 创建
+    Branch { source: String, source_revision: u64 },
+    /// 从 source 分支合并过来的提交
+    Merge { source: String },
+    /// [WS-PICK]，从 source 分支挑选的 revisions
+    Pick { source: String, revisions: Vec<u64> },
+    /// [WS-UNDO]，撤销了 revisions 这些提交
+    Undo { revisions: Vec<u64> },
+    /// [WS-ROLLBACK]，还原到 source_revision，paths 不为空时只还原了这些路径
+    Rollback {
+        source_revision: Option<u64>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        paths: Vec<String>,
+    },
 }
 
+#[derive(Serialize)]
 pub struct LogEntry {
-    pub revision: String,
+    pub revision: u64,
+    /// svn 记录的 ISO 8601 时间
     pub date: String,
+    pub author: String,
+    /// 原始提交信息
     pub message: String,
-    pub is_rollback: bool,
+    #[serde(flatten)]
+    pub kind: LogEntryKind,
     pub is_current: bool,
+    /// 指向该版本的标签，只在 ws log 中填写
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// 一次提交中更改的路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedPath {
+    /// A、M、D、R
+    pub action: String,
+    /// 仓库中的路径
+    pub path: String,
+    /// 复制（包括创建分支）的来源路径和版本
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_from_revision: Option<u64>,
+}
+
+/// ws show 的结果
+#[derive(Serialize)]
+pub struct RevisionDetail {
+    #[serde(flatten)]
+    pub entry: LogEntry,
+    /// 合并提交带来的版本，来自 svn log -g
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merged_revisions: Vec<u64>,
+    pub paths: Vec<ChangedPath>,
+    pub diff: Vec<FileDiff>,
+}
+
+/// ws branch --compare 的结果，提交从新到旧
+#[derive(Serialize)]
+pub struct BranchComparison {
+    /// 比较的基准分支
+    pub base: String,
+    pub branch: String,
+    /// branch 中可以合并到 base 的提交，即 ws push -t base 会带过去的提交
+    pub ahead: Vec<LogEntry>,
+    /// base 中可以合并到 branch 的提交
+    pub behind: Vec<LogEntry>,
+}
+
+/// ws log --graph 中的一个提交
+#[derive(Serialize)]
+pub struct GraphCommit {
+    #[serde(flatten)]
+    pub entry: LogEntry,
+    /// 提交所在的分支
+    pub branch: String,
+    /// 连到其他分支的边：分支创建时复制的来源分支，或合并的来源分支
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_branch: Option<String>,
+}
+
+/// 工作区中一个条目在提交时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusKind {
+    Added,
+    Modified,
+    Deleted,
+    /// 未受控且被 .gitignore 忽略，不会提交
+    Ignored,
+    /// 冲突（包括树冲突），需要先解决
+    Conflicted,
+    /// 被 switch 到其他 URL
+    Switched,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusEntry {
+    pub path: String,
+    pub kind: StatusKind,
+    /// 提交时由 ws 自动处理：未受控的文件会被 svn add，缺失的文件会被 svn delete
+    pub auto: bool,
+}
+
+impl StatusEntry {
+    pub fn new(path: &str, kind: StatusKind, auto: bool) -> Self {
+        StatusEntry { path: path.to_string(), kind, auto }
+    }
+}
+
+/// 提交时识别出的改名或移动，路径使用 / 分隔
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedRename {
+    pub from: String,
+    pub to: String,
+    /// 内容相同的行所占的百分比，目录为其中文件的平均值
+    pub similarity: u8,
+    pub is_dir: bool,
+}
+
+/// ws status 的结果
+#[derive(Serialize)]
+pub struct WorkspaceStatus {
+    pub project: String,
+    pub branch: String,
+    pub revision: u64,
+    pub latest_revision: u64,
+    /// 工作副本不在最新版本，处于 Review 模式
+    pub is_review: bool,
+    pub entries: Vec<StatusEntry>,
+}
+
+/// ws diff 中一个文件的 diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    /// 相对于项目根目录的路径，使用 / 分隔
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+    /// 包括 Index: 行在内的统一 diff 文本
+    pub lines: Vec<String>,
+    /// 已经读到 @@ 行，之后的 +/- 行才是内容
+    #[serde(skip)]
+    in_hunk: bool,
+}
+
+impl FileDiff {
+    pub fn new(path: &str) -> Self {
+        FileDiff { path: path.to_string(), added: 0, removed: 0, lines: Vec::new(), in_hunk: false }
+    }
+
+    /// 追加一行 diff，并统计增删的行数
+    pub fn push_line(&mut self, line: &str) {
+        if line.starts_with("@@") {
+            self.in_hunk = true;
+        } else if self.in_hunk && line.starts_with('+') {
+            self.added += 1;
+        } else if self.in_hunk && line.starts_with('-') {
+            self.removed += 1;
+        }
+        self.lines.push(line.to_string());
+    }
+
+    /// 拆分出文本的修改块，属性的修改不算在内
+    pub fn hunks(&self) -> Vec<DiffHunk> {
+        let mut hunks: Vec<DiffHunk> = Vec::new();
+        for line in &self.lines {
+            if line.starts_with("Property changes on:") {
+                break;
+            }
+            if let Some(hunk) = DiffHunk::parse_header(line) {
+                hunks.push(hunk);
+            } else if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push(line.clone());
+            }
+        }
+        hunks
+    }
+}
+
+/// diff 中的一个修改块，行号与 @@ -a,b +c,d @@ 相同，从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    /// 包括 @@ 行
+    pub lines: Vec<String>,
+}
+
+impl DiffHunk {
+    fn parse_header(line: &str) -> Option<Self> {
+        let mut ranges = line.strip_prefix("@@ ")?.split_whitespace();
+        let parse_range = |range: &str| -> Option<(usize, usize)> {
+            match range.split_once(',') {
+                Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+                None => Some((range.parse().ok()?, 1)),
+            }
+        };
+        let (old_start, old_len) = parse_range(ranges.next()?.strip_prefix('-')?)?;
+        let (new_start, new_len) = parse_range(ranges.next()?.strip_prefix('+')?)?;
+        Some(DiffHunk { old_start, old_len, new_start, new_len, lines: vec![line.to_string()] })
+    }
+}
+
+
+/// 冲突文件中 <<<<<<< 与 >>>>>>> 之间的一处冲突
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// <<<<<<< 所在的行号，从 1 开始
+    pub line: usize,
+    pub mine: Vec<String>,
+    /// ||||||| 与 ======= 之间的共同祖先，冲突标记中没有时为空
+    pub base: Vec<String>,
+    pub theirs: Vec<String>,
 }
 
 
@@ -50,7 +285,7 @@ impl SpinnerInfo {
 
         pb.enable_steady_tick(std::time::Duration::from_millis(steady_tick));
         SpinnerInfo {
-            pb: pb,
+            pb,
             _start_time: time::Instant::now(),
             _steady_tick: steady_tick,
             _frames: frames,
@@ -61,7 +296,7 @@ impl SpinnerInfo {
         // let elapsed = self.start_time.elapsed().as_millis() as u64;
         // let frame_index = ((elapsed / self.steady_tick) % ((self.frames.len() - 1) as u64)) as usize;
         // return format!("{} {}", self.frames[frame_index].blue().bold(), self.pb.message());
-        return format!("{} {}", "[WAIT]".cyan().bold(), self.pb.message());
+        format!("{} {}", "[WAIT]".cyan().bold(), self.pb.message())
     }
 }
 
@@ -0,0 +1,191 @@
+//! ### 交互策略
+//!
+//! 由 --non-interactive/--yes、--on-dirty、--conflicts、--message 组成，
+//! 未给出的参数使用配置文件中 [prompt] 的默认值，
+//! AppUI 在打开终端控件之前先按提示的种类查找策略
+
+use crate::core::{config::PromptDefaults, error::{AppError, AppResult}};
+
+/// 交互提示的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    /// 工作区有未提交的更改，对应 --on-dirty
+    DirtyWorkspace,
+    /// 当前项目不是最新版本时是否继续提交，对应 --on-dirty=commit|branch:<name>|abort，
+    /// 其他情况下非交互模式视为继续提交
+    OutdatedCommit,
+    /// 新分支名称，对应 --on-dirty=branch:<name>
+    BranchName,
+    /// 提交信息，对应 --message
+    CommitMessage,
+    /// 冲突文件的处理方式，对应 --conflicts
+    Conflict,
+    /// 是否继续，非交互模式下视为 Yes
+    Confirm,
+    /// 操作失败后是否重试，由策略给出答案时不重试
+    Retry,
+    /// 选择要提交的文件和修改块，只能交互完成
+    Selection,
+}
+
+/// 选择器选项的含义，策略按它选择选项，与显示的文字无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptChoice {
+    /// 提交更改后继续
+    Commit,
+    /// 创建新分支并把更改提交到新分支
+    Branch,
+    /// 暂存更改后继续
+    Stash,
+    /// 丢弃所有更改后继续
+    Discard,
+    /// 保留本地版本
+    KeepMine,
+    /// 使用仓库版本
+    TakeTheirs,
+    Yes,
+    No,
+    /// 没有策略对应的选项，如取消操作、查看差异
+    Other,
+}
+
+/// 工作区有未提交的更改时的处理方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirtyPolicy {
+    /// 提交到当前分支
+    Commit,
+    /// 创建新分支并提交到新分支
+    Branch(String),
+    /// 保存到 stash，见 ws stash
+    Stash,
+    /// 丢弃所有更改
+    Discard,
+    /// 取消操作
+    Abort,
+}
+
+impl DirtyPolicy {
+    /// 解析 --on-dirty 参数：commit | branch:<name> | stash | discard | abort
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "commit" => Ok(DirtyPolicy::Commit),
+            "stash" => Ok(DirtyPolicy::Stash),
+            "discard" => Ok(DirtyPolicy::Discard),
+            "abort" => Ok(DirtyPolicy::Abort),
+            _ => match value.strip_prefix("branch:") {
+                Some(name) if !name.trim().is_empty() => Ok(DirtyPolicy::Branch(name.trim().to_string())),
+                Some(_) => Err("branch name is required, e.g. branch:my-changes".to_string()),
+                None => Err("expected commit, branch:<name>, stash, discard or abort".to_string()),
+            },
+        }
+    }
+}
+
+/// 冲突文件的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// 保留本地版本
+    Mine,
+    /// 使用仓库版本
+    Theirs,
+    /// 取消操作，冲突保持未解决
+    Abort,
+}
+
+impl ConflictPolicy {
+    /// 解析 --conflicts 参数：mine | theirs | abort
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "mine" => Ok(ConflictPolicy::Mine),
+            "theirs" => Ok(ConflictPolicy::Theirs),
+            "abort" => Ok(ConflictPolicy::Abort),
+            _ => Err("expected mine, theirs or abort".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PromptPolicy {
+    /// 不允许打开任何终端控件，缺少策略时直接报错
+    pub non_interactive: bool,
+    pub on_dirty: Option<DirtyPolicy>,
+    pub conflicts: Option<ConflictPolicy>,
+    pub message: Option<String>,
+}
+
+impl PromptPolicy {
+    /// 用配置中的默认值补全命令行没有给出的策略
+    pub fn with_defaults(self, defaults: &PromptDefaults) -> Self {
+        PromptPolicy {
+            non_interactive: self.non_interactive || defaults.non_interactive,
+            on_dirty: self.on_dirty.or_else(|| defaults.on_dirty.clone()),
+            conflicts: self.conflicts.or(defaults.conflicts),
+            message: self.message,
+        }
+    }
+
+    /// 选择器的策略答案，返回应当选择的选项
+    /// - 策略为 abort 时返回 OperationCancelled
+    /// - 没有对应策略时返回 None
+    pub fn select_answer(&self, kind: PromptKind) -> AppResult<Option<PromptChoice>> {
+        let answer = match kind {
+            PromptKind::DirtyWorkspace => match &self.on_dirty {
+                Some(DirtyPolicy::Commit) => Some(PromptChoice::Commit),
+                Some(DirtyPolicy::Branch(_)) => Some(PromptChoice::Branch),
+                Some(DirtyPolicy::Stash) => Some(PromptChoice::Stash),
+                Some(DirtyPolicy::Discard) => Some(PromptChoice::Discard),
+                Some(DirtyPolicy::Abort) => return Err(AppError::OperationCancelled),
+                None => None,
+            },
+            PromptKind::OutdatedCommit => match &self.on_dirty {
+                Some(DirtyPolicy::Commit) => Some(PromptChoice::Commit),
+                Some(DirtyPolicy::Branch(_)) => Some(PromptChoice::Branch),
+                Some(DirtyPolicy::Abort) => return Err(AppError::OperationCancelled),
+                // stash 和 discard 不适用于这里，要提交的正是这些更改
+                _ if self.non_interactive => Some(PromptChoice::Commit),
+                _ => None,
+            },
+            PromptKind::Conflict => match self.conflicts {
+                Some(ConflictPolicy::Mine) => Some(PromptChoice::KeepMine),
+                Some(ConflictPolicy::Theirs) => Some(PromptChoice::TakeTheirs),
+                Some(ConflictPolicy::Abort) => return Err(AppError::OperationCancelled),
+                None => None,
+            },
+            PromptKind::Confirm if self.non_interactive => Some(PromptChoice::Yes),
+            // 分支名来自策略时，重试只会得到同样的名字
+            PromptKind::Retry if matches!(self.on_dirty, Some(DirtyPolicy::Branch(_))) => Some(PromptChoice::No),
+            _ => None,
+        };
+        Ok(answer)
+    }
+
+    /// 输入框的策略答案，没有对应策略时返回 None
+    pub fn input_answer(&self, kind: PromptKind) -> Option<String> {
+        match kind {
+            PromptKind::CommitMessage => self.message.clone(),
+            PromptKind::BranchName => match &self.on_dirty {
+                Some(DirtyPolicy::Branch(name)) => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// 非交互模式下，确认需要打开终端控件；否则返回缺少策略的错误
+    pub fn ensure_interactive(&self, kind: PromptKind, prompt: &str) -> AppResult<()> {
+        if !self.non_interactive {
+            return Ok(());
+        }
+
+        let hint = match kind {
+            PromptKind::DirtyWorkspace => "use --on-dirty=commit|branch:<name>|stash|discard|abort",
+            PromptKind::OutdatedCommit => "use --on-dirty=commit|branch:<name>|abort",
+            PromptKind::BranchName => "use --on-dirty=branch:<name>",
+            PromptKind::CommitMessage => "use --message",
+            PromptKind::Conflict => "use --conflicts=mine|theirs|abort",
+            PromptKind::Confirm | PromptKind::Retry => "run it interactively",
+            PromptKind::Selection => "pass the paths to commit instead",
+        };
+        Err(AppError::PromptRequired { prompt: prompt.to_string(), hint: hint.to_string() })
+    }
+}